@@ -13,13 +13,14 @@
 //!   and then copies it to the `/bin` folder.
 // 
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use clap::Parser;
-use log::{info, LevelFilter};
+use log::{error, info, LevelFilter};
 use simplelog::{ColorChoice, TermLogger, TerminalMode};
 
 use rust_build::{Cache, Builder, Installer, TargetBuilder};
+use rust_build::spec::{Architecture, OperatingSystem};
 use rust_build_std::targets::CargoTarget;
 use rust_build_std::targets::cargo::CargoMode;
 
@@ -50,7 +51,7 @@ fn main() {
     info!("Hello World Installer v{}", env!("CARGO_PKG_VERSION"));
 
     // Define an installer, or at least, the start of it.
-    let cache       : Rc<Cache> = Rc::new(Cache::new("./target/make_cache", true).unwrap());
+    let cache       : Arc<Cache> = Arc::new(Cache::new("./target/make_cache", true).unwrap());
     let mut builder : Builder   = Installer::builder();
 
     // We have to define so-called _targets_ to build to. This is effectively a single step in the building process.
@@ -67,4 +68,12 @@ fn main() {
 
     // We can then add the builder
     builder = builder.add_target(target);
+
+    // Finally, we can build the installer and use it to build the target (and anything it depends on) for this machine.
+    // Thanks to the cache, running the installer again only rebuilds the binary if it has changed.
+    let installer: Installer = builder.build();
+    if let Err(err) = installer.build("hello-world", OperatingSystem::host(), Architecture::host(), false, false) {
+        error!("{}", err);
+        std::process::exit(1);
+    }
 }
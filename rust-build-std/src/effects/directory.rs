@@ -0,0 +1,264 @@
+//  DIRECTORY.rs
+//    by Lut99
+// 
+//  Created:
+//    15 Oct 2026, 13:52:17
+//  Last edited:
+//    15 Oct 2026, 13:52:17
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines an effect that represents a whole directory tree, which
+//!   has changed if any of the files in it has.
+// 
+
+use std::any::Any;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rust_build::spec::{Effect, Named};
+use rust_build::cache::{Cache, CacheGuard, DirectoryEntry, LastEditedTime};
+
+use crate::trace;
+
+
+/***** ERRORS *****/
+/// Defines errors that relate to the Directory.
+#[derive(Debug)]
+pub enum Error {
+    /// The directory was not found.
+    DirNotFound{ path: PathBuf },
+    /// Failed to read the entries of a (nested) directory.
+    DirReadError{ path: PathBuf, err: std::io::Error },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            DirNotFound{ path }       => write!(f, "Dependency directory '{}' not found (did a previous target fail?)", path.display()),
+            DirReadError{ path, err } => write!(f, "Failed to read directory '{}': {}", path.display(), err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Matches a single path segment against a single pattern segment, where `*` matches any sequence of characters and `?` matches any single character.
+/// 
+/// # Arguments
+/// - `pattern`: The pattern segment to match with.
+/// - `segment`: The path segment to match.
+/// 
+/// # Returns
+/// Whether the segment matches the pattern.
+fn segment_match(pattern: &[char], segment: &[char]) -> bool {
+    match (pattern.first(), segment.first()) {
+        (None, None)         => true,
+        (Some('*'), _)       => segment_match(&pattern[1..], segment) || (!segment.is_empty() && segment_match(pattern, &segment[1..])),
+        (Some('?'), Some(_)) => segment_match(&pattern[1..], &segment[1..]),
+        (Some(p), Some(s))   => p == s && segment_match(&pattern[1..], &segment[1..]),
+        _                    => false,
+    }
+}
+
+/// Matches a relative path against a glob pattern, where `**` matches any number of (including zero) segments.
+/// 
+/// # Arguments
+/// - `pattern`: The segments of the pattern to match with.
+/// - `path`: The segments of the path to match.
+/// 
+/// # Returns
+/// Whether the path matches the pattern.
+fn glob_match(pattern: &[&str], path: &[String]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None)       => true,
+        (Some(&"**"), _)   => glob_match(&pattern[1..], path) || (!path.is_empty() && glob_match(pattern, &path[1..])),
+        (Some(p), Some(s)) => segment_match(&p.chars().collect::<Vec<char>>(), &s.chars().collect::<Vec<char>>()) && glob_match(&pattern[1..], &path[1..]),
+        _                  => false,
+    }
+}
+
+
+
+/// Finds the most recent last edited time of the given directory or any file or directory nested in it, together with the number of files and directories nested in it.
+/// 
+/// Symlinks are not followed, such that symlink loops are harmless.
+/// 
+/// # Arguments
+/// - `root`: The path of the tracked directory, to which the ignore patterns are relative.
+/// - `path`: The path of the (nested) directory to search.
+/// - `ignore`: Glob patterns of paths that are skipped (see `Directory::ignore()`).
+/// 
+/// # Returns
+/// A DirectoryEntry with the most recent LastEditedTime in the tree and the number of (non-ignored) entries in it.
+/// 
+/// # Errors
+/// This function errors if we failed to read any of the directories or their metadata.
+fn scan(root: &Path, path: &Path, ignore: &[String]) -> Result<DirectoryEntry, Box<dyn std::error::Error + Send + Sync>> {
+    let mut res: DirectoryEntry = DirectoryEntry{ last_edited: LastEditedTime::from_path(path)?, count: 0 };
+    if !path.is_dir() { return Ok(res); }
+
+    // Recurse into the entries
+    let entries: fs::ReadDir = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(err)    => { return Err(Box::new(Error::DirReadError{ path: path.into(), err })); },
+    };
+    for entry in entries {
+        let entry: fs::DirEntry = match entry {
+            Ok(entry) => entry,
+            Err(err)  => { return Err(Box::new(Error::DirReadError{ path: path.into(), err })); },
+        };
+        let entry_path: PathBuf = entry.path();
+
+        // Skip it if it's ignored
+        let rel: Vec<String> = entry_path.strip_prefix(root).unwrap_or(&entry_path).components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+        if ignore.iter().any(|pattern| glob_match(&pattern.split('/').filter(|s| !s.is_empty()).collect::<Vec<&str>>(), &rel)) { continue; }
+
+        // Count it, and only recurse into actual directories (not symlinks to them)
+        res.count += 1;
+        let is_dir: bool = match entry.file_type() {
+            Ok(file_type) => file_type.is_dir(),
+            Err(err)      => { return Err(Box::new(Error::DirReadError{ path: entry_path, err })); },
+        };
+        let nested: DirectoryEntry = if is_dir {
+            scan(root, &entry_path, ignore)?
+        } else {
+            match fs::symlink_metadata(&entry_path) {
+                Ok(metadata) => DirectoryEntry{ last_edited: LastEditedTime::from_metadata(&metadata), count: 0 },
+                Err(err)     => { return Err(Box::new(Error::DirReadError{ path: entry_path, err })); },
+            }
+        };
+        res.last_edited = res.last_edited.max(nested.last_edited);
+        res.count += nested.count;
+    }
+    Ok(res)
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// A Directory is an Effect that represents a whole directory tree (e.g., generated documentation).
+/// 
+/// It is considered changed if the most recent last edited time of the directory or anything in it, or the number of files in it, differs from the one in the cache. Paths matching any of its ignore patterns (by default, `target/**` and `.git/**`) are not considered.
+#[derive(Debug, Clone)]
+pub struct Directory {
+    /// The name of this directory.
+    name  : String,
+    /// The Cache that we use to discover if the directory has changed since last checks.
+    cache : Arc<Cache>,
+
+    /// The path of the directory this Effect concerns itself about.
+    pub path   : PathBuf,
+    /// Glob patterns (relative to `path`) of files and directories that are excluded from the change computation.
+    pub ignore : Vec<String>,
+}
+
+impl Directory {
+    /// Constructor for the Directory effect.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of this Directory.
+    /// - `cache`: The Cache to use to keep track of this directory's changed status.
+    /// - `path`: The path of the directory that this effect tracks.
+    /// 
+    /// # Returns
+    /// A new Directory instance.
+    #[inline]
+    pub fn new(name: impl Into<String>, cache: Arc<Cache>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            name : name.into(),
+            cache,
+
+            path   : path.into(),
+            ignore : vec![ "target/**".into(), ".git/**".into() ],
+        }
+    }
+
+
+
+    /// Sets the glob patterns of files and directories that are excluded from the change computation, replacing the defaults (`target/**` and `.git/**`).
+    /// 
+    /// Patterns are matched against paths relative to the tracked directory, and support `*` and `?` within a path segment and `**` for any number of segments (e.g., `**/*.swp`).
+    /// 
+    /// # Arguments
+    /// - `patterns`: The glob patterns to ignore.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn ignore(mut self, patterns: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.ignore = patterns.into_iter().map(|p| p.into()).collect();
+        self
+    }
+}
+
+impl Named for Directory {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+
+impl Effect for Directory {
+    fn has_changed(&self) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        // A directory that does not exist yet has yet to be produced
+        if !self.path.exists() {
+            trace!("{}: Marking '{}' as changed (directory does not exist yet)", self.name(), self.path.display());
+            return Ok(true);
+        }
+
+        // Compare the cached time and count with the ones in the tree
+        let entry: DirectoryEntry = match self.cache.get_directory_lenient(&self.path) {
+            Ok(Some(entry)) => entry,
+            Ok(None)        => {
+                trace!("{}: Marking '{}' as changed (no cache entry found)", self.name(), self.path.display());
+                return Ok(true);
+            },
+            Err(err) => { return Err(Box::new(err)); },
+        };
+        let current: DirectoryEntry = scan(&self.path, &self.path, &self.ignore)?;
+        #[cfg(feature = "log")]
+        if entry.last_edited != current.last_edited {
+            trace!("{}: Marking '{}' as changed (last edited time in tree differs from cache)", self.name(), self.path.display());
+        } else if entry.count != current.count {
+            trace!("{}: Marking '{}' as changed (number of files in tree differs from cache)", self.name(), self.path.display());
+        } else {
+            trace!("{}: Marking '{}' as unchanged (same last edited time and number of files as in cache)", self.name(), self.path.display());
+        }
+        Ok(entry != current)
+    }
+
+    fn commit_change(&self, dry_run: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Check if the directory exists
+        if !self.path.exists() { return Err(Box::new(Error::DirNotFound{ path: self.path.clone() })); }
+
+        // Write the most recent last edited time and the number of files to the cache
+        let current: DirectoryEntry = scan(&self.path, &self.path, &self.ignore)?;
+        trace!("{}: Updating cache for directory '{}'", self.name(), self.path.display());
+        let _lock: CacheGuard = self.cache.lock()?;
+        match self.cache.update_directory(&self.path, &current, dry_run) {
+            Ok(_)    => Ok(()),
+            Err(err) => Err(Box::new(err)),
+        }
+    }
+
+    fn last_changed(&self) -> Result<Option<LastEditedTime>, Box<dyn std::error::Error + Send + Sync>> {
+        // A directory that does not exist (yet) has no last changed time
+        if !self.path.exists() { return Ok(None); }
+        scan(&self.path, &self.path, &self.ignore).map(|entry| Some(entry.last_edited))
+    }
+
+
+
+    #[inline]
+    fn as_any(&self) -> &dyn Any { self }
+}
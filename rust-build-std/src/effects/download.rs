@@ -0,0 +1,548 @@
+//  DOWNLOAD.rs
+//    by Lut99
+// 
+//  Created:
+//    15 Oct 2026, 12:41:09
+//  Last edited:
+//    15 Oct 2026, 12:41:09
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines an effect that represents a file downloaded from some
+//!   (HTTP) server, which is only considered changed if the remote file
+//!   is.
+// 
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs as _};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rust_build::spec::{Effect, Named};
+use rust_build::cache::{Cache, CacheGuard, DownloadEntry};
+use rust_build::shell::deadline;
+
+use crate::{debug, trace};
+
+
+/***** CONSTANTS *****/
+/// The default time that connecting to the server, or a single read from or write to it, may take.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+
+
+
+
+/***** ERRORS *****/
+/// Defines errors that relate to the RemoteFile.
+#[derive(Debug)]
+pub enum Error {
+    /// The given URL is not a (supported) URL.
+    UnsupportedUrl{ url: String },
+    /// Failed to connect to the server.
+    ConnectError{ url: String, err: std::io::Error },
+    /// Failed to send the request or read the response.
+    RequestError{ url: String, err: std::io::Error },
+    /// The server sent a response we did not understand.
+    ResponseParseError{ url: String, reason: String },
+    /// The server sent the response in a transfer encoding we do not support.
+    UnsupportedTransferEncoding{ url: String, encoding: String },
+    /// The server responded with an unexpected status code.
+    HttpError{ url: String, code: u16 },
+    /// The server resumed a download at another offset than the one we asked for.
+    RangeMismatch{ url: String, offset: u64, range: Option<String> },
+    /// The server did not respond in time (or the deadline of the build passed).
+    Timeout{ url: String },
+
+    /// The downloaded file was not found.
+    FileNotFound{ path: PathBuf },
+    /// Failed to read or write the downloaded file.
+    FileError{ path: PathBuf, err: std::io::Error },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use Error::*;
+        match self {
+            UnsupportedUrl{ url }                        => write!(f, "Unsupported URL '{}' (only plain 'http://' URLs are supported)", url),
+            ConnectError{ url, err }                     => write!(f, "Failed to connect to '{}': {}", url, err),
+            RequestError{ url, err }                     => write!(f, "Failed to request '{}': {}", url, err),
+            ResponseParseError{ url, reason }            => write!(f, "Failed to parse response of '{}': {}", url, reason),
+            UnsupportedTransferEncoding{ url, encoding } => write!(f, "Response of '{}' has unsupported transfer encoding '{}'", url, encoding),
+            HttpError{ url, code }                       => write!(f, "Request to '{}' failed with status code {}", url, code),
+            RangeMismatch{ url, offset, range }          => write!(f, "Response of '{}' does not resume at byte {} (got range '{}')", url, offset, range.as_deref().unwrap_or("<none>")),
+            Timeout{ url }                               => write!(f, "Request to '{}' timed out", url),
+
+            FileNotFound{ path }    => write!(f, "Downloaded file '{}' not found (did a previous target fail?)", path.display()),
+            FileError{ path, err }  => write!(f, "Failed to access downloaded file '{}': {}", path.display(), err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns how long a single network operation may take, given the timeout of the RemoteFile and the deadline of the current build (see `Installer::build_with_deadline()`).
+/// 
+/// # Arguments
+/// - `url`: The URL that is being requested (used for debugging).
+/// - `timeout`: The timeout of the RemoteFile.
+/// 
+/// # Returns
+/// The (non-zero) duration the operation may take.
+/// 
+/// # Errors
+/// This function errors if the deadline of the build has already passed.
+fn op_timeout(url: &str, timeout: Duration) -> Result<Duration, Error> {
+    let timeout: Duration = match deadline() {
+        Some(deadline) => timeout.min(deadline.saturating_duration_since(Instant::now())),
+        None           => timeout,
+    };
+    if timeout.is_zero() { return Err(Error::Timeout{ url: url.into() }); }
+    Ok(timeout)
+}
+
+/// Maps an error that occurred while talking to the server to a Timeout if that's what it is.
+/// 
+/// # Arguments
+/// - `url`: The URL that is being requested.
+/// - `err`: The error that occurred.
+/// 
+/// # Returns
+/// Either an `Error::Timeout` or an `Error::RequestError`.
+fn request_error(url: &str, err: std::io::Error) -> Error {
+    match err.kind() {
+        ErrorKind::TimedOut | ErrorKind::WouldBlock => Error::Timeout{ url: url.into() },
+        _                                           => Error::RequestError{ url: url.into(), err },
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines the parts of a URL we need to make a request.
+struct Url {
+    /// The host to connect to.
+    host : String,
+    /// The port to connect to.
+    port : u16,
+    /// The path to request.
+    path : String,
+}
+
+impl Url {
+    /// Parses the given URL.
+    /// 
+    /// # Arguments
+    /// - `url`: The URL to parse. Must be a plain `http://` URL.
+    /// 
+    /// # Returns
+    /// A new Url instance.
+    /// 
+    /// # Errors
+    /// This function errors if the URL is not a (supported) URL.
+    fn parse(url: &str) -> Result<Self, Error> {
+        let rest: &str = match url.strip_prefix("http://") {
+            Some(rest) => rest,
+            None       => { return Err(Error::UnsupportedUrl{ url: url.into() }); },
+        };
+
+        // Split the authority from the path, and the host from the port
+        let (authority, path): (&str, &str) = match rest.find('/') {
+            Some(pos) => (&rest[..pos], &rest[pos..]),
+            None      => (rest, "/"),
+        };
+        let (host, port): (&str, u16) = match authority.rsplit_once(':') {
+            Some((host, port)) => match port.parse() {
+                Ok(port) => (host, port),
+                Err(_)   => { return Err(Error::UnsupportedUrl{ url: url.into() }); },
+            },
+            None => (authority, 80),
+        };
+        if host.is_empty() { return Err(Error::UnsupportedUrl{ url: url.into() }); }
+
+        // Done
+        Ok(Self {
+            host : host.into(),
+            port,
+            path : path.into(),
+        })
+    }
+}
+
+
+
+/// Defines a (partially read) response from the server.
+struct Response {
+    /// The status code of the response.
+    code    : u16,
+    /// The headers of the response, with lowercase names.
+    headers : HashMap<String, String>,
+    /// The stream from which the body may be read.
+    body    : BufReader<TcpStream>,
+    /// The timeout of every read from the stream.
+    timeout : Duration,
+}
+
+impl Response {
+    /// Sends a request for the given URL, and reads the response up to (but not including) the body.
+    /// 
+    /// # Arguments
+    /// - `method`: The HTTP method to use (e.g., `GET`).
+    /// - `url`: The URL to request.
+    /// - `headers`: Any additional headers to send.
+    /// - `timeout`: The time that connecting, or a single read or write, may take (limited by the deadline of the build).
+    /// 
+    /// # Returns
+    /// A new Response with the status code and headers read.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to connect, send the request or parse the response, or if the server did not respond in time.
+    fn request(method: &str, url: &str, headers: &[(&str, String)], timeout: Duration) -> Result<Self, Error> {
+        let purl: Url = Url::parse(url)?;
+
+        // Connect (trying every address the host resolves to) & send the request
+        debug!("{} {}", method, url);
+        let addrs: Vec<SocketAddr> = match (purl.host.as_str(), purl.port).to_socket_addrs() {
+            Ok(addrs) => addrs.collect(),
+            Err(err)  => { return Err(Error::ConnectError{ url: url.into(), err }); },
+        };
+        let mut res: Result<TcpStream, std::io::Error> = Err(std::io::Error::new(ErrorKind::NotFound, format!("host '{}' resolved to no addresses", purl.host)));
+        for addr in addrs {
+            res = TcpStream::connect_timeout(&addr, op_timeout(url, timeout)?);
+            if res.is_ok() { break; }
+        }
+        let mut stream: TcpStream = match res {
+            Ok(stream)                                   => stream,
+            Err(err) if err.kind() == ErrorKind::TimedOut => { return Err(Error::Timeout{ url: url.into() }); },
+            Err(err)                                     => { return Err(Error::ConnectError{ url: url.into(), err }); },
+        };
+        let op: Duration = op_timeout(url, timeout)?;
+        if let Err(err) = stream.set_read_timeout(Some(op)).and_then(|_| stream.set_write_timeout(Some(op))) { return Err(Error::ConnectError{ url: url.into(), err }); }
+        let mut request: String = format!("{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n", method, purl.path, purl.host);
+        for (name, value) in headers {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        request.push_str("\r\n");
+        if let Err(err) = stream.write_all(request.as_bytes()) { return Err(request_error(url, err)); }
+
+        // Read the status line
+        let mut body: BufReader<TcpStream> = BufReader::new(stream);
+        let mut line: String = String::new();
+        if let Err(err) = body.read_line(&mut line) { return Err(request_error(url, err)); }
+        let code: u16 = match line.split_whitespace().nth(1).map(|c| c.parse()) {
+            Some(Ok(code)) => code,
+            _              => { return Err(Error::ResponseParseError{ url: url.into(), reason: format!("invalid status line '{}'", line.trim_end()) }); },
+        };
+
+        // Read the headers
+        let mut headers: HashMap<String, String> = HashMap::new();
+        loop {
+            line.clear();
+            match body.read_line(&mut line) {
+                Ok(0)    => { return Err(Error::ResponseParseError{ url: url.into(), reason: "unexpected end of headers".into() }); },
+                Ok(_)    => {},
+                Err(err) => { return Err(request_error(url, err)); },
+            }
+            let header: &str = line.trim_end();
+            if header.is_empty() { break; }
+            match header.split_once(':') {
+                Some((name, value)) => { headers.insert(name.trim().to_lowercase(), value.trim().into()); },
+                None                => { return Err(Error::ResponseParseError{ url: url.into(), reason: format!("invalid header '{}'", header) }); },
+            }
+        }
+
+        // Done
+        trace!("{} {}: {} {:?}", method, url, code, headers);
+        Ok(Self {
+            code,
+            headers,
+            body,
+            timeout,
+        })
+    }
+
+    /// Reads the body of the response into the given writer.
+    /// 
+    /// # Arguments
+    /// - `url`: The URL that was requested (used for debugging).
+    /// - `path`: The path of the file that we write to (used for debugging).
+    /// - `writer`: The Write to write the body to.
+    /// 
+    /// # Errors
+    /// This function errors if the body was in an unsupported transfer encoding, if we failed to read or write it or if the server (or build) ran out of time.
+    fn read_body(mut self, url: &str, path: &Path, writer: &mut impl Write) -> Result<(), Error> {
+        if let Some(encoding) = self.headers.get("transfer-encoding") {
+            if !encoding.eq_ignore_ascii_case("identity") { return Err(Error::UnsupportedTransferEncoding{ url: url.into(), encoding: encoding.clone() }); }
+        }
+
+        // Read either the given length or until the connection closes, making sure no read outlasts the deadline
+        let mut left: Option<u64> = self.headers.get("content-length").and_then(|l| l.parse().ok());
+        let mut buffer: [u8; 8192] = [0; 8192];
+        while left != Some(0) {
+            if let Err(err) = self.body.get_ref().set_read_timeout(Some(op_timeout(url, self.timeout)?)) { return Err(Error::RequestError{ url: url.into(), err }); }
+            let max: usize = left.map(|l| l.min(buffer.len() as u64) as usize).unwrap_or(buffer.len());
+            let n: usize = match self.body.read(&mut buffer[..max]) {
+                Ok(0)    => { break; },
+                Ok(n)    => n,
+                Err(err) => { return Err(request_error(url, err)); },
+            };
+            left = left.map(|l| l - n as u64);
+            if let Err(err) = writer.write_all(&buffer[..n]) { return Err(Error::FileError{ path: path.into(), err }); }
+        }
+        Ok(())
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// A RemoteFile is an Effect that represents a file that is downloaded from a plain HTTP server.
+/// 
+/// It asks the server whether the file has changed since it was last committed by doing a conditional request (using the ETag or Last-Modified time the server reported), so it is only changed if the remote file (or the local copy) is. As such, it is typically used as both an input and an effect of the target that calls `RemoteFile::download()`.
+/// 
+/// Note that only plain `http://` URLs are supported, and the response may not use chunked transfer encoding.
+#[derive(Debug)]
+pub struct RemoteFile {
+    /// The name of this file.
+    name  : String,
+    /// The Cache that we use to remember what we downloaded.
+    cache : Arc<Cache>,
+
+    /// The URL to download the file from.
+    pub url  : String,
+    /// The path to download the file to.
+    pub path : PathBuf,
+    /// The time that connecting to the server, or a single read from or write to it, may take.
+    pub timeout : Duration,
+
+    /// The ETag and Last-Modified time the server reported when we last downloaded the file.
+    validators : Mutex<Option<(Option<String>, Option<String>)>>,
+}
+
+impl RemoteFile {
+    /// Constructor for the RemoteFile.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of this RemoteFile.
+    /// - `cache`: The Cache to use to remember what we downloaded.
+    /// - `url`: The (plain `http://`) URL to download the file from.
+    /// - `path`: The path to download the file to.
+    /// 
+    /// # Returns
+    /// A new RemoteFile instance, which times out after `DEFAULT_TIMEOUT`.
+    #[inline]
+    pub fn new(name: impl Into<String>, cache: Arc<Cache>, url: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            name : name.into(),
+            cache,
+
+            url     : url.into(),
+            path    : path.into(),
+            timeout : DEFAULT_TIMEOUT,
+
+            validators : Mutex::new(None),
+        }
+    }
+
+    /// Sets the time that connecting to the server, or a single read from or write to it, may take.
+    /// 
+    /// Regardless, requests are aborted once the deadline of the current build passes (see `Installer::build_with_deadline()`).
+    /// 
+    /// # Arguments
+    /// - `timeout`: The new timeout.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+
+
+    /// Returns the path where partial downloads are kept until they are complete.
+    #[inline]
+    pub fn part_path(&self) -> PathBuf {
+        let mut path: PathBuf = self.path.clone();
+        path.as_mut_os_string().push(".part");
+        path
+    }
+
+    /// Returns the path where the ETag of a partial download is kept, such that it is only resumed if the remote file is still the same.
+    #[inline]
+    pub fn part_etag_path(&self) -> PathBuf {
+        let mut path: PathBuf = self.part_path();
+        path.as_mut_os_string().push(".etag");
+        path
+    }
+
+
+
+    /// Downloads the file to its path.
+    /// 
+    /// If a partial download is found (see `RemoteFile::part_path()`), the download is resumed using an HTTP Range request (if the server supports it). This is only done if the server reported a (strong) ETag when the download was started, which is sent along in an `If-Range` header such that the server sends the whole file instead if it has changed since.
+    /// 
+    /// # Arguments
+    /// - `dry_run`: If 'true', prints what would be done instead of actually downloading.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to download the file or to write it to disk.
+    pub fn download(&self, dry_run: bool) -> Result<(), Error> {
+        if dry_run {
+            println!("[dry_run] Would download '{}' to '{}'", self.url, self.path.display());
+            return Ok(());
+        }
+
+        // Resume any partial download of which we know the version
+        let part: PathBuf = self.part_path();
+        let part_etag: PathBuf = self.part_etag_path();
+        let (offset, headers): (u64, Vec<(&str, String)>) = match (fs::metadata(&part), fs::read_to_string(&part_etag)) {
+            (Ok(meta), Ok(etag)) if meta.len() > 0 => (meta.len(), vec![ ("Range", format!("bytes={}-", meta.len())), ("If-Range", etag) ]),
+            _                                      => (0, vec![]),
+        };
+        let res: Response = Response::request("GET", &self.url, &headers, self.timeout)?;
+        let validators: (Option<String>, Option<String>) = (res.headers.get("etag").cloned(), res.headers.get("last-modified").cloned());
+
+        // Write the body as appropriate
+        let resume: bool = match res.code {
+            200 => {
+                // Remember the version we're downloading, such that we can resume it if we're interrupted (weak ETags may not be used for that)
+                let written: std::io::Result<()> = match &validators.0 {
+                    Some(etag) if !etag.starts_with("W/") => fs::write(&part_etag, etag),
+                    _                                     => fs::remove_file(&part_etag).or_else(|err| if err.kind() == ErrorKind::NotFound { Ok(()) } else { Err(err) }),
+                };
+                if let Err(err) = written { return Err(Error::FileError{ path: part_etag, err }); }
+                false
+            },
+            206 => {
+                // Make sure the server resumes where we left off
+                let range: Option<&String> = res.headers.get("content-range");
+                if range.and_then(|r| r.strip_prefix("bytes ")).and_then(|r| r.split_once('-')).and_then(|(start, _)| start.trim().parse::<u64>().ok()) != Some(offset) {
+                    return Err(Error::RangeMismatch{ url: self.url.clone(), offset, range: range.cloned() });
+                }
+                true
+            },
+            // The partial download is already complete
+            416 if offset > 0 => {
+                trace!("{}: Partial download '{}' is already complete", self.name(), part.display());
+                return self.finish_download(&part, &part_etag, validators);
+            },
+            code => { return Err(Error::HttpError{ url: self.url.clone(), code }); },
+        };
+        trace!("{}: Downloading '{}' to '{}' ({})", self.name(), self.url, self.path.display(), if resume { format!("resuming at {}", offset) } else { "from scratch".into() });
+        let mut handle: fs::File = match fs::OpenOptions::new().create(true).write(true).append(resume).truncate(!resume).open(&part) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileError{ path: part, err }); },
+        };
+        res.read_body(&self.url, &part, &mut handle)?;
+        self.finish_download(&part, &part_etag, validators)
+    }
+
+    /// Moves a completed download in place and forgets that it was partial.
+    /// 
+    /// # Arguments
+    /// - `part`: The path of the completed download (see `RemoteFile::part_path()`).
+    /// - `part_etag`: The path of its ETag (see `RemoteFile::part_etag_path()`).
+    /// - `validators`: The ETag and Last-Modified time the server reported for the download.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to move the file.
+    fn finish_download(&self, part: &Path, part_etag: &Path, validators: (Option<String>, Option<String>)) -> Result<(), Error> {
+        if let Err(err) = fs::rename(part, &self.path) { return Err(Error::FileError{ path: self.path.clone(), err }); }
+        if let Err(err) = fs::remove_file(part_etag) {
+            if err.kind() != ErrorKind::NotFound { return Err(Error::FileError{ path: part_etag.into(), err }); }
+        }
+        *self.validators.lock().unwrap_or_else(|err| err.into_inner()) = Some(validators);
+        Ok(())
+    }
+}
+
+impl Named for RemoteFile {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+
+impl Effect for RemoteFile {
+    fn has_changed(&self) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        // If we have no (valid) local copy or do not remember downloading it, we are changed
+        if !self.path.exists() {
+            trace!("{}: Marking as changed (file '{}' does not exist yet)", self.name(), self.path.display());
+            return Ok(true);
+        }
+        let entry: DownloadEntry = match self.cache.get_download(&self.url) {
+            Ok(Some(entry)) => entry,
+            Ok(None)        => {
+                trace!("{}: Marking as changed (no cache entry found)", self.name());
+                return Ok(true);
+            },
+            Err(err) => { return Err(Box::new(err)); },
+        };
+        if Cache::hash_file(&self.path)? != entry.content_hash {
+            trace!("{}: Marking as changed (local file '{}' differs from download)", self.name(), self.path.display());
+            return Ok(true);
+        }
+
+        // Ask the server if anything changed since
+        let mut headers: Vec<(&str, String)> = vec![];
+        if let Some(etag) = entry.etag { headers.push(("If-None-Match", etag)); }
+        if let Some(last_modified) = entry.last_modified { headers.push(("If-Modified-Since", last_modified)); }
+        if headers.is_empty() {
+            trace!("{}: Marking as changed (server did not report an ETag or Last-Modified time)", self.name());
+            return Ok(true);
+        }
+        match Response::request("GET", &self.url, &headers, self.timeout)?.code {
+            304 => {
+                trace!("{}: Marking as unchanged (server reported not modified)", self.name());
+                Ok(false)
+            },
+            200 => {
+                trace!("{}: Marking as changed (server reported modified)", self.name());
+                Ok(true)
+            },
+            code => Err(Box::new(Error::HttpError{ url: self.url.clone(), code })),
+        }
+    }
+
+    fn commit_change(&self, dry_run: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if !self.path.exists() { return Err(Box::new(Error::FileNotFound{ path: self.path.clone() })); }
+
+        // Use the validators of the last download, or ask the server for them if we didn't download
+        let (etag, last_modified): (Option<String>, Option<String>) = match self.validators.lock().unwrap_or_else(|err| err.into_inner()).clone() {
+            Some(validators) => validators,
+            None             => {
+                let res: Response = Response::request("HEAD", &self.url, &[], self.timeout)?;
+                if res.code != 200 { return Err(Box::new(Error::HttpError{ url: self.url.clone(), code: res.code })); }
+                (res.headers.get("etag").cloned(), res.headers.get("last-modified").cloned())
+            },
+        };
+
+        // Write the entry to the cache
+        trace!("{}: Updating cache for download '{}'", self.name(), self.url);
+        let _lock: CacheGuard = self.cache.lock()?;
+        match self.cache.update_download(&self.url, &DownloadEntry{ etag, last_modified, content_hash: Cache::hash_file(&self.path)? }, dry_run) {
+            Ok(_)    => Ok(()),
+            Err(err) => Err(Box::new(err)),
+        }
+    }
+
+
+
+    #[inline]
+    fn as_any(&self) -> &dyn Any { self }
+}
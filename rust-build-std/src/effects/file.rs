@@ -12,12 +12,13 @@
 //!   Defines file-related effects, targets and dependencies.
 // 
 
+use std::any::Any;
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::path::PathBuf;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use rust_build::spec::{Effect, Named};
-use rust_build::cache::{Cache, CacheEntry, LastEditedTime};
+use rust_build::cache::{Cache, CacheEntry, CacheGuard, LastEditedTime};
 
 use crate::{trace, warn};
 
@@ -28,13 +29,16 @@ use crate::{trace, warn};
 pub enum Error {
     /// The file was not found
     FileNotFound{ path: PathBuf },
+    /// None of the caches of the file could be written to.
+    NoWritableCache{ path: PathBuf },
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         use Error::*;
         match self {
-            FileNotFound{ path } => write!(f, "Dependency file '{}' not found (did a previous target fail?)", path.display()),
+            FileNotFound{ path }    => write!(f, "Dependency file '{}' not found (did a previous target fail?)", path.display()),
+            NoWritableCache{ path } => write!(f, "Cannot commit changes to file '{}' because none of its caches are writable", path.display()),
         }
     }
 }
@@ -46,16 +50,24 @@ impl std::error::Error for Error {}
 
 
 /***** LIBRARY *****/
-/// A File is both a Dependency and an Effect. It can be though of as a particular file that may be updated or changed by some target.
+/// A File is an Effect that may also be used as a dependency (through an `EffectView`). It can be thought of as a particular file that may be updated or changed by some target.
+/// 
+/// If the path is a directory, the most recent last edited time of anything in it is used instead. Use a `Directory` to also detect removed files.
 #[derive(Debug, Clone)]
 pub struct File {
     /// The name of this file.
-    name  : String,
-    /// The Cache that we use to discover if the file has changed since last checks.
-    cache : Rc<Cache>,
+    name   : String,
+    /// The Caches that we use to discover if the file has changed since last checks. They are consulted in-order.
+    caches : Vec<Arc<Cache>>,
 
     /// The path of the file this Effect concerns itself about.
-    pub path : PathBuf,
+    pub path      : PathBuf,
+    /// Whether the file is expected to exist already (i.e., it's an input) or not (i.e., it's an artifact that may still have to be produced).
+    expect_exists : bool,
+    /// Whether to compare the hash of the file's contents if its last edited time changed.
+    hashing       : bool,
+    /// The hash of the command that produces this file, if any. If it differs from the one in the cache, the file is considered changed.
+    command_hash  : Option<u64>,
 }
 
 impl File {
@@ -69,12 +81,125 @@ impl File {
     /// # Returns
     /// A new File instance.
     #[inline]
-    pub fn new(name: impl Into<String>, cache: Rc<Cache>, path: impl Into<PathBuf>) -> Self {
+    pub fn new(name: impl Into<String>, cache: Arc<Cache>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            name   : name.into(),
+            caches : vec![ cache ],
+
+            path          : path.into(),
+            expect_exists : false,
+            hashing       : false,
+            command_hash  : None,
+        }
+    }
+
+    /// Constructor for the File dependency that consults multiple, layered caches.
+    /// 
+    /// When checking if the file has changed, the caches are consulted in-order, and the first one that has an entry for this file is used. When committing changes, they are written to the first cache that is not read-only.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of this File.
+    /// - `caches`: The Caches to use to keep track of this file's changed status. Typically, this is some local overlay cache first and some shared base cache second.
+    /// - `path`: The path of the file that this dependency tracks.
+    /// 
+    /// # Returns
+    /// A new File instance.
+    #[inline]
+    pub fn with_caches(name: impl Into<String>, caches: Vec<Arc<Cache>>, path: impl Into<PathBuf>) -> Self {
         Self {
             name : name.into(),
-            cache,
+            caches,
 
-            path : path.into(),
+            path          : path.into(),
+            expect_exists : false,
+            hashing       : false,
+            command_hash  : None,
+        }
+    }
+
+
+
+    /// Sets whether the file is expected to exist when checking if it has changed.
+    /// 
+    /// By default, a missing file is simply considered changed, since it is typically an artifact that has yet to be produced (e.g., on a fresh checkout). For files that should already be there (e.g., declared inputs), set this to true to get an error instead.
+    /// 
+    /// # Arguments
+    /// - `expect_exists`: Whether a missing file is an error (true) or simply changed (false).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn expect_exists(mut self, expect_exists: bool) -> Self {
+        self.expect_exists = expect_exists;
+        self
+    }
+
+    /// Sets whether the File also tracks the hash of its contents.
+    /// 
+    /// By default, the file is considered changed whenever its last edited time differs from the one in the cache. This produces false positives after, e.g., a `git checkout` or a `touch`. With hashing enabled, a file with a new last edited time is only considered changed if the SHA-256 hash of its contents differs too (this does not apply to directories). The file is streamed through the hasher, so this is also suitable for large binaries.
+    /// 
+    /// # Arguments
+    /// - `hashing`: Whether to compare content hashes (true) or only last edited times (false).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn with_hashing(mut self, hashing: bool) -> Self {
+        self.hashing = hashing;
+        self
+    }
+
+    /// Sets the hash of the command that produces this file (see `ShellCommand::command_hash()`).
+    /// 
+    /// The hash is stored in the cache when committing changes, and the file is considered changed if the stored hash differs from this one. This catches artifacts that are up-to-date on disk but were produced with different flags (e.g., another feature set or target triple).
+    /// 
+    /// # Arguments
+    /// - `command_hash`: The hash of the command that produces this file, or `None` to not track it (the default).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn with_command_hash(mut self, command_hash: Option<u64>) -> Self {
+        self.command_hash = command_hash;
+        self
+    }
+
+    /// Gives the File a different name.
+    /// 
+    /// The File is tracked in the cache by its path, so this does not affect its changed status.
+    /// 
+    /// # Arguments
+    /// - `name`: The new name of this File.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+
+
+    /// Returns a copy of this File that tracks the file at another path, e.g., for targets that copy or install an artifact somewhere else.
+    /// 
+    /// The copy keeps the name and caches of this File, but since the cache is keyed by path, its changed status is tracked separately from this File. Use `File::with_name()` to give it another name.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the file that the new File tracks (e.g., the destination of a copy).
+    /// 
+    /// # Returns
+    /// A new File instance.
+    #[inline]
+    pub fn remap(&self, path: impl Into<PathBuf>) -> Self {
+        Self {
+            name   : self.name.clone(),
+            caches : self.caches.clone(),
+
+            path          : path.into(),
+            expect_exists : self.expect_exists,
+            hashing       : self.hashing,
+            command_hash  : self.command_hash,
         }
     }
 }
@@ -85,26 +210,53 @@ impl Named for File {
 }
 
 impl Effect for File {
-    fn has_changed(&self) -> Result<bool, Box<dyn std::error::Error>> {
+    fn has_changed(&self) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
         // Check if the file exists
-        if !self.path.exists() { return Err(Box::new(Error::FileNotFound{ path: self.path.clone() })); }
+        if !self.path.exists() {
+            if self.expect_exists { return Err(Box::new(Error::FileNotFound{ path: self.path.clone() })); }
+            trace!("{}: Marking '{}' as changed (file does not exist yet)", self.name(), self.path.display());
+            return Ok(true);
+        }
 
-        // Check if the cache file exists
-        let entry: CacheEntry = match self.cache.get_file(&self.path) {
-            Ok(Some(entry)) => entry,
-            Ok(None)        => {
+        // Check if the cache file exists in any of the caches
+        let mut entry: Option<CacheEntry> = None;
+        for cache in &self.caches {
+            match cache.get_file_lenient(&self.path) {
+                Ok(Some(centry)) => { entry = Some(centry); break; },
+                Ok(None)         => { continue; },
+                Err(err)         => { return Err(Box::new(err)); },
+            }
+        }
+        let entry: CacheEntry = match entry {
+            Some(entry) => entry,
+            None        => {
                 trace!("{}: Marking '{}' as changed (no cache entry found)", self.name(), self.path.display());
                 return Ok(true);
             },
-            Err(err) => { return Err(Box::new(err)); },
         };
 
+        // If it was produced by another command than ours, it is outdated regardless of when it was edited
+        if self.command_hash.is_some() && entry.command_hash != self.command_hash {
+            trace!("{}: Marking '{}' as changed (produced by a different command)", self.name(), self.path.display());
+            return Ok(true);
+        }
+
         // If it does, fetch the file's most recent change date
-        let last_edited: LastEditedTime = match LastEditedTime::from_path(&self.path) {
+        let last_edited: LastEditedTime = match LastEditedTime::from_path_recursive(&self.path) {
             Ok(last_edited) => last_edited,
             Err(err)        => { return Err(Box::new(err)); },
         };
 
+        // If the last edited time changed but the contents did not, it is still unchanged
+        if self.hashing && self.path.is_file() && entry.last_edited != last_edited {
+            if let Some(hash) = &entry.hash {
+                if *hash == Cache::hash_file(&self.path)? {
+                    trace!("{}: Marking '{}' as unchanged (same hash as in cache)", self.name(), self.path.display());
+                    return Ok(false);
+                }
+            }
+        }
+
         // Check if it's needed to recompile
         if entry.last_edited > last_edited {
             warn!("Last edited time in the cache is later than on disk; that seems weird (assuming recompilation is needed)");
@@ -113,31 +265,60 @@ impl Effect for File {
         } else {
             #[cfg(feature = "log")]
             if entry.last_edited != last_edited {
-                trace!("{}: Marking '{}' as unchanged (same last edited time as in cache)", self.name(), self.path.display());
-            } else {
                 trace!("{}: Marking '{}' as changed (last edited time later than in cache)", self.name(), self.path.display());
+            } else {
+                trace!("{}: Marking '{}' as unchanged (same last edited time as in cache)", self.name(), self.path.display());
             }
             Ok(entry.last_edited != last_edited)
         }
     }
 
-    fn commit_change(&self, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    fn commit_change(&self, dry_run: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // Check if the file exists
         if !self.path.exists() { return Err(Box::new(Error::FileNotFound{ path: self.path.clone() })); }
 
         // Fetch the current last edited file
-        let last_edited: LastEditedTime = match LastEditedTime::from_path(&self.path) {
+        let last_edited: LastEditedTime = match LastEditedTime::from_path_recursive(&self.path) {
             Ok(last_edited) => last_edited,
             Err(err)        => { return Err(Box::new(err)); },
         };
 
-        // Write the last edited date to the cache
+        // Find the cache to write to
+        let cache: &Arc<Cache> = match self.caches.iter().find(|c| !c.is_read_only()) {
+            Some(cache) => cache,
+            None        => { return Err(Box::new(Error::NoWritableCache{ path: self.path.clone() })); },
+        };
+
+        // Write the last edited date (and hash, if any) to the cache while we have it locked
         trace!("{}: Updating cache for file '{}'", self.name(), self.path.display());
-        match self.cache.update_file(&self.path, CacheEntry {
+        let _lock: CacheGuard = cache.lock()?;
+        let entry: CacheEntry = CacheEntry {
             last_edited,
-        }, dry_run) {
+            hash         : None,
+            command_hash : self.command_hash,
+        };
+        let res: Result<(), rust_build::cache::Error> = if self.hashing && self.path.is_file() {
+            cache.update_file_hash(&self.path, entry, dry_run).map(|_| ())
+        } else {
+            cache.update_file(&self.path, entry, dry_run)
+        };
+        match res {
             Ok(_)    => Ok(()),
             Err(err) => Err(Box::new(err)),
         }
     }
+
+    fn last_changed(&self) -> Result<Option<LastEditedTime>, Box<dyn std::error::Error + Send + Sync>> {
+        // A file that does not exist (yet) has no last changed time
+        if !self.path.exists() { return Ok(None); }
+        match LastEditedTime::from_path_recursive(&self.path) {
+            Ok(last_edited) => Ok(Some(last_edited)),
+            Err(err)        => Err(Box::new(err)),
+        }
+    }
+
+
+
+    #[inline]
+    fn as_any(&self) -> &dyn Any { self }
 }
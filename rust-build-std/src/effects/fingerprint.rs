@@ -0,0 +1,100 @@
+//  FINGERPRINT.rs
+//    by Lut99
+// 
+//  Created:
+//    15 Oct 2026, 09:41:12
+//  Last edited:
+//    15 Oct 2026, 09:41:12
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines an effect that tracks changes in the configuration of
+//!   something (typically a target) instead of in a file.
+// 
+
+use std::any::Any;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use rust_build::spec::{Effect, Named};
+use rust_build::cache::{Cache, CacheGuard};
+
+use crate::trace;
+
+
+/***** LIBRARY *****/
+/// A Fingerprint is an Effect that represents the hash of some configuration. It is marked as changed when that hash differs from the one last committed to the cache.
+/// 
+/// This is typically used as one of a Target's `inputs()`, such that it is rebuild when its configuration (features, mode, ...) changes even though its sources did not.
+#[derive(Debug, Clone)]
+pub struct Fingerprint {
+    /// The name of this fingerprint. Also acts as its unique identifier in the cache.
+    name  : String,
+    /// The Cache that we use to discover if the fingerprint has changed since last checks.
+    cache : Arc<Cache>,
+
+    /// The current fingerprint.
+    pub fingerprint : u64,
+}
+
+impl Fingerprint {
+    /// Constructor for the Fingerprint.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of this Fingerprint. Note that it is used as its identifier in the cache, so it should be unique.
+    /// - `cache`: The Cache to use to keep track of this fingerprint's changed status.
+    /// - `source`: The configuration to hash.
+    /// 
+    /// # Returns
+    /// A new Fingerprint instance.
+    #[inline]
+    pub fn new(name: impl Into<String>, cache: Arc<Cache>, source: impl Hash) -> Self {
+        Self {
+            name  : name.into(),
+            cache,
+
+            fingerprint : Cache::hash(source),
+        }
+    }
+}
+
+impl Named for Fingerprint {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+
+impl Effect for Fingerprint {
+    fn has_changed(&self) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        match self.cache.get_fingerprint_lenient(&self.name) {
+            Ok(Some(fingerprint)) => {
+                #[cfg(feature = "log")]
+                if fingerprint != self.fingerprint {
+                    trace!("{}: Marking as changed (fingerprint differs from cache)", self.name());
+                } else {
+                    trace!("{}: Marking as unchanged (same fingerprint as in cache)", self.name());
+                }
+                Ok(fingerprint != self.fingerprint)
+            },
+            Ok(None) => {
+                trace!("{}: Marking as changed (no cache entry found)", self.name());
+                Ok(true)
+            },
+            Err(err) => Err(Box::new(err)),
+        }
+    }
+
+    fn commit_change(&self, dry_run: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        trace!("{}: Updating cache for fingerprint", self.name());
+        let _lock: CacheGuard = self.cache.lock()?;
+        match self.cache.update_fingerprint(&self.name, self.fingerprint, dry_run) {
+            Ok(_)    => Ok(()),
+            Err(err) => Err(Box::new(err)),
+        }
+    }
+
+
+
+    #[inline]
+    fn as_any(&self) -> &dyn Any { self }
+}
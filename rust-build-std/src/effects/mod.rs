@@ -16,6 +16,16 @@
 // Declare the effects
 pub mod trivial;
 pub mod file;
+pub mod directory;
+pub mod fingerprint;
+pub mod renamed;
+#[cfg(feature = "download")]
+pub mod download;
 
 // Pull some stuff into this module's namespace
 pub use file::File;
+pub use directory::Directory;
+pub use fingerprint::Fingerprint;
+pub use renamed::Renamed;
+#[cfg(feature = "download")]
+pub use download::RemoteFile;
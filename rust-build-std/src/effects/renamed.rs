@@ -0,0 +1,76 @@
+//  RENAMED.rs
+//    by Lut99
+// 
+//  Created:
+//    15 Oct 2026, 14:27:51
+//  Last edited:
+//    15 Oct 2026, 14:27:51
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines an effect that gives another effect a different name.
+// 
+
+use std::any::Any;
+
+use rust_build::cache::LastEditedTime;
+use rust_build::spec::{Effect, Named};
+
+
+/***** LIBRARY *****/
+/// A Renamed is an Effect that wraps another effect under a different name, while otherwise behaving exactly the same.
+/// 
+/// This is useful to give (automatically deduced) effects friendlier, stable names to use in, e.g., `ViewFilter::Allow`. Note that only the name seen by targets changes; any cache keys derived by the wrapped effect itself (such as a `File`'s path) stay the same.
+pub struct Renamed {
+    /// The new name of the effect.
+    name   : String,
+    /// The effect that is renamed.
+    effect : Box<dyn Effect>,
+}
+
+impl Renamed {
+    /// Constructor for the Renamed effect.
+    /// 
+    /// # Arguments
+    /// - `name`: The new name of the effect.
+    /// - `effect`: The effect to rename.
+    /// 
+    /// # Returns
+    /// A new Renamed instance.
+    #[inline]
+    pub fn new(name: impl Into<String>, effect: Box<dyn Effect>) -> Self {
+        Self {
+            name : name.into(),
+            effect,
+        }
+    }
+
+
+
+    /// Returns the effect that is renamed.
+    #[inline]
+    pub fn inner(&self) -> &dyn Effect { self.effect.as_ref() }
+}
+
+impl Named for Renamed {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+
+impl Effect for Renamed {
+    #[inline]
+    fn has_changed(&self) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> { self.effect.has_changed() }
+
+    #[inline]
+    fn commit_change(&self, dry_run: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> { self.effect.commit_change(dry_run) }
+
+    #[inline]
+    fn last_changed(&self) -> Result<Option<LastEditedTime>, Box<dyn std::error::Error + Send + Sync>> { self.effect.last_changed() }
+
+
+
+    /// Returns the wrapped effect, such that it can still be downcasted to its original type.
+    #[inline]
+    fn as_any(&self) -> &dyn Any { self.effect.as_any() }
+}
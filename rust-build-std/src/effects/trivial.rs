@@ -13,6 +13,8 @@
 //!   for driving builds.
 // 
 
+use std::any::Any;
+
 use rust_build::spec::{Effect, Named};
 
 use crate::trace;
@@ -28,16 +30,21 @@ impl Named for TrueEffect {
 }
 impl Effect for TrueEffect {
     #[inline]
-    fn has_changed(&self) -> Result<bool, Box<dyn std::error::Error>> {
+    fn has_changed(&self) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
         trace!("Marking '{}' as changed (always outdated)", self.name());
         Ok(true)
     }
 
     #[inline]
-    fn commit_change(&self, _dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    fn commit_change(&self, _dry_run: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         trace!("{}: Updating cache (virtually)", self.name());
         Ok(())
     }
+
+
+
+    #[inline]
+    fn as_any(&self) -> &dyn Any { self }
 }
 
 
@@ -51,14 +58,19 @@ impl Named for FalseEffect {
 }
 impl Effect for FalseEffect {
     #[inline]
-    fn has_changed(&self) -> Result<bool, Box<dyn std::error::Error>> {
+    fn has_changed(&self) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
         trace!("Marking '{}' as unchanged (always up-to-date)", self.name());
         Ok(false)
     }
 
     #[inline]
-    fn commit_change(&self, _dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    fn commit_change(&self, _dry_run: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         trace!("{}: Updating cache (virtually)", self.name());
         Ok(())
     }
+
+
+
+    #[inline]
+    fn as_any(&self) -> &dyn Any { self }
 }
@@ -17,6 +17,8 @@
 pub mod effects;
 pub use effects as deps;
 pub mod targets;
+#[cfg(test)]
+pub mod tests;
 
 
 // Define a few useful crate-local macros
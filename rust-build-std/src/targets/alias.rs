@@ -0,0 +1,73 @@
+//  ALIAS.rs
+//    by Lut99
+// 
+//  Created:
+//    15 Oct 2026, 10:21:05
+//  Last edited:
+//    15 Oct 2026, 10:21:05
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Provides a target that groups other targets under a single name
+//!   (like a phony aggregate rule in Makefiles).
+// 
+
+use rust_build::errors::TargetError;
+use rust_build::spec::{Architecture, Effect, Named, OperatingSystem, Target};
+use rust_build::view::EffectView;
+
+use crate::trace;
+
+
+/***** LIBRARY *****/
+/// Defines the Alias target, which groups other targets such that they can be built together (e.g., as `all`).
+/// 
+/// It does not build anything itself and exposes no effects; it simply depends on all of its members, which are thus built (in order) whenever the alias is.
+pub struct AliasTarget<'a> {
+    /// The name of this target.
+    name    : String,
+    /// The views on the members of this alias.
+    members : Vec<EffectView<'a>>,
+}
+
+impl<'a> AliasTarget<'a> {
+    /// Constructor for the AliasTarget.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the alias.
+    /// - `members`: An iterator over the targets that this alias groups. They are built in the given order.
+    /// 
+    /// # Returns
+    /// A new AliasTarget instance.
+    #[inline]
+    pub fn new(name: impl Into<String>, members: impl IntoIterator<Item = &'a dyn Target, IntoIter = impl Iterator<Item = &'a dyn Target>>) -> Self {
+        Self {
+            name    : name.into(),
+            members : members.into_iter().map(EffectView::new).collect(),
+        }
+    }
+}
+
+impl<'a> Named for AliasTarget<'a> {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+impl<'a> Target for AliasTarget<'a> {
+    #[inline]
+    fn build(&self, _os: OperatingSystem, _arch: Architecture, _dry_run: bool) -> Result<(), TargetError> {
+        trace!("{}: Built all {} members", self.name(), self.members.len());
+        Ok(())
+    }
+
+    #[inline]
+    fn kind(&self) -> &str { "alias" }
+
+
+
+    #[inline]
+    fn deps(&self) -> &[EffectView<'_>] { &self.members }
+
+    #[inline]
+    fn effects(&self) -> &[Box<dyn Effect>] { &[] }
+}
@@ -15,23 +15,39 @@
 //!   provided in the standard library.
 // 
 
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter, Result as FResult};
 use std::fs;
 use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::rc::Rc;
-use std::sync::Arc;
+use std::process::{Command, Output};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
+use serde::Deserialize;
 use toml::Value;
 use toml::map::Map;
 
 use rust_build::errors::TargetError;
+use rust_build::shell::{Error as ShellCommandError, ShellCommand, StreamLine};
 use rust_build::spec::{Architecture, Effect, Named, OperatingSystem, Target, TargetBuilder};
 use rust_build::view::EffectView;
 use rust_build::cache::Cache;
 
-use crate::{debug, trace};
-use crate::effects::File;
+use crate::{debug, trace, warn};
+use crate::effects::{Directory, File, Fingerprint, Renamed};
+use crate::effects::trivial::TrueEffect;
+
+
+/***** CONSTANTS *****/
+/// The environment variable that the CargoTarget reads its features from if none are given explicitly (see `CargoTargetBuilder::features_env()`).
+pub const DEFAULT_FEATURES_ENV: &str = "INSTALLER_CARGO_FEATURES";
+
+/// The resource class of the targets that compile with cargo (see `Target::resource_class()`), such that concurrent cargo builds can be limited with `Builder::resource_limit()`.
+pub const CARGO_RESOURCE_CLASS: &str = "cargo";
+
+
+
 
 
 /***** ERRORS *****/
@@ -55,6 +71,12 @@ pub enum Error {
     CargoTomlBinsTypeError{ path: PathBuf, data_type: &'static str },
     /// One of the '[[bin]]'s is not a table.
     CargoTomlBinTypeError{ path: PathBuf, data_type: &'static str },
+    /// The '[lib]' is not a table.
+    CargoTomlLibTypeError{ path: PathBuf, data_type: &'static str },
+    /// The 'crate-type' of the '[lib]' is not an Array of Strings.
+    CargoTomlCrateTypeError{ path: PathBuf, data_type: &'static str },
+    /// The 'required-features' of a '[[bin]]' are not an Array.
+    CargoTomlRequiredFeaturesTypeError{ path: PathBuf, data_type: &'static str },
     /// The given Cargo.toml has a 'package' table, but not a nested 'name' field.
     CargoTomlMissingName{ table: &'static str, path: PathBuf },
     /// The 'name' field in the Cargo.toml was of an incorrect type.
@@ -65,6 +87,32 @@ pub enum Error {
     CargoTomlMembersTypeError{ path: PathBuf, data_type: &'static str },
     /// The 'members' list in the Cargo.toml had a non-String element
     CargoTomlMemberTypeError{ path: PathBuf, data_type: &'static str },
+    /// The '[[example]]'s are not an Array.
+    CargoTomlExamplesTypeError{ path: PathBuf, data_type: &'static str },
+    /// One of the '[[example]]'s is not a table.
+    CargoTomlExampleTypeError{ path: PathBuf, data_type: &'static str },
+    /// Failed to read the examples directory of a package.
+    ExamplesDirReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to read the `src/bin` directory of a package.
+    BinDirReadError{ path: PathBuf, err: std::io::Error },
+    /// A requested example was not found in the package (or workspace).
+    UnknownExample{ path: PathBuf, example: String },
+
+    /// Failed to launch `cargo metadata`.
+    CargoMetadataLaunchError{ path: PathBuf, err: std::io::Error },
+    /// `cargo metadata` returned a non-zero exit code.
+    CargoMetadataFailure{ path: PathBuf, code: Option<i32>, stderr: String },
+    /// Failed to parse the output of `cargo metadata`.
+    CargoMetadataParseError{ path: PathBuf, err: serde_json::Error },
+
+    /// Failed to launch `cargo build`.
+    CargoBuildLaunchError{ path: PathBuf, err: std::io::Error },
+    /// `cargo build` returned a non-zero exit code.
+    CargoBuildFailure{ path: PathBuf, code: Option<i32> },
+    /// `cargo build` succeeded, but emitted warnings while they are denied.
+    CargoBuildWarnings{ path: PathBuf, count: usize },
+    /// Asked to cross-compile for another triple than the one the effects were deduced for.
+    TripleMismatch{ path: PathBuf, expected: Option<String>, got: String },
 }
 
 impl Display for Error {
@@ -80,11 +128,28 @@ impl Display for Error {
             CargoTomlEffectsDeduceError{ path }             => write!(f, "{}: No '[[bin]]', '[package]' or '[workspace]' toplevel table found", path.display()),
             CargoTomlBinsTypeError{ path, data_type }       => write!(f, "{}: Expected an Array as '[[bin]]'s, but got {}", path.display(), data_type),
             CargoTomlBinTypeError{ path, data_type }        => write!(f, "{}: Expected only Tables in '[[bin]]'s, but got {}", path.display(), data_type),
+            CargoTomlLibTypeError{ path, data_type }        => write!(f, "{}: Expected a Table as '[lib]', but got {}", path.display(), data_type),
+            CargoTomlCrateTypeError{ path, data_type }      => write!(f, "{}: Expected an Array of Strings as '[lib]' crate types, but got {}", path.display(), data_type),
+            CargoTomlRequiredFeaturesTypeError{ path, data_type } => write!(f, "{}: Expected an Array as '[[bin]]' required features, but got {}", path.display(), data_type),
             CargoTomlMissingName{ table, path }             => write!(f, "{}: There is a toplevel '{}' table, but not a nested 'name' field", table, path.display()),
             CargoTomlNameTypeError{ what, path, data_type } => write!(f, "{}: Expected a String as {} name, but got {}", what, path.display(), data_type),
             CargoTomlMissingMembers{ path }                 => write!(f, "{}: There is a toplevel '[workspace]' table, but not a nested 'members' list", path.display()),
             CargoTomlMembersTypeError{ path, data_type }    => write!(f, "{}: Expected an Array as workspace members, but got {}", path.display(), data_type),
             CargoTomlMemberTypeError{ path, data_type }     => write!(f, "{}: Expected only Strings in workspace members, but got {}", path.display(), data_type),
+            CargoTomlExamplesTypeError{ path, data_type }   => write!(f, "{}: Expected an Array as '[[example]]'s, but got {}", path.display(), data_type),
+            CargoTomlExampleTypeError{ path, data_type }    => write!(f, "{}: Expected only Tables in '[[example]]'s, but got {}", path.display(), data_type),
+            ExamplesDirReadError{ path, err }               => write!(f, "Failed to read examples directory '{}': {}", path.display(), err),
+            BinDirReadError{ path, err }                    => write!(f, "Failed to read binaries directory '{}': {}", path.display(), err),
+            UnknownExample{ path, example }                 => write!(f, "{}: Unknown example '{}'", path.display(), example),
+
+            CargoMetadataLaunchError{ path, err }      => write!(f, "Failed to launch 'cargo metadata' in '{}': {}", path.display(), err),
+            CargoMetadataFailure{ path, code, stderr } => write!(f, "'cargo metadata' in '{}' failed with exit code {}:\n{}", path.display(), code.map(|c| c.to_string()).unwrap_or_else(|| "???".into()), stderr),
+            CargoMetadataParseError{ path, err }       => write!(f, "Failed to parse output of 'cargo metadata' in '{}': {}", path.display(), err),
+
+            CargoBuildLaunchError{ path, err } => write!(f, "Failed to launch 'cargo build' in '{}': {}", path.display(), err),
+            CargoBuildFailure{ path, code }    => write!(f, "'cargo build' in '{}' failed with exit code {}", path.display(), code.map(|c| c.to_string()).unwrap_or_else(|| "???".into())),
+            CargoBuildWarnings{ path, count }  => write!(f, "'cargo build' in '{}' emitted {} warning(s) (and warnings are denied)", path.display(), count),
+            TripleMismatch{ path, expected, got } => write!(f, "Cannot build '{}' for '{}', since its effects were deduced for {} (set it with `CargoTargetBuilder::triple()` instead)", path.display(), got, expected.as_ref().map(|t| format!("'{}'", t)).unwrap_or_else(|| "the host".into())),
         }
     }
 }
@@ -95,34 +160,404 @@ impl std::error::Error for Error {}
 
 
 
+/***** AUXILLARY *****/
+/// Defines the (relevant subset of) output of `cargo metadata`.
+#[derive(Debug, Deserialize)]
+pub struct CargoMetadata {
+    /// The directory where cargo will write its build artifacts.
+    pub target_directory : PathBuf,
+    /// The packages in the package or workspace (i.e., the workspace members, since we never ask for dependencies).
+    #[serde(default)]
+    pub packages         : Vec<CargoMetadataPackage>,
+}
+
+/// Defines the (relevant subset of) a package in the output of `cargo metadata`.
+#[derive(Debug, Deserialize)]
+pub struct CargoMetadataPackage {
+    /// The name of the package.
+    pub name     : String,
+    /// The features of the package, mapped to the features (or dependencies) they enable in turn.
+    #[serde(default)]
+    pub features : HashMap<String, Vec<String>>,
+    /// The targets of the package, as discovered by cargo (i.e., including those found automatically in `src/bin`).
+    #[serde(default)]
+    pub targets  : Vec<CargoMetadataTarget>,
+}
+
+/// Defines the (relevant subset of) a target of a package in the output of `cargo metadata`.
+#[derive(Debug, Deserialize)]
+pub struct CargoMetadataTarget {
+    /// The name of the target.
+    pub name              : String,
+    /// The kinds of the target (e.g., `bin`, `lib`, `example`, ...).
+    pub kind              : Vec<String>,
+    /// The crate types of the target (e.g., `bin`, `rlib`, `cdylib`, ...).
+    #[serde(default)]
+    pub crate_types       : Vec<String>,
+    /// The features that have to be enabled for the target to be built.
+    #[serde(rename = "required-features", default)]
+    pub required_features : Vec<String>,
+}
+
+impl CargoMetadata {
+    /// Constructor for the CargoMetadata that runs `cargo metadata` in the given directory.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to the package or workspace directory to run `cargo metadata` in.
+    /// 
+    /// # Returns
+    /// A new CargoMetadata instance with the parsed output.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to run `cargo metadata` or to parse its output.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+
+        // Run the command
+        let mut cmd: Command = Command::new("cargo");
+        cmd.args([ "metadata", "--format-version=1", "--no-deps" ]);
+        cmd.current_dir(path);
+        trace!("Running {:?}", cmd);
+        let output: Output = match cmd.output() {
+            Ok(output) => output,
+            Err(err)   => { return Err(Error::CargoMetadataLaunchError{ path: path.into(), err }); },
+        };
+        if !output.status.success() { return Err(Error::CargoMetadataFailure{ path: path.into(), code: output.status.code(), stderr: String::from_utf8_lossy(&output.stderr).into() }); }
+
+        // Parse the output
+        match serde_json::from_slice(&output.stdout) {
+            Ok(metadata) => Ok(metadata),
+            Err(err)     => Err(Error::CargoMetadataParseError{ path: path.into(), err }),
+        }
+    }
+}
+
+
+
+/// Defines the (relevant subset of) a message emitted by `cargo build --message-format=json`.
+#[derive(Debug, Deserialize)]
+struct CargoMessage {
+    /// The kind of message (e.g., `compiler-message` or `compiler-artifact`).
+    reason  : String,
+    /// The compiler diagnostic, if this is a `compiler-message`.
+    message : Option<CompilerDiagnostic>,
+}
+
+/// Defines the (relevant subset of) a compiler diagnostic emitted by cargo.
+#[derive(Debug, Deserialize)]
+struct CompilerDiagnostic {
+    /// The level of the diagnostic (e.g., `warning` or `error`).
+    level    : String,
+    /// The diagnostic as rustc would print it to the terminal.
+    rendered : Option<String>,
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the target triple that cargo uses for the given operating system, architecture and C library.
+/// 
+/// # Arguments
+/// - `os`: The OperatingSystem to compile for.
+/// - `arch`: The Architecture to compile for.
+/// - `libc`: The Libc to link against. This only affects Linux, since the other operating systems have only one.
+/// 
+/// # Returns
+/// The target triple (e.g., `x86_64-unknown-linux-gnu`).
+/// 
+/// # Panics
+/// This function panics if the given OS or architecture is a custom one, as we don't know their triples.
+pub fn target_triple(os: OperatingSystem, arch: Architecture, libc: Libc) -> String {
+    // Cast architectures to a suitable string
+    let arch: &str = match arch {
+        Architecture::x86_32       => "i686",
+        Architecture::x86_64       => "x86_64",
+        Architecture::Aarch32      => "arm",
+        Architecture::Aarch64      => "aarch64",
+        Architecture::PowerPc32    => "powerpc",
+        Architecture::PowerPc64    => "powerpc64",
+        Architecture::Mips         => "mips",
+        Architecture::Custom(arch) => { panic!("Custom architectures ('{}') are not supported by CargoTarget", arch); },
+    };
+
+    // Use that to prepare the cargo target string
+    match os {
+        OperatingSystem::Windows      => { format!("{}-pc-windows-msvc", arch) },
+        OperatingSystem::MacOs        => { format!("{}-apple-darwin", arch) },
+        // 32-bit ARM on Linux is most commonly ARMv7 with hardware floats, which shows in the ABI
+        OperatingSystem::Linux        => match (arch, libc) {
+            ("arm", Libc::Gnu)  => "armv7-unknown-linux-gnueabihf".into(),
+            ("arm", Libc::Musl) => "armv7-unknown-linux-musleabihf".into(),
+            (arch, Libc::Gnu)   => format!("{}-unknown-linux-gnu", arch),
+            (arch, Libc::Musl)  => format!("{}-unknown-linux-musl", arch),
+        },
+        OperatingSystem::Custom(arch) => { panic!("Custom operating systems ('{}') are not supported by CargoTarget", arch); },
+    }
+}
+
+/// Returns the operating system that cargo compiles for with the given target triple.
+/// 
+/// # Arguments
+/// - `triple`: The target triple (e.g., `x86_64-pc-windows-msvc`).
+/// 
+/// # Returns
+/// The OperatingSystem of the triple, or a custom one if it's not one we know.
+fn triple_os(triple: &str) -> OperatingSystem {
+    if triple.contains("windows") {
+        OperatingSystem::Windows
+    } else if triple.contains("apple") {
+        OperatingSystem::MacOs
+    } else if triple.contains("linux") {
+        OperatingSystem::Linux
+    } else {
+        OperatingSystem::Custom("unknown")
+    }
+}
+
+/// Returns the name of the executable that cargo produces for a binary on the given operating system.
+/// 
+/// # Arguments
+/// - `name`: The name of the binary.
+/// - `os`: The OperatingSystem that the binary is compiled for.
+/// 
+/// # Returns
+/// The name of the executable (i.e., with `.exe` appended on Windows).
+fn exe_name(name: &str, os: OperatingSystem) -> String {
+    match os {
+        OperatingSystem::Windows => format!("{}.exe", name),
+        _                        => name.into(),
+    }
+}
+
+/// Returns the directory where cargo writes the artifacts for the given target triple and mode.
+/// 
+/// # Arguments
+/// - `target_dir`: The directory where cargo writes its artifacts.
+/// - `triple`: The target triple that is compiled for, if any. If given, cargo nests its artifacts in a directory named after it.
+/// - `mode`: The CargoMode that is compiled in.
+/// 
+/// # Returns
+/// The path to the directory with the artifacts (i.e., `<target_dir>[/<triple>]/<mode>`).
+fn build_dir(target_dir: &Path, triple: Option<&str>, mode: &CargoMode) -> PathBuf {
+    match triple {
+        Some(triple) => target_dir.join(triple).join(mode.to_build_dir()),
+        None         => target_dir.join(mode.to_build_dir()),
+    }
+}
+
+/// Returns the name of the file that cargo produces for a library of the given crate type.
+/// 
+/// # Arguments
+/// - `name`: The name of the library (with dashes replaced by underscores).
+/// - `crate_type`: The crate type to produce (e.g., `rlib` or `cdylib`).
+/// - `os`: The OperatingSystem that the library is compiled for, which determines the names of dynamic and static libraries.
+/// 
+/// # Returns
+/// The name of the file, or `None` if the crate type is unknown (or produces no library, like `bin`).
+fn lib_file_name(name: &str, crate_type: &str, os: OperatingSystem) -> Option<String> {
+    match crate_type {
+        "lib" | "rlib"                     => Some(format!("lib{}.rlib", name)),
+        "dylib" | "cdylib" | "proc-macro" => Some(match os {
+            OperatingSystem::Windows => format!("{}.dll", name),
+            OperatingSystem::MacOs   => format!("lib{}.dylib", name),
+            _                        => format!("lib{}.so", name),
+        }),
+        "staticlib"                        => Some(if os == OperatingSystem::Windows { format!("{}.lib", name) } else { format!("lib{}.a", name) }),
+        _                                  => None,
+    }
+}
+
+/// Ensures that the standard library for the given target triple is installed with rustup, installing it if it isn't.
+/// 
+/// If rustup cannot be found (e.g., because Rust was installed through a package manager), this only warns.
+/// 
+/// # Arguments
+/// - `name`: The name of the target on whose behalf the triple is installed.
+/// - `rustup`: The rustup executable to run.
+/// - `triple`: The target triple to install.
+/// - `dry_run`: If true, does not actually install the triple but instead just prints what would be run. Installed triples are still listed.
+/// 
+/// # Errors
+/// This function errors if the triple was missing and we failed to install it.
+fn install_target(name: &str, rustup: &str, triple: &str, dry_run: bool) -> Result<(), TargetError> {
+    // Find out which targets are installed
+    let output: Output = match Command::new(rustup).args([ "target", "list", "--installed" ]).output() {
+        Ok(output) => output,
+        Err(_err)  => {
+            warn!("{}: Failed to run '{} target list --installed' ({}); not installing target '{}'", name, rustup, _err, triple);
+            return Ok(());
+        },
+    };
+    if !output.status.success() {
+        warn!("{}: '{} target list --installed' failed with exit code {}; not installing target '{}'", name, rustup, output.status.code().map(|c| c.to_string()).unwrap_or_else(|| "???".into()), triple);
+        return Ok(());
+    }
+    if String::from_utf8_lossy(&output.stdout).lines().any(|l| l.trim() == triple) {
+        trace!("{}: Target '{}' is already installed", name, triple);
+        return Ok(());
+    }
+
+    // It's missing, so install it
+    debug!("{}: Installing missing target '{}'", name, triple);
+    let cmd: ShellCommand = ShellCommand::with_args(rustup, [ "target", "add", triple ]);
+    match cmd.run_dry_timeout_checked(dry_run, None) {
+        Ok(_)    => Ok(()),
+        Err(err) => Err(TargetError::BuildError{ name: name.into(), err: Box::new(err) }),
+    }
+}
+
+/// Prepares a command that runs the given cargo subcommand on the package (or workspace) in the given directory.
+/// 
+/// The command runs in that directory, such that cargo also picks up the configuration (i.e., `.cargo/config.toml`) that belongs to it.
+/// 
+/// # Arguments
+/// - `subcommand`: The cargo subcommand to run (e.g., `test`).
+/// - `path`: The path to the directory with the package (or workspace).
+/// - `mode`: The mode in which to build the packages.
+/// - `packages`: The packages to run the subcommand for. If empty, cargo's default is used.
+/// - `features`: The features to enable.
+/// - `triple`: The target triple to compile for, if not the host's.
+/// - `toolchain`: The rustup toolchain to run cargo with (e.g., `nightly`), if not the default one.
+/// 
+/// # Returns
+/// A new ShellCommand that runs cargo.
+fn cargo_command(subcommand: &str, path: &Path, mode: &CargoMode, packages: &[String], features: &[String], triple: Option<&str>, toolchain: Option<&str>) -> ShellCommand {
+    let mut cmd: ShellCommand = ShellCommand::with_cwd("cargo", path);
+    // Rustup only intercepts the toolchain if it's the very first argument
+    if let Some(toolchain) = toolchain { cmd.add_arg(format!("+{}", toolchain)); }
+    cmd.add_arg(subcommand);
+    cmd.add_args(mode.to_args());
+    for p in packages { cmd.add_args([ "--package", p ]); }
+    if !features.is_empty() { cmd.add_args([ "--features".to_string(), features.join(",") ]); }
+    if let Some(triple) = triple { cmd.add_args([ "--target", triple ]); }
+    cmd
+}
+
+/// Adds the flags that select features wholesale to the given cargo command.
+/// 
+/// # Arguments
+/// - `cmd`: The ShellCommand that runs cargo to add the flags to.
+/// - `all_features`: Whether to enable all features (`--all-features`).
+/// - `no_default_features`: Whether to disable the default features (`--no-default-features`).
+fn add_feature_flags(cmd: &mut ShellCommand, all_features: bool, no_default_features: bool) {
+    if all_features { cmd.add_arg("--all-features"); }
+    if no_default_features { cmd.add_arg("--no-default-features"); }
+}
+
+/// Adds the flags that restrict cargo's access to the lockfile and the network to the given cargo command.
+/// 
+/// # Arguments
+/// - `cmd`: The ShellCommand that runs cargo to add the flags to.
+/// - `locked`: Whether to require the `Cargo.lock` to be up-to-date (`--locked`).
+/// - `offline`: Whether to forbid accessing the network (`--offline`).
+/// - `frozen`: Whether to do both (`--frozen`).
+fn add_lock_flags(cmd: &mut ShellCommand, locked: bool, offline: bool, frozen: bool) {
+    if locked { cmd.add_arg("--locked"); }
+    if offline { cmd.add_arg("--offline"); }
+    if frozen { cmd.add_arg("--frozen"); }
+}
+
+/// Returns the target triple to pass to cargo when building for the given OS and architecture.
+/// 
+/// # Arguments
+/// - `os`: The target OS that we intend to build.
+/// - `arch`: The target architecture that we intend to build.
+/// - `libc`: The C library to link against when compiling for Linux.
+/// - `default`: The target triple to build for when building for the host (see `CargoTargetBuilder::triple()`), if not the host's.
+/// 
+/// # Returns
+/// The triple for the OS and architecture if they are not the host's, `default` otherwise.
+fn cargo_triple(os: OperatingSystem, arch: Architecture, libc: Libc, default: Option<&str>) -> Option<String> {
+    if os != OperatingSystem::host() || arch != Architecture::host() { Some(target_triple(os, arch, libc)) } else { default.map(String::from) }
+}
+
+/// Sets which executable runs the given cargo command, and with which additional environment variables.
+/// 
+/// # Arguments
+/// - `cmd`: The ShellCommand that runs cargo.
+/// - `target_dir`: The directory where cargo should write its artifacts (`--target-dir`), if not the default one.
+/// - `cargo_bin`: The cargo executable to run.
+/// - `use_cross`: Whether to run `cross` instead of the cargo executable.
+/// - `envs`: The environment variables to set on top of the inherited ones.
+fn set_cargo_exec(cmd: &mut ShellCommand, target_dir: Option<&Path>, cargo_bin: &str, use_cross: bool, envs: &HashMap<String, String>) {
+    if let Some(target_dir) = target_dir { cmd.add_args([ "--target-dir".to_string(), target_dir.display().to_string() ]); }
+    cmd.set_exec(if use_cross { "cross" } else { cargo_bin });
+    cmd.add_envs(envs.iter().map(|(n, v)| (n.as_str(), v.as_str())));
+}
+
+/// Runs the given cargo command on behalf of the given target.
+/// 
+/// # Arguments
+/// - `name`: The name of the target that runs the command.
+/// - `command`: The ShellCommand to run.
+/// - `timeout`: The maximum time the command may take, if any.
+/// - `dry_run`: If true, does not actually run the command but instead just prints what would be run.
+/// 
+/// # Errors
+/// This function errors if the command failed to run or returned a non-zero exit code.
+fn run_cargo_command(name: &str, command: &ShellCommand, timeout: Option<Duration>, dry_run: bool) -> Result<(), TargetError> {
+    match command.run_dry_timeout_checked(dry_run, timeout) {
+        Ok(_)    => Ok(()),
+        Err(err) => Err(TargetError::BuildError{ name: name.into(), err: Box::new(err) }),
+    }
+}
+
+
+
+
+
 /***** LIBRARY *****/
-/// Defines whether to build in release or debug mode.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// Defines the C library to link against when compiling for Linux, which determines the target triple.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Libc {
+    /// The GNU C library (i.e., `*-linux-gnu`), which is dynamically linked.
+    #[default]
+    Gnu,
+    /// The musl C library (i.e., `*-linux-musl`), which is statically linked by default such that the binaries run on any Linux distribution.
+    Musl,
+}
+
+
+
+/// Defines whether to build in release or debug mode, or with some other (named) profile.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum CargoMode {
     /// Building in release mode.
     Release,
     /// Building in debug/development mode.
     Debug,
+    /// Building with a custom profile (e.g., `dist`) as defined in the `[profile]` section of the `Cargo.toml`.
+    Profile(String),
 }
 
 impl CargoMode {
-    /// Converts the CargoMode to a flag.
+    /// Converts the CargoMode to the arguments that select it in cargo (e.g., to pass to `ShellCommand::add_args()`).
     #[inline]
-    pub fn to_flag(&self) -> &str {
+    pub fn to_args(&self) -> Vec<&str> {
         use CargoMode::*;
         match self {
-            Release => " --release",
-            Debug   => "",
+            Release       => vec![ "--release" ],
+            Debug         => vec![],
+            Profile(name) => vec![ "--profile", name ],
         }
     }
 
     /// Converts the CargoMode to the relevant build folder.
+    /// 
+    /// Custom profiles are built in a folder with their own name, except for cargo's built-in profiles (e.g., `dev` is built in `debug`).
     #[inline]
     pub fn to_build_dir(&self) -> &str {
         use CargoMode::*;
         match self {
-            Release => "release",
-            Debug   => "debug",
+            Release       => "release",
+            Debug         => "debug",
+            Profile(name) => match name.as_str() {
+                "dev" | "test" => "debug",
+                "bench"        => "release",
+                name           => name,
+            },
         }
     }
 }
@@ -143,11 +578,62 @@ pub struct CargoTargetBuilder<'a> {
     effects : Option<Vec<Box<dyn Effect>>>,
 
     /// The path of the directory where the target package (or workspace) lives.
-    path     : Option<PathBuf>,
+    path       : Option<PathBuf>,
     /// The packages that we build in this run.
-    packages : Vec<String>,
+    packages   : Vec<String>,
     /// The build mode (i.e., release or debug) we are in.
-    mode     : CargoMode,
+    mode       : CargoMode,
+    /// Whether to consult `cargo metadata` to learn about the package(s).
+    metadata   : bool,
+    /// The target triple to build for by default (i.e., when building for the host), if not the host's.
+    triple     : Option<String>,
+    /// The directory where cargo writes its artifacts, if not the default one.
+    target_dir : Option<PathBuf>,
+    /// The C library to link against when compiling for Linux.
+    libc       : Libc,
+
+    /// The features to enable.
+    features            : Vec<String>,
+    /// The environment variable to read the features from if none are given.
+    features_env        : String,
+    /// Whether to enable all features.
+    all_features        : bool,
+    /// Whether to disable the default features.
+    no_default_features : bool,
+
+    /// Whether to require the `Cargo.lock` to be up-to-date.
+    locked  : bool,
+    /// Whether to forbid cargo from accessing the network.
+    offline : bool,
+    /// Whether to require the `Cargo.lock` to be up-to-date and forbid cargo from accessing the network.
+    frozen  : bool,
+
+    /// The examples that we build in this run.
+    examples     : Vec<String>,
+    /// Whether to build all examples in this run.
+    all_examples : bool,
+
+    /// The maximum time the build may take.
+    timeout       : Option<Duration>,
+    /// Whether to fail the build if cargo emits any warnings.
+    deny_warnings : bool,
+    /// Whether to rebuild if the `Cargo.lock` changes.
+    lockfile      : bool,
+    /// Any deduced effects to rename, as `(old, new)` pairs.
+    renames       : Vec<(String, String)>,
+    /// The environment variables to set for cargo, on top of the inherited ones.
+    envs          : HashMap<String, String>,
+    /// The rustup toolchain to run cargo with, if not the default one.
+    toolchain     : Option<String>,
+    /// The cargo executable to use.
+    cargo_bin     : String,
+    /// Whether to use `cross` instead of the cargo executable.
+    use_cross     : bool,
+
+    /// Whether to install the target triple with rustup before cross-compiling, if it's missing.
+    auto_install_target : bool,
+    /// The rustup executable to use.
+    rustup              : String,
 }
 
 impl<'a> TargetBuilder<'a> for CargoTargetBuilder<'a> {
@@ -161,9 +647,37 @@ impl<'a> TargetBuilder<'a> for CargoTargetBuilder<'a> {
             deps    : vec![],
             effects : None,
 
-            path     : None,
-            packages : vec![],
-            mode     : CargoMode::Release,
+            path       : None,
+            packages   : vec![],
+            mode       : CargoMode::Release,
+            metadata   : false,
+            triple     : None,
+            target_dir : None,
+            libc       : Libc::Gnu,
+
+            features            : vec![],
+            features_env        : DEFAULT_FEATURES_ENV.into(),
+            all_features        : false,
+            no_default_features : false,
+
+            locked  : false,
+            offline : false,
+            frozen  : false,
+
+            examples     : vec![],
+            all_examples : false,
+
+            timeout       : None,
+            deny_warnings : false,
+            lockfile      : true,
+            renames       : vec![],
+            envs          : HashMap::new(),
+            toolchain     : None,
+            cargo_bin     : "cargo".into(),
+            use_cross     : false,
+
+            auto_install_target : false,
+            rustup              : "rustup".into(),
         }
     }
 
@@ -206,26 +720,131 @@ impl<'a> TargetBuilder<'a> for CargoTargetBuilder<'a> {
 
 
 
-    fn build(self, cache: Rc<Cache>) -> Result<Self::Target, Box<dyn std::error::Error>> {
+    fn build(self, cache: Arc<Cache>) -> Result<Self::Target, Box<dyn std::error::Error>> {
         // Assert we have what we need and/or default
         let path: PathBuf = match self.path {
             Some(path) => path,
             None       => { panic!("You have to call `CargoTargetBuilder::path()` before callign `CargoTargetBuilder::build()`"); },
         };
+
+        // Fall back to the features in the environment if none are given
+        let features: Vec<String> = if self.features.is_empty() {
+            match std::env::var(&self.features_env) {
+                Ok(features) => {
+                    debug!("Using features '{}' from '{}' for '{}'", features, self.features_env, self.name);
+                    features.split(',').map(|f| f.trim()).filter(|f| !f.is_empty()).map(String::from).collect()
+                },
+                Err(_) => vec![],
+            }
+        } else {
+            self.features
+        };
+        // Fall back to the host's triple if we have to link against another libc, and then to the default target in cargo's configuration if none is given
+        let libc: Libc = self.libc;
+        let triple: Option<String> = self.triple
+            .or_else(|| if libc != Libc::Gnu && OperatingSystem::host() == OperatingSystem::Linux { Some(target_triple(OperatingSystem::host(), Architecture::host(), libc)) } else { None })
+            .or_else(|| CargoTarget::find_config_target(&path));
+
+        let deduced: bool = self.effects.is_none();
         let effects: Vec<Box<dyn Effect>> = match self.effects {
             Some(effects) => effects,
-            None          => { CargoTarget::deduce_effects(&self.name, &path, self.mode, cache).map_err(|err| Box::new(err))? },
+            None          => {
+                // Prefer asking cargo itself which artifacts it builds, if told to do so
+                let metadata: Option<CargoMetadata> = if self.metadata {
+                    match CargoMetadata::from_path(&path) {
+                        Ok(metadata) => Some(metadata),
+                        Err(_err)    => { warn!("{} (falling back to deducing effects from Cargo.toml)", _err); None },
+                    }
+                } else {
+                    None
+                };
+
+                // Cargo writes the artifacts of explicit targets to a nested directory, which the deduction takes care of
+                let (target_dir, mut effects): (PathBuf, Vec<Box<dyn Effect>>) = match metadata {
+                    Some(mut metadata) => {
+                        // An explicit target directory overrides whatever cargo would use by default
+                        if let Some(target_dir) = &self.target_dir { metadata.target_directory = path.join(target_dir); }
+                        (metadata.target_directory.clone(), CargoTarget::deduce_effects_metadata(&self.name, &path, &metadata, &self.mode, triple.as_deref(), &features, cache.clone())?)
+                    },
+                    None => {
+                        let target_dir: PathBuf = match &self.target_dir {
+                            Some(target_dir) => path.join(target_dir),
+                            None             => CargoTarget::resolve_target_dir(&path, false),
+                        };
+                        let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects(&self.name, &path, &target_dir, &self.mode, triple.as_deref(), &features, cache.clone())?;
+                        (target_dir, effects)
+                    },
+                };
+                if self.all_examples || !self.examples.is_empty() {
+                    effects.append(&mut CargoTarget::deduce_example_effects(&self.name, &path, &target_dir, &self.mode, triple.as_deref(), &self.examples, self.all_examples, cache.clone())?);
+                }
+
+                // Remember the command that builds the artifacts, such that they are rebuilt when it changes
+                let mut command: ShellCommand = cargo_command("build", &path, &self.mode, &self.packages, &features, triple.as_deref(), self.toolchain.as_deref());
+                add_feature_flags(&mut command, self.all_features, self.no_default_features);
+                add_lock_flags(&mut command, self.locked, self.offline, self.frozen);
+                set_cargo_exec(&mut command, self.target_dir.as_deref(), &self.cargo_bin, self.use_cross, &self.envs);
+                let command_hash: u64 = command.command_hash();
+                let effects: Vec<Box<dyn Effect>> = effects.into_iter().map(|e| match e.as_any().downcast_ref::<File>() {
+                    Some(file) => Box::new(file.clone().with_command_hash(Some(command_hash))) as Box<dyn Effect>,
+                    None       => e,
+                }).collect();
+
+                // Give them friendlier names if told to do so
+                effects.into_iter().map(|e| match self.renames.iter().find(|(old, _)| old == e.name()) {
+                    Some((_, new)) => Box::new(Renamed::new(new, e)) as Box<dyn Effect>,
+                    None           => e,
+                }).collect()
+            },
         };
 
+        // Fingerprint the configuration such that we rebuild when it changes
+        let mut inputs: Vec<Box<dyn Effect>> = vec![ Box::new(Fingerprint::new(format!("{}_fingerprint", self.name), cache.clone(), (&path, &self.packages, &self.mode, &triple, &self.examples, self.all_examples, &features, self.all_features, self.no_default_features))) ];
+        // Also rebuild when the dependencies are bumped
+        if self.lockfile {
+            if let Some(lockfile) = CargoTarget::find_lockfile(&path) {
+                inputs.push(Box::new(File::new(format!("{}_lockfile", self.name), cache, lockfile)));
+            } else {
+                debug!("No Cargo.lock found for '{}' (not tracking it)", path.display());
+            }
+        }
+
         // Simply create a target with those properties
         Ok(CargoTarget {
-            name : self.name,
-            deps : self.deps,
+            name   : self.name,
+            deps   : self.deps,
             effects,
+            inputs,
+            deduced,
 
             path,
-            packages : self.packages,
-            mode     : self.mode,
+            packages   : self.packages,
+            mode       : self.mode,
+            triple,
+            target_dir : self.target_dir,
+            libc       : self.libc,
+            features,
+
+            all_features        : self.all_features,
+            no_default_features : self.no_default_features,
+
+            locked  : self.locked,
+            offline : self.offline,
+            frozen  : self.frozen,
+
+            examples     : self.examples,
+            all_examples : self.all_examples,
+
+            timeout       : self.timeout,
+            deny_warnings : self.deny_warnings,
+            warnings      : Mutex::new(None),
+            envs          : self.envs,
+            toolchain     : self.toolchain,
+            cargo_bin     : self.cargo_bin,
+            use_cross     : self.use_cross,
+
+            auto_install_target : self.auto_install_target,
+            rustup              : self.rustup,
         })
     }
 }
@@ -291,232 +910,2257 @@ impl<'a> CargoTargetBuilder<'a> {
         self.mode = mode;
         self
     }
-}
-
 
+    /// Adds a feature to enable when building.
+    /// 
+    /// If no features are given at all, they are read from the environment instead (see `CargoTargetBuilder::features_env()`).
+    /// 
+    /// # Arguments
+    /// - `feature`: The name of the feature to enable.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn feature(mut self, feature: impl Into<String>) -> Self {
+        self.features.push(feature.into());
+        self
+    }
+    /// Adds a whole list of features to enable when building.
+    /// 
+    /// If no features are given at all, they are read from the environment instead (see `CargoTargetBuilder::features_env()`).
+    /// 
+    /// # Arguments
+    /// - `features`: An iterator over the names of the features to enable.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn features(mut self, features: impl IntoIterator<Item = impl Into<String>, IntoIter = impl Iterator<Item = impl Into<String>>>) -> Self {
+        self.features.extend(features.into_iter().map(|f| f.into()));
+        self
+    }
 
-/// Defines the Cargo target, which uses the Cargo build system to compile Rust code.
-/// 
-/// This can typically be used as a starting point in your dependency tree.
-pub struct CargoTarget<'a> {
-    /// The name of this target.
-    name    : String,
-    /// The dependencies of this target.
-    deps    : Vec<EffectView<'a>>,
-    /// The effects (that we care about) of this target.
-    effects : Vec<Box<dyn Effect>>,
+    /// Sets the environment variable from which the features are read if none are given explicitly (e.g., to drive them from a CI matrix).
+    /// 
+    /// The variable should contain a comma-separated list of features, and is read when `CargoTargetBuilder::build()` is called. Explicitly given features always take precedence.
+    /// 
+    /// Defaults to `INSTALLER_CARGO_FEATURES` (see `DEFAULT_FEATURES_ENV`).
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the environment variable.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn features_env(mut self, name: impl Into<String>) -> Self {
+        self.features_env = name.into();
+        self
+    }
 
-    /// The path of the directory where the target package (or workspace) lives.
-    path     : PathBuf,
-    /// The packages that we build in this run.
-    packages : Vec<String>,
-    /// The build mode (i.e., release or debug) we are in.
-    mode     : CargoMode,
-}
+    /// Sets whether to enable all features of the package(s) when building (i.e., pass `--all-features`).
+    /// 
+    /// Note that the automatically deduced effects only account for the features given explicitly (and the default ones), so binaries that are gated behind other features are missed. Specify the effects manually if you rely on those.
+    /// 
+    /// Defaults to `false`.
+    /// 
+    /// # Arguments
+    /// - `all_features`: Whether to enable all features (true) or not (false).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn all_features(mut self, all_features: bool) -> Self {
+        self.all_features = all_features;
+        self
+    }
 
-impl<'a> CargoTarget<'a> {
-    /// Returns a builder for the CargoTarget that can be used to fully define it.
+    /// Sets whether to disable the default features of the package(s) when building (i.e., pass `--no-default-features`).
     /// 
-    /// Note that you have to call at least `CargoTargetBuilder::path()` before calling `CargoTargetBuilder::build()`.
+    /// Note that the automatically deduced effects still assume the default features are enabled, so they may over-report binaries that are gated behind them. Specify the effects manually if that matters.
     /// 
-    /// Also note that if you do not specify any effects, they will automatically be deduced from the `Cargo.toml` file(s) sa all binaries they produce.
+    /// Defaults to `false`.
     /// 
     /// # Arguments
-    /// - `name`: The name of the target to build.
+    /// - `no_default_features`: Whether to disable the default features (true) or not (false).
     /// 
     /// # Returns
-    /// A new CargoTargetBuilder instance.
+    /// The same `self` as given for chaining purposes.
     #[inline]
-    pub fn builder(name: impl Into<String>) -> CargoTargetBuilder<'a> {
-        CargoTargetBuilder::new(name)
+    pub fn no_default_features(mut self, no_default_features: bool) -> Self {
+        self.no_default_features = no_default_features;
+        self
     }
 
+    /// Sets whether cargo must fail if the `Cargo.lock` is missing or out-of-date, instead of updating it (i.e., pass `--locked`).
+    /// 
+    /// Defaults to `false`.
+    /// 
+    /// # Arguments
+    /// - `locked`: Whether to require an up-to-date lockfile (true) or not (false).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn locked(mut self, locked: bool) -> Self {
+        self.locked = locked;
+        self
+    }
 
+    /// Sets whether cargo may not access the network, such that it only uses dependencies that are already downloaded (i.e., pass `--offline`).
+    /// 
+    /// Defaults to `false`.
+    /// 
+    /// # Arguments
+    /// - `offline`: Whether to forbid network access (true) or not (false).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
 
-    /// Deduces the list of effects from either the given package or workspace directory by inspecting the Cargo.toml.
+    /// Sets whether cargo must use the `Cargo.lock` as-is and may not access the network (i.e., pass `--frozen`).
     /// 
-    /// If the path points to a package, the resulting binary file (either the lib or name) read from the Cargo.toml or deduced is returned.
+    /// Cargo considers this to imply both `--locked` and `--offline`, but they are still only passed if set with `CargoTargetBuilder::locked()` and `CargoTargetBuilder::offline()`, respectively.
     /// 
-    /// Otherwise, it recursively collects resulting binaries from each package in the workspace.
+    /// Defaults to `false`.
     /// 
     /// # Arguments
-    /// - `name`: The name of the target-to-be (used for debugging purposes only).
-    /// - `path`: The path to the directory with the package (or workspace).
-    /// - `mode`: Whether we're building in `CargoMode::Release` or `CargoMode::Debug` mode.
-    /// - `cache`: The Cache that we use to keep track of file changed.
+    /// - `frozen`: Whether to freeze the lockfile and the network access (true) or not (false).
     /// 
     /// # Returns
-    /// A vector of effects, each of which is the (relevant) output file(s) of a package.
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn frozen(mut self, frozen: bool) -> Self {
+        self.frozen = frozen;
+        self
+    }
+
+    /// Sets whether to use `cargo metadata` to learn about the package(s) this target builds.
     /// 
-    /// # Errors
-    /// This function errors if we failed to find, read or parse the `Cargo.toml` file.
-    pub fn deduce_effects(name: impl AsRef<str>, path: impl AsRef<Path>, mode: CargoMode, cache: Rc<Cache>) -> Result<Vec<Box<dyn Effect>>, Error> {
-        let name : &str  = name.as_ref();
-        let path : &Path = path.as_ref();
-        trace!("Duducing effects for CargoTarget '{}' in directory '{}'", name, path.display());
+    /// When enabled, the target directory where cargo writes its artifacts is read from `cargo metadata`'s output, which also respects the `[build] target-dir` setting in `.cargo/config.toml`. Similarly, the effects are deduced from the binaries and libraries that cargo reports (see `CargoTarget::deduce_effects_metadata()`) instead of by parsing the `Cargo.toml` file(s). If `cargo metadata` fails (e.g., because cargo is not available), the target falls back to the usual heuristics.
+    /// 
+    /// Defaults to `false`.
+    /// 
+    /// # Arguments
+    /// - `metadata`: Whether to run `cargo metadata` (true) or not (false).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
 
-        // Attempt to open the Cargo.toml file and read its contents
-        let cargo_path: PathBuf = path.join("Cargo.toml");
-        let cargo_toml: Vec<u8> = match fs::File::open(&cargo_path) {
-            Ok(mut handle) => {
-                let mut res: Vec<u8> = vec![];
-                match handle.read_to_end(&mut res) {
-                    Ok(_)    => res,
-                    Err(err) => { return Err(Error::CargoTomlReadError{ path: cargo_path, err }); },
-                }
-            },
-            Err(err) => {
-                if err.kind() != std::io::ErrorKind::NotFound { return Err(Error::MissingCargoToml { path: path.into() }); }
-                return Err(Error::CargoTomlOpenError{ path: cargo_path, err });
-            }
-        };
+    /// Sets the directory where cargo writes its artifacts (i.e., pass `--target-dir`), e.g., to share one across multiple packages or to keep it out of the source tree.
+    /// 
+    /// The automatically deduced effects are placed in this directory too, such that they point to the actual artifacts. Like for cargo, a relative path is relative to the package (or workspace) directory.
+    /// 
+    /// Defaults to cargo's default target directory (see `CargoTarget::resolve_target_dir()`).
+    /// 
+    /// # Arguments
+    /// - `target_dir`: The path to the target directory.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn target_dir(mut self, target_dir: impl Into<PathBuf>) -> Self {
+        self.target_dir = Some(target_dir.into());
+        self
+    }
 
-        // Parse it with serde (and toml)
-        let cargo_toml: Value = match toml::from_slice(&cargo_toml) {
-            Ok(cargo_toml) => cargo_toml,
-            Err(err)       => { return Err(Error::CargoTomlParseError{ path: cargo_path, err }); },
+    /// Sets the C library to link against when compiling for Linux (e.g., `Libc::Musl` for fully static binaries).
+    /// 
+    /// This determines the target triple (see `target_triple()`). If it's not `Libc::Gnu`, builds for a Linux host pass that triple explicitly too, unless a default one is given with `CargoTargetBuilder::triple()`.
+    /// 
+    /// Defaults to `Libc::Gnu`.
+    /// 
+    /// # Arguments
+    /// - `libc`: The Libc to link against.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn libc(mut self, libc: Libc) -> Self {
+        self.libc = libc;
+        self
+    }
+
+    /// Sets the target triple to build for by default, i.e., when building for the host's OS and architecture.
+    /// 
+    /// If not given, the `build.target` in the package's `.cargo/config.toml` (or the `CARGO_BUILD_TARGET` environment variable) is used, like cargo does. Note that this also changes where the deduced effects are expected (i.e., in `<target_dir>/<triple>`), which is why building deduced effects for any other OS or architecture than this triple's is refused.
+    /// 
+    /// # Arguments
+    /// - `triple`: The target triple to build for (e.g., `x86_64-unknown-linux-musl`).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn triple(mut self, triple: impl Into<String>) -> Self {
+        self.triple = Some(triple.into());
+        self
+    }
+
+    /// Adds an example to the list of examples that this target will build (akin to `cargo build --example <name>`).
+    /// 
+    /// If no effects are given explicitly, the example's executable (at `<target_dir>/<mode>/examples/<name>`) is added to the deduced effects.
+    /// 
+    /// # Arguments
+    /// - `example`: The name of the example to build.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn example(mut self, example: impl Into<String>) -> Self {
+        self.examples.push(example.into());
+        self
+    }
+
+    /// Sets whether this target builds all examples (akin to `cargo build --examples`).
+    /// 
+    /// If no effects are given explicitly, the executables of all examples found in the package (or workspace) are added to the deduced effects.
+    /// 
+    /// Defaults to `false`.
+    /// 
+    /// # Arguments
+    /// - `all_examples`: Whether to build all examples (true) or only the ones given with `CargoTargetBuilder::example()` (false).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn all_examples(mut self, all_examples: bool) -> Self {
+        self.all_examples = all_examples;
+        self
+    }
+
+    /// Sets the maximum time that building this target may take.
+    /// 
+    /// If cargo is still running once the timeout passes, it is killed and the build fails. By default, there is no limit.
+    /// 
+    /// # Arguments
+    /// - `timeout`: The maximum time the build may take.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets whether this target fails if cargo emits any warnings while building.
+    /// 
+    /// When enabled, cargo is run with `--message-format=json` such that its compiler messages can be inspected, and the build fails with the number of warnings if there are any. This is mostly useful for strict CI builds.
+    /// 
+    /// Defaults to `false`.
+    /// 
+    /// # Arguments
+    /// - `deny_warnings`: Whether warnings fail the build (true) or not (false).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn deny_warnings(mut self, deny_warnings: bool) -> Self {
+        self.deny_warnings = deny_warnings;
+        self
+    }
+
+    /// Sets whether this target tracks the package's (or workspace's) `Cargo.lock` as an input, such that bumping any dependency triggers a rebuild.
+    /// 
+    /// The lockfile is searched for in the target's directory and its parents (since packages in a workspace share that of the workspace). If none exists yet, it is not tracked. Disable this for crates that intentionally do not commit a lockfile.
+    /// 
+    /// Defaults to `true`.
+    /// 
+    /// # Arguments
+    /// - `lockfile`: Whether to track the `Cargo.lock` (true) or not (false).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn track_lockfile(mut self, lockfile: bool) -> Self {
+        self.lockfile = lockfile;
+        self
+    }
+
+    /// Gives one of the automatically deduced effects a different name.
+    /// 
+    /// Deduced effects are named `<target>_<binary>` (or `<target>_example_<example>`), which may be unwieldy to use in, e.g., `ViewFilter::Allow`. This renames such an effect to something friendlier. It has no effect if the effects are given explicitly or if no deduced effect has the given name.
+    /// 
+    /// # Arguments
+    /// - `old`: The deduced name of the effect.
+    /// - `new`: The new name of the effect.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn rename_effect(mut self, old: impl Into<String>, new: impl Into<String>) -> Self {
+        self.renames.push((old.into(), new.into()));
+        self
+    }
+
+    /// Sets an environment variable for the cargo process that builds this target (e.g., `PKG_CONFIG_ALLOW_CROSS=1` when cross-compiling).
+    /// 
+    /// The variable is set on top of the environment that cargo inherits, overwriting it only if it exists there already.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the environment variable.
+    /// - `value`: The value of the environment variable.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.envs.insert(name.into(), value.into());
+        self
+    }
+    /// Sets a whole list of environment variables for the cargo process that builds this target.
+    /// 
+    /// The variables are set on top of the environment that cargo inherits, overwriting it only if they exist there already.
+    /// 
+    /// # Arguments
+    /// - `envs`: An iterator over (name, value) pairs of the environment variables to set.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn envs(mut self, envs: impl IntoIterator<Item = (impl Into<String>, impl Into<String>), IntoIter = impl Iterator<Item = (impl Into<String>, impl Into<String>)>>) -> Self {
+        self.envs.extend(envs.into_iter().map(|(n, v)| (n.into(), v.into())));
+        self
+    }
+
+    /// Adds flags to pass to every invocation of rustc by cargo (e.g., `-C target-cpu=native`), by setting the `RUSTFLAGS` environment variable for cargo.
+    /// 
+    /// If called multiple times (or if `RUSTFLAGS` is also given with `CargoTargetBuilder::env()`), the flags are appended to the ones given earlier, separated by a space. Note that they replace any `RUSTFLAGS` inherited from the environment, and that changing them forces cargo to recompile everything.
+    /// 
+    /// # Arguments
+    /// - `flags`: The (space-separated) flags to pass to rustc.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn rustflags(mut self, flags: impl Into<String>) -> Self {
+        let flags: String = flags.into();
+        match self.envs.get_mut("RUSTFLAGS") {
+            Some(rustflags) => { rustflags.push(' '); rustflags.push_str(&flags); },
+            None            => { self.envs.insert("RUSTFLAGS".into(), flags); },
+        }
+        self
+    }
+
+    /// Sets the rustup toolchain to build with (e.g., `nightly`), by running `cargo +<toolchain> build`.
+    /// 
+    /// This requires cargo to be installed through rustup, which intercepts the `+<toolchain>` argument. By default, the default toolchain is used.
+    /// 
+    /// # Arguments
+    /// - `toolchain`: The name of the toolchain to use.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn toolchain(mut self, toolchain: impl Into<String>) -> Self {
+        self.toolchain = Some(toolchain.into());
+        self
+    }
+
+    /// Sets the cargo executable that builds this target, e.g., if it's installed in a nonstandard location or to use a drop-in replacement like `cross`.
+    /// 
+    /// Defaults to `cargo` (i.e., as found on the PATH).
+    /// 
+    /// # Arguments
+    /// - `cargo_bin`: The path to (or name of) the cargo executable.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn cargo_bin(mut self, cargo_bin: impl Into<String>) -> Self {
+        self.cargo_bin = cargo_bin.into();
+        self
+    }
+
+    /// Sets whether to build with [`cross`](https://github.com/cross-rs/cross) instead of cargo, which cross-compiles in Docker images that contain the toolchain (and linker) for the target.
+    /// 
+    /// The command is constructed exactly like for cargo (including `--target <triple>`), and takes precedence over `CargoTargetBuilder::cargo_bin()`. Note that this requires both `cross` and Docker (or Podman) to be installed, and that the target triple is never installed with rustup (see `CargoTargetBuilder::auto_install_target()`).
+    /// 
+    /// Defaults to `false`.
+    /// 
+    /// # Arguments
+    /// - `use_cross`: Whether to build with cross (true) or cargo (false).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn use_cross(mut self, use_cross: bool) -> Self {
+        self.use_cross = use_cross;
+        self
+    }
+
+    /// Sets whether to install the target triple with `rustup target add` before building, if we're cross-compiling and it isn't installed yet.
+    /// 
+    /// Whether it's installed is checked with `rustup target list --installed`. This is skipped entirely for host builds, and only warns if rustup is not available.
+    /// 
+    /// Defaults to `false`.
+    /// 
+    /// # Arguments
+    /// - `auto_install_target`: Whether to install missing target triples (true) or not (false).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn auto_install_target(mut self, auto_install_target: bool) -> Self {
+        self.auto_install_target = auto_install_target;
+        self
+    }
+
+    /// Sets the rustup executable used to install missing target triples (see `CargoTargetBuilder::auto_install_target()`).
+    /// 
+    /// Defaults to `rustup` (i.e., as found on the PATH).
+    /// 
+    /// # Arguments
+    /// - `rustup`: The path to (or name of) the rustup executable.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn rustup(mut self, rustup: impl Into<String>) -> Self {
+        self.rustup = rustup.into();
+        self
+    }
+}
+
+
+
+/// Defines the Cargo target, which uses the Cargo build system to compile Rust code.
+/// 
+/// This can typically be used as a starting point in your dependency tree.
+pub struct CargoTarget<'a> {
+    /// The name of this target.
+    name    : String,
+    /// The dependencies of this target.
+    deps    : Vec<EffectView<'a>>,
+    /// The effects (that we care about) of this target.
+    effects : Vec<Box<dyn Effect>>,
+    /// The inputs of this target, i.e., the fingerprint of its configuration.
+    inputs  : Vec<Box<dyn Effect>>,
+    /// Whether the effects were deduced for `triple` (instead of given by the user).
+    deduced : bool,
+
+    /// The path of the directory where the target package (or workspace) lives.
+    path       : PathBuf,
+    /// The packages that we build in this run.
+    packages   : Vec<String>,
+    /// The build mode (i.e., release or debug) we are in.
+    mode       : CargoMode,
+    /// The target triple to build for by default (i.e., when building for the host), if not the host's.
+    triple     : Option<String>,
+    /// The directory where cargo writes its artifacts, if not the default one.
+    target_dir : Option<PathBuf>,
+    /// The C library to link against when compiling for Linux.
+    libc       : Libc,
+    /// The features to enable.
+    features   : Vec<String>,
+
+    /// Whether to enable all features.
+    all_features        : bool,
+    /// Whether to disable the default features.
+    no_default_features : bool,
+
+    /// Whether to require the `Cargo.lock` to be up-to-date.
+    locked  : bool,
+    /// Whether to forbid cargo from accessing the network.
+    offline : bool,
+    /// Whether to require the `Cargo.lock` to be up-to-date and forbid cargo from accessing the network.
+    frozen  : bool,
+
+    /// The examples that we build in this run.
+    examples     : Vec<String>,
+    /// Whether to build all examples in this run.
+    all_examples : bool,
+
+    /// The maximum time the build may take.
+    timeout       : Option<Duration>,
+    /// Whether to fail the build if cargo emits any warnings.
+    deny_warnings : bool,
+    /// The number of warnings emitted by the last build, if we counted them.
+    warnings      : Mutex<Option<usize>>,
+    /// The environment variables to set for cargo, on top of the inherited ones.
+    envs          : HashMap<String, String>,
+    /// The rustup toolchain to run cargo with, if not the default one.
+    toolchain     : Option<String>,
+    /// The cargo executable to use.
+    cargo_bin     : String,
+    /// Whether to use `cross` instead of the cargo executable.
+    use_cross     : bool,
+
+    /// Whether to install the target triple with rustup before cross-compiling, if it's missing.
+    auto_install_target : bool,
+    /// The rustup executable to use.
+    rustup              : String,
+}
+
+impl<'a> CargoTarget<'a> {
+    /// Returns a builder for the CargoTarget that can be used to fully define it.
+    /// 
+    /// Note that you have to call at least `CargoTargetBuilder::path()` before calling `CargoTargetBuilder::build()`.
+    /// 
+    /// Also note that if you do not specify any effects, they will automatically be deduced from the `Cargo.toml` file(s) sa all binaries they produce.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target to build.
+    /// 
+    /// # Returns
+    /// A new CargoTargetBuilder instance.
+    #[inline]
+    pub fn builder(name: impl Into<String>) -> CargoTargetBuilder<'a> {
+        CargoTargetBuilder::new(name)
+    }
+
+
+
+    /// Resolves the directory where cargo will write its build artifacts.
+    /// 
+    /// If `metadata` is true, this uses `cargo metadata` to find the authoritative location. Otherwise (or if that fails), it respects the `CARGO_TARGET_DIR` environment variable first, then the `CARGO_BUILD_TARGET_DIR` variable and then defaults to `./target`.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to the directory with the package (or workspace).
+    /// - `metadata`: Whether to consult `cargo metadata` or not.
+    /// 
+    /// # Returns
+    /// The path of the target directory.
+    pub fn resolve_target_dir(path: impl AsRef<Path>, metadata: bool) -> PathBuf {
+        if metadata {
+            match CargoMetadata::from_path(path) {
+                Ok(metadata) => {
+                    trace!("Using target directory '{}' from cargo metadata", metadata.target_directory.display());
+                    return metadata.target_directory;
+                },
+                Err(_err) => { warn!("{} (falling back to deducing target directory)", _err); },
+            }
+        }
+
+        for var in ["CARGO_TARGET_DIR", "CARGO_BUILD_TARGET_DIR"] {
+            if let Some(dir) = std::env::var_os(var) {
+                if !dir.is_empty() {
+                    trace!("Using target directory '{}' from environment variable '{}'", PathBuf::from(&dir).display(), var);
+                    return dir.into();
+                }
+            }
+        }
+        PathBuf::from("./target")
+    }
+
+    /// Finds the `Cargo.lock` file that belongs to the package or workspace in the given directory.
+    /// 
+    /// Since packages in a workspace share the lockfile of the workspace, this searches the given directory and then all of its parents.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to the directory with the package (or workspace).
+    /// 
+    /// # Returns
+    /// The path of the lockfile, or `None` if there is none (yet).
+    pub fn find_lockfile(path: impl AsRef<Path>) -> Option<PathBuf> {
+        path.as_ref().ancestors().map(|p| p.join("Cargo.lock")).find(|p| p.is_file())
+    }
+
+    /// Finds the default target triple that cargo builds for in the given package or workspace directory.
+    /// 
+    /// This respects the `CARGO_BUILD_TARGET` environment variable first, and then the `build.target` in the `.cargo/config.toml` (or legacy `.cargo/config`) files in the given directory and all of its parents (where the nearest one wins).
+    /// 
+    /// Note that it does not consider the config in cargo's home directory.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to the directory with the package (or workspace).
+    /// 
+    /// # Returns
+    /// The target triple, or `None` if cargo builds for the host by default.
+    pub fn find_config_target(path: impl AsRef<Path>) -> Option<String> {
+        if let Ok(triple) = std::env::var("CARGO_BUILD_TARGET") {
+            if !triple.is_empty() {
+                trace!("Using target triple '{}' from environment variable 'CARGO_BUILD_TARGET'", triple);
+                return Some(triple);
+            }
+        }
+
+        for config_path in path.as_ref().ancestors().flat_map(|p| [ p.join(".cargo").join("config.toml"), p.join(".cargo").join("config") ]) {
+            if !config_path.is_file() { continue; }
+
+            // Read the file, skipping it if it's invalid (cargo will complain about it anyway)
+            let config: Value = match fs::read_to_string(&config_path).map_err(|err| err.to_string()).and_then(|raw| toml::from_str(&raw).map_err(|err| err.to_string())) {
+                Ok(config) => config,
+                Err(_err)  => { warn!("Failed to read cargo config '{}' ({}); ignoring it", config_path.display(), _err); continue; },
+            };
+            match config.get("build").and_then(|b| b.get("target")) {
+                Some(Value::String(triple)) => {
+                    trace!("Using target triple '{}' from cargo config '{}'", triple, config_path.display());
+                    return Some(triple.clone());
+                },
+                Some(_triple) => { warn!("Cargo config '{}' has a non-String 'build.target' ({}); ignoring it", config_path.display(), _triple.type_str()); },
+                None          => {},
+            }
+        }
+        None
+    }
+
+    /// Reads and parses the `Cargo.toml` file in the given package or workspace directory.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to the directory with the package (or workspace).
+    /// 
+    /// # Returns
+    /// The parsed contents of the file.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to find, read or parse the `Cargo.toml` file.
+    fn read_cargo_toml(path: &Path) -> Result<Value, Error> {
+        // Attempt to open the Cargo.toml file and read its contents
+        let cargo_path: PathBuf = path.join("Cargo.toml");
+        let cargo_toml: Vec<u8> = match fs::File::open(&cargo_path) {
+            Ok(mut handle) => {
+                let mut res: Vec<u8> = vec![];
+                match handle.read_to_end(&mut res) {
+                    Ok(_)    => res,
+                    Err(err) => { return Err(Error::CargoTomlReadError{ path: cargo_path, err }); },
+                }
+            },
+            Err(err) => {
+                if err.kind() == std::io::ErrorKind::NotFound { return Err(Error::MissingCargoToml{ path: cargo_path }); }
+                return Err(Error::CargoTomlOpenError{ path: cargo_path, err });
+            }
+        };
+
+        // Parse it with serde (and toml)
+        match toml::from_slice(&cargo_toml) {
+            Ok(cargo_toml) => Ok(cargo_toml),
+            Err(err)       => Err(Error::CargoTomlParseError{ path: cargo_path, err }),
+        }
+    }
+
+    /// Finds the names of all examples in the given package or workspace directory.
+    /// 
+    /// This includes examples explicitly defined as `[[example]]`s in the Cargo.toml and, unless `package.autoexamples` is false, those discovered in the `examples` directory (either as `examples/<name>.rs` or `examples/<name>/main.rs`). Workspace members are searched recursively.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to the directory with the package (or workspace).
+    /// 
+    /// # Returns
+    /// The names of the examples found, in no particular order.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to find, read or parse the `Cargo.toml` file, or to read the examples directory.
+    pub fn find_examples(path: impl AsRef<Path>) -> Result<Vec<String>, Error> {
+        let path: &Path = path.as_ref();
+        let cargo_path: PathBuf = path.join("Cargo.toml");
+        let table: Map<String, Value> = match Self::read_cargo_toml(path)? {
+            Value::Table(table) => table,
+            _                   => { return Err(Error::CargoTomlNotATable{ path: cargo_path }); },
+        };
+
+        // Collect the explicitly defined examples first
+        let mut names: Vec<String> = vec![];
+        if let Some(examples) = table.get("example") {
+            let examples: &[Value] = match examples {
+                Value::Array(examples) => examples,
+                examples               => { return Err(Error::CargoTomlExamplesTypeError{ path: cargo_path, data_type: examples.type_str() }); },
+            };
+            for e in examples {
+                let example: &Map<String, Value> = match e {
+                    Value::Table(example) => example,
+                    e                     => { return Err(Error::CargoTomlExampleTypeError{ path: cargo_path, data_type: e.type_str() }); },
+                };
+                names.push(match example.get("name") {
+                    Some(Value::String(name)) => name.clone(),
+                    Some(name)                => { return Err(Error::CargoTomlNameTypeError{ what: "example", path: cargo_path, data_type: name.type_str() }); },
+                    None                      => { return Err(Error::CargoTomlMissingName{ table: "[example]", path: cargo_path }); },
+                });
+            }
+        }
+
+        // Then discover the ones in the examples directory (if this is a package that allows it)
+        if let Some(package) = table.get("package") {
+            let examples_dir: PathBuf = path.join("examples");
+            if package.get("autoexamples") != Some(&Value::Boolean(false)) && examples_dir.is_dir() {
+                let entries: fs::ReadDir = match fs::read_dir(&examples_dir) {
+                    Ok(entries) => entries,
+                    Err(err)    => { return Err(Error::ExamplesDirReadError{ path: examples_dir, err }); },
+                };
+                for entry in entries {
+                    let entry_path: PathBuf = match entry {
+                        Ok(entry) => entry.path(),
+                        Err(err)  => { return Err(Error::ExamplesDirReadError{ path: examples_dir, err }); },
+                    };
+                    let name: Option<&str> = if entry_path.is_file() && entry_path.extension().map(|e| e == "rs").unwrap_or(false) {
+                        entry_path.file_stem().and_then(|n| n.to_str())
+                    } else if entry_path.join("main.rs").is_file() {
+                        entry_path.file_name().and_then(|n| n.to_str())
+                    } else {
+                        None
+                    };
+                    if let Some(name) = name {
+                        if !names.iter().any(|n| n == name) { names.push(name.into()); }
+                    }
+                }
+            }
+        }
+
+        // Finally, recurse into any workspace members
+        if let Some(Value::Array(members)) = table.get("workspace").and_then(|w| w.get("members")) {
+            for m in members {
+                if let Value::String(member) = m {
+                    names.append(&mut Self::find_examples(path.join(member))?);
+                }
+            }
+        }
+
+        // Done
+        Ok(names)
+    }
+
+    /// Deduces the list of effects for the examples built in the given package or workspace directory.
+    /// 
+    /// Every example produces an executable at `<target_dir>/<mode>/examples/<name>`, or at `<target_dir>/<triple>/<mode>/examples/<name>` when cross-compiling.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target-to-be (used for debugging purposes only).
+    /// - `path`: The path to the directory with the package (or workspace).
+    /// - `target_dir`: The directory where cargo writes its artifacts (see `CargoTarget::resolve_target_dir()`).
+    /// - `mode`: The CargoMode (i.e., release, debug or a custom profile) that we are building in.
+    /// - `triple`: The target triple that we compile for, if any. If given, cargo writes to a subdirectory named after it, and it determines the names of the executables. Otherwise, we assume the host is compiled for.
+    /// - `examples`: The names of the examples to build.
+    /// - `all_examples`: Whether to build all examples found, in addition to the given ones.
+    /// - `cache`: The Cache that we use to keep track of file changed.
+    /// 
+    /// # Returns
+    /// A vector of effects, one per example.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to find the examples, or if any of the requested examples does not exist.
+    #[allow(clippy::too_many_arguments)]
+    pub fn deduce_example_effects(name: impl AsRef<str>, path: impl AsRef<Path>, target_dir: impl AsRef<Path>, mode: &CargoMode, triple: Option<&str>, examples: &[String], all_examples: bool, cache: Arc<Cache>) -> Result<Vec<Box<dyn Effect>>, Error> {
+        let name       : &str            = name.as_ref();
+        let path       : &Path           = path.as_ref();
+        let target_dir : &Path           = target_dir.as_ref();
+        let build_dir  : PathBuf         = build_dir(target_dir, triple, mode);
+        let os         : OperatingSystem = triple.map(triple_os).unwrap_or(OperatingSystem::host());
+        trace!("Deducing example effects for CargoTarget '{}' in directory '{}'", name, path.display());
+
+        // Find which examples exist, and assert the requested ones are among them
+        let found: Vec<String> = Self::find_examples(path)?;
+        for e in examples {
+            if !found.contains(e) { return Err(Error::UnknownExample{ path: path.into(), example: e.clone() }); }
+        }
+        let mut names: Vec<&String> = if all_examples { found.iter().collect() } else { examples.iter().collect() };
+        names.sort_unstable();
+        names.dedup();
+
+        // Cast them to effects
+        let res: Vec<Box<dyn Effect>> = names.into_iter().map(|n| {
+            let path: PathBuf = build_dir.join("examples").join(exe_name(n, os));
+            Box::new(File::new(format!("{}_example_{}", name, n), cache.clone(), path)) as Box<dyn Effect>
+        }).collect();
+        debug!("Example effects deduced from '{}': {:?}", path.display(), res.iter().map(|e| e.name()).collect::<Vec<&str>>());
+        Ok(res)
+    }
+
+    /// Finds the binaries that cargo automatically creates for the files in a package's `src/bin` directory (i.e., `src/bin/<name>.rs` or `src/bin/<name>/main.rs`).
+    /// 
+    /// # Arguments
+    /// - `path`: The path to the package directory.
+    /// - `package`: The `[package]` table of the package's Cargo.toml, which may disable this with `autobins = false`.
+    /// 
+    /// # Returns
+    /// The (sorted) names of the binaries found, together with the path of their source file.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read the `src/bin` directory.
+    fn find_autobins(path: &Path, package: &Value) -> Result<Vec<(String, PathBuf)>, Error> {
+        let bin_dir: PathBuf = path.join("src").join("bin");
+        if package.get("autobins") == Some(&Value::Boolean(false)) || !bin_dir.is_dir() { return Ok(vec![]); }
+
+        // Go through the directory
+        let entries: fs::ReadDir = match fs::read_dir(&bin_dir) {
+            Ok(entries) => entries,
+            Err(err)    => { return Err(Error::BinDirReadError{ path: bin_dir, err }); },
+        };
+        let mut names: Vec<(String, PathBuf)> = vec![];
+        for entry in entries {
+            let entry_path: PathBuf = match entry {
+                Ok(entry) => entry.path(),
+                Err(err)  => { return Err(Error::BinDirReadError{ path: bin_dir, err }); },
+            };
+            let bin: Option<(&str, PathBuf)> = if entry_path.is_file() && entry_path.extension().map(|e| e == "rs").unwrap_or(false) {
+                entry_path.file_stem().and_then(|n| n.to_str()).map(|n| (n, entry_path.clone()))
+            } else if entry_path.join("main.rs").is_file() {
+                entry_path.file_name().and_then(|n| n.to_str()).map(|n| (n, entry_path.join("main.rs")))
+            } else {
+                None
+            };
+            if let Some((name, source)) = bin { names.push((name.into(), source)); }
+        }
+
+        // Done
+        names.sort_unstable();
+        Ok(names)
+    }
+
+    /// Finds the files that cargo produces for the library of a package, if it has one (i.e., a `[lib]` table or a `src/lib.rs`).
+    /// 
+    /// The library is named after `lib.name`, or the package name (with dashes replaced by underscores) otherwise. Which files are produced depends on its `crate-type`s: an `rlib` for `lib` and `rlib`, a platform-specific dynamic library for `dylib`, `cdylib` and `proc-macro`, and a static archive for `staticlib`.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to the package directory.
+    /// - `table`: The toplevel table of the package's Cargo.toml.
+    /// - `package_name`: The name of the package.
+    /// - `os`: The OperatingSystem that we compile for, which determines the names of dynamic and static libraries.
+    /// 
+    /// # Returns
+    /// The names of the files produced for the library. Empty if the package has no library.
+    /// 
+    /// # Errors
+    /// This function errors if the `[lib]` table is ill-formed.
+    fn find_lib_files(path: &Path, table: &Map<String, Value>, package_name: &str, os: OperatingSystem) -> Result<Vec<String>, Error> {
+        let cargo_path: PathBuf = path.join("Cargo.toml");
+        let lib: Option<&Map<String, Value>> = match table.get("lib") {
+            Some(Value::Table(lib)) => Some(lib),
+            Some(lib)               => { return Err(Error::CargoTomlLibTypeError{ path: cargo_path, data_type: lib.type_str() }); },
+            None                    => None,
+        };
+        if lib.is_none() && !path.join("src").join("lib.rs").is_file() { return Ok(vec![]); }
+
+        // Resolve its name
+        let name: String = match lib.and_then(|l| l.get("name")) {
+            Some(Value::String(name)) => name.clone(),
+            Some(name)                => { return Err(Error::CargoTomlNameTypeError{ what: "lib", path: cargo_path, data_type: name.type_str() }); },
+            None                      => package_name.replace('-', "_"),
+        };
+
+        // Map the crate types to files
+        let crate_types: Vec<&str> = match lib.and_then(|l| l.get("crate-type")) {
+            Some(Value::Array(types)) => {
+                let mut res: Vec<&str> = Vec::with_capacity(types.len());
+                for t in types {
+                    match t {
+                        Value::String(t) => { res.push(t); },
+                        t                => { return Err(Error::CargoTomlCrateTypeError{ path: cargo_path, data_type: t.type_str() }); },
+                    }
+                }
+                res
+            },
+            Some(types) => { return Err(Error::CargoTomlCrateTypeError{ path: cargo_path, data_type: types.type_str() }); },
+            None        => vec![ "lib" ],
+        };
+        let mut res: Vec<String> = Vec::with_capacity(crate_types.len());
+        for t in crate_types {
+            let file: String = match lib_file_name(&name, t, os) {
+                Some(file) => file,
+                None       => {
+                    warn!("{}: Unknown crate type '{}' (ignoring it)", cargo_path.display(), t);
+                    continue;
+                },
+            };
+            if !res.contains(&file) { res.push(file); }
+        }
+        Ok(res)
+    }
+
+    /// Resolves which features of a package are enabled, i.e., the given ones and the default ones, together with all the features they enable in turn.
+    /// 
+    /// # Arguments
+    /// - `features`: The features that are explicitly enabled.
+    /// - `implied`: A closure that returns the features (or dependencies) that the given feature enables in turn, as declared by the package.
+    /// 
+    /// # Returns
+    /// The names of all enabled features.
+    fn enabled_features<'f>(features: &[String], implied: impl Fn(&str) -> Vec<&'f str>) -> HashSet<String> {
+        let mut todo: Vec<String> = features.iter().cloned().chain([ "default".to_string() ]).collect();
+        let mut enabled: HashSet<String> = HashSet::new();
+        while let Some(feature) = todo.pop() {
+            if !enabled.insert(feature.clone()) { continue; }
+            // Only other features of this package are relevant (not those of dependencies)
+            todo.extend(implied(&feature).into_iter().filter(|f| !f.contains(':') && !f.contains('/')).map(String::from));
+        }
+        enabled
+    }
+
+    /// Deduces the list of effects from either the given package or workspace directory by inspecting the Cargo.toml.
+    /// 
+    /// If the path points to a package, the resulting binary file (either the lib or name) read from the Cargo.toml or deduced is returned. Any binaries that cargo automatically discovers in `src/bin` (or `src/main.rs`) are added too, unless `package.autobins` is false or a `[[bin]]` already uses that file as its `path`, as are the files produced for its library (an rlib, dynamic library and/or static library, depending on its `crate-type`).
+    /// 
+    /// Otherwise, it recursively collects resulting binaries from each package in the workspace.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target-to-be (used for debugging purposes only).
+    /// - `path`: The path to the directory with the package (or workspace).
+    /// - `target_dir`: The directory where cargo writes its artifacts (see `CargoTarget::resolve_target_dir()`).
+    /// - `mode`: The CargoMode (i.e., release, debug or a custom profile) that we are building in.
+    /// - `triple`: The target triple that we compile for, if any. If given, cargo writes to a subdirectory named after it, and it determines the names of the binaries (e.g., `.exe` on Windows) and libraries. Otherwise, we assume the host is compiled for.
+    /// - `features`: The features that are enabled (besides the default ones). Binaries whose `required-features` are not all enabled are skipped, since cargo won't build them. Note that the default features are always assumed to be enabled, so this may over-report binaries gated behind them if cargo runs with `--no-default-features`.
+    /// - `cache`: The Cache that we use to keep track of file changed.
+    /// 
+    /// # Returns
+    /// A vector of effects, each of which is the (relevant) output file(s) of a package.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to find, read or parse the `Cargo.toml` file.
+    pub fn deduce_effects(name: impl AsRef<str>, path: impl AsRef<Path>, target_dir: impl AsRef<Path>, mode: &CargoMode, triple: Option<&str>, features: &[String], cache: Arc<Cache>) -> Result<Vec<Box<dyn Effect>>, Error> {
+        let name       : &str            = name.as_ref();
+        let path       : &Path           = path.as_ref();
+        let target_dir : &Path           = target_dir.as_ref();
+        let ws_dir     : &Path           = path;
+        let build_dir  : PathBuf         = build_dir(target_dir, triple, mode);
+        let os         : OperatingSystem = triple.map(triple_os).unwrap_or(OperatingSystem::host());
+        trace!("Duducing effects for CargoTarget '{}' in directory '{}'", name, path.display());
+
+        // Attempt to read the Cargo.toml file
+        let cargo_path: PathBuf = path.join("Cargo.toml");
+        let cargo_toml: Value = Self::read_cargo_toml(path)?;
+
+        // The file must be a toplevel table
+        debug!("Extracting effects from '{}'...", cargo_path.display());
+        if let Value::Table(table) = cargo_toml {
+            // If there is a toplevel '[[bin]]', we can deduce the name; otherwise, assume the name
+            let enabled: HashSet<String> = Self::enabled_features(features, |f| match table.get("features").and_then(|fs| fs.get(f)) {
+                Some(Value::Array(implied)) => implied.iter().filter_map(|i| i.as_str()).collect(),
+                _                           => vec![],
+            });
+            let mut skipped: Vec<String> = vec![];
+            let mut sources: Vec<PathBuf> = vec![];
+            let mut names: Vec<String> = if let Some(bins) = table.get("bin") {
+                // Assert it is an array
+                let bins: &[Value] = match bins {
+                    Value::Array(bins) => bins,
+                    bins               => { return Err(Error::CargoTomlBinsTypeError{ path: cargo_path, data_type: bins.type_str() }); },  
+                };
+
+                // Add all the binaries
+                let mut names: Vec<String> = Vec::with_capacity(bins.len());
+                for b in bins {
+                    // Assert it is a table
+                    let bin: &Map<String, Value> = match b {
+                        Value::Table(bin) => bin,
+                        b                 => { return Err(Error::CargoTomlBinTypeError{ path: cargo_path, data_type: b.type_str() }); },
+                    };
+
+                    // Fetch the name field to add it
+                    let bin_name: String = match bin.get("name") {
+                        Some(Value::String(name)) => name.clone(),
+                        Some(name)                => { return Err(Error::CargoTomlNameTypeError { what: "bin", path: cargo_path, data_type: name.type_str() }); },
+                        None                      => { return Err(Error::CargoTomlMissingName { table: "[bin]", path: cargo_path }); },
+                    };
+                    // Remember its custom source file, since cargo won't discover another binary for it
+                    if let Some(Value::String(source)) = bin.get("path") { sources.push(path.join(source)); }
+
+                    // Skip it if cargo won't build it because not all of its required features are enabled
+                    let required: &[Value] = match bin.get("required-features") {
+                        Some(Value::Array(required)) => required,
+                        Some(required)               => { return Err(Error::CargoTomlRequiredFeaturesTypeError{ path: cargo_path, data_type: required.type_str() }); },
+                        None                         => &[],
+                    };
+                    if !required.iter().all(|f| f.as_str().map(|f| enabled.contains(f)).unwrap_or(false)) {
+                        debug!("Skipping binary '{}' in '{}' because not all of its required features are enabled", bin_name, cargo_path.display());
+                        skipped.push(bin_name);
+                        continue;
+                    }
+                    names.push(bin_name);
+                }
+                names
+
+            } else if let Some(package) = table.get("package") {
+                // Attempt to find the 'name' field
+                let package_name: &String = match package.get("name") {
+                    Some(Value::String(name)) => name,
+                    Some(name)                => { return Err(Error::CargoTomlNameTypeError{ what: "package", path: cargo_path, data_type: name.type_str() }); },
+                    None                      => { return Err(Error::CargoTomlMissingName{ table: "package", path: cargo_path }); },
+                };
+
+                // Only assume it produces a binary with that name if it has a `src/main.rs` or no other binaries or library either
+                if path.join("src").join("main.rs").is_file() || (Self::find_autobins(path, package)?.is_empty() && Self::find_lib_files(path, &table, package_name, os)?.is_empty()) { vec![ package_name.clone() ] } else { vec![] }
+
+            } else {
+                vec![]
+            };
+
+            // Add the binaries automatically discovered in `src/bin` (and `src/main.rs`, if it's not already covered by the package), unless they are already given explicitly by name or by source file
+            if let Some(package) = table.get("package") {
+                let mut autobins: Vec<(String, PathBuf)> = vec![];
+                if let (Some(Value::String(package_name)), true) = (package.get("name"), table.contains_key("bin")) {
+                    let main: PathBuf = path.join("src").join("main.rs");
+                    if package.get("autobins") != Some(&Value::Boolean(false)) && main.is_file() { autobins.push((package_name.clone(), main)); }
+                }
+                autobins.append(&mut Self::find_autobins(path, package)?);
+                for (n, source) in autobins {
+                    if !names.contains(&n) && !skipped.contains(&n) && !sources.contains(&source) { names.push(n); }
+                }
+            }
+
+            // Cast the names to paths, then to (File) effects
+            let mut res: Vec<Box<dyn Effect>> = names.into_iter().map(|n| {
+                // First, create a path from that
+                let bin_path: PathBuf = build_dir.join(exe_name(&n, os));
+
+                // Next, wrap it in a FileEffect
+                Box::new(File::new(format!("{}_{}", name, n), cache.clone(), bin_path)) as Box<dyn Effect>
+            }).collect();
+
+            // Add the files produced for the library, if any
+            if let Some(Value::String(package_name)) = table.get("package").and_then(|p| p.get("name")) {
+                for f in Self::find_lib_files(path, &table, package_name, os)? {
+                    let lib_path: PathBuf = build_dir.join(&f);
+                    res.push(Box::new(File::new(format!("{}_{}", name, f), cache.clone(), lib_path)));
+                }
+            }
+
+            // Recurse into any workspace files to handle those
+            if let Some(workspace) = table.get("workspace") {
+                // Get the list
+                let members: &[Value] = match workspace.get("members") {
+                    Some(Value::Array(members)) => members,
+                    Some(members)               => { return Err(Error::CargoTomlMembersTypeError{ path: cargo_path, data_type: members.type_str() }); },
+                    None                        => { return Err(Error::CargoTomlMissingMembers{ path: cargo_path }); },
+                };
+
+                // Unwrap the list to strings
+                let mut smembers: Vec<&String> = Vec::with_capacity(members.len());
+                for m in members {
+                    smembers.push(if let Value::String(member) = m {
+                        member
+                    } else {
+                        return Err(Error::CargoTomlMemberTypeError{ path: cargo_path, data_type: m.type_str() });
+                    });
+                }
+
+                // We can now recurse each of the members to find their package names (relative to the workspace directory)
+                for m in smembers {
+                    res.append(&mut Self::deduce_effects(name, ws_dir.join(m), target_dir, mode, triple, features, cache.clone())?);
+                }
+            }
+
+            // If the names are still empty, we failed
+            if res.is_empty() {
+                return Err(Error::CargoTomlEffectsDeduceError { path: cargo_path });
+            };
+
+            // Return that
+            debug!("Effects deduced from '{}': {:?}", cargo_path.display(), res.iter().map(|e| e.name()).collect::<Vec<&str>>());
+            Ok(res)
+        } else {
+            Err(Error::CargoTomlNotATable{ path: cargo_path })
+        }
+    }
+
+    /// Deduces the list of effects from the output of `cargo metadata` for the given package or workspace directory.
+    /// 
+    /// Unlike `CargoTarget::deduce_effects()`, this relies on cargo itself to discover the binaries and libraries of every package (e.g., also those in `src/bin`), such that it follows the manifest exactly. The artifacts are placed in the target directory reported by cargo.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target-to-be (used for debugging purposes only).
+    /// - `path`: The path to the directory with the package (or workspace), used for error messages.
+    /// - `metadata`: The CargoMetadata of the package (or workspace).
+    /// - `mode`: The CargoMode (i.e., release, debug or a custom profile) that we are building in.
+    /// - `triple`: The target triple that we compile for, if any. If given, cargo writes to a subdirectory named after it, and it determines the names of the binaries (e.g., `.exe` on Windows) and libraries. Otherwise, we assume the host is compiled for.
+    /// - `features`: The features that are enabled (besides the default ones). Binaries whose `required-features` are not all enabled are skipped, since cargo won't build them.
+    /// - `cache`: The Cache that we use to keep track of file changed.
+    /// 
+    /// # Returns
+    /// A vector of effects, each of which is the (relevant) output file(s) of a package.
+    /// 
+    /// # Errors
+    /// This function errors if cargo reported no binaries or libraries at all.
+    pub fn deduce_effects_metadata(name: impl AsRef<str>, path: impl AsRef<Path>, metadata: &CargoMetadata, mode: &CargoMode, triple: Option<&str>, features: &[String], cache: Arc<Cache>) -> Result<Vec<Box<dyn Effect>>, Error> {
+        let name      : &str            = name.as_ref();
+        let path      : &Path           = path.as_ref();
+        let build_dir : PathBuf         = build_dir(&metadata.target_directory, triple, mode);
+        let os        : OperatingSystem = triple.map(triple_os).unwrap_or(OperatingSystem::host());
+        trace!("Deducing effects for CargoTarget '{}' in directory '{}' from cargo metadata", name, path.display());
+
+        let mut res: Vec<Box<dyn Effect>> = vec![];
+        for package in &metadata.packages {
+            let enabled: HashSet<String> = Self::enabled_features(features, |f| package.features.get(f).map(|i| i.iter().map(String::as_str).collect()).unwrap_or_default());
+
+            // Collect the binaries first, then the library files (like the Cargo.toml deduction does)
+            let mut files: Vec<String> = vec![];
+            for target in package.targets.iter().filter(|t| t.kind.iter().any(|k| k == "bin")) {
+                if !target.required_features.iter().all(|f| enabled.contains(f)) {
+                    debug!("Skipping binary '{}' in package '{}' because not all of its required features are enabled", target.name, package.name);
+                    continue;
+                }
+                files.push(exe_name(&target.name, os));
+            }
+            for target in package.targets.iter().filter(|t| t.kind.iter().any(|k| matches!(k.as_str(), "lib" | "rlib" | "dylib" | "cdylib" | "staticlib" | "proc-macro"))) {
+                let lib_name: String = target.name.replace('-', "_");
+                for t in &target.crate_types {
+                    if let Some(file) = lib_file_name(&lib_name, t, os) {
+                        if !files.contains(&file) { files.push(file); }
+                    }
+                }
+            }
+
+            // Cast them to (File) effects
+            res.extend(files.into_iter().map(|f| {
+                let file_path: PathBuf = build_dir.join(&f);
+                Box::new(File::new(format!("{}_{}", name, f), cache.clone(), file_path)) as Box<dyn Effect>
+            }));
+        }
+
+        // If we found nothing, we failed
+        if res.is_empty() { return Err(Error::CargoTomlEffectsDeduceError{ path: path.join("Cargo.toml") }); }
+        debug!("Effects deduced from cargo metadata for '{}': {:?}", path.display(), res.iter().map(|e| e.name()).collect::<Vec<&str>>());
+        Ok(res)
+    }
+
+
+
+    /// Runs the given `cargo build` command with JSON output and counts the warnings it emits.
+    /// 
+    /// The rendered compiler messages are forwarded to the log, and the count is remembered such that it can be retrieved later with `CargoTarget::warnings()`.
+    /// 
+    /// # Arguments
+    /// - `command`: The `cargo build` command to run (see `CargoTarget::command()`).
+    /// - `dry_run`: If true, does not actually run cargo but instead just prints what would be run (and counts no warnings).
+    /// 
+    /// # Returns
+    /// The number of warnings cargo emitted.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to launch cargo, if it timed out or if the build itself failed.
+    fn count_warnings(&self, command: &ShellCommand, dry_run: bool) -> Result<usize, TargetError> {
+        // Prepare the command
+        let mut command: ShellCommand = command.clone();
+        command.add_arg("--message-format=json");
+        if dry_run {
+            run_cargo_command(&self.name, &command, self.timeout, dry_run)?;
+            return Ok(0);
+        }
+
+        // Run it, counting the warnings in the compiler messages (any line we can't parse is not a message)
+        trace!("Running {}", command);
+        let mut count: usize = 0;
+        let callback = |line: StreamLine| match line {
+            StreamLine::Stdout(line) => match serde_json::from_str::<CargoMessage>(&line) {
+                Ok(CargoMessage{ reason, message: Some(message) }) if reason == "compiler-message" => {
+                    if message.level == "warning" { count += 1; }
+                    if let Some(_rendered) = message.rendered { debug!("{}: {}", self.name, _rendered.trim_end()); }
+                },
+                Ok(_)  => {},
+                Err(_) => { debug!("{}: {}", self.name, line); },
+            },
+            StreamLine::Stderr(_line) => { debug!("{}: {}", self.name, _line); },
+        };
+        let res: Result<i32, ShellCommandError> = match self.timeout {
+            Some(timeout) => command.run_streaming_timeout(timeout, callback),
+            None          => command.run_streaming(callback),
+        };
+        let code: i32 = match res {
+            Ok(code) => code,
+            Err(err) => { return Err(TargetError::BuildError{ name: self.name.clone(), err: Box::new(err) }); },
+        };
+        debug!("'cargo build' in '{}' emitted {} warning(s)", self.path.display(), count);
+        *self.warnings.lock().unwrap_or_else(|err| err.into_inner()) = Some(count);
+        if code != 0 { return Err(TargetError::BuildError{ name: self.name.clone(), err: Box::new(Error::CargoBuildFailure{ path: self.path.clone(), code: Some(code) }) }); }
+        Ok(count)
+    }
+
+
+
+    /// Returns the `cargo build` command that builds this target.
+    /// 
+    /// # Arguments
+    /// - `os`: The target OS that we intend to build.
+    /// - `arch`: The target architecture that we intend to build.
+    /// 
+    /// # Returns
+    /// A new ShellCommand that runs cargo (with `--target` only if we're cross-compiling or have a default target triple).
+    pub fn command(&self, os: OperatingSystem, arch: Architecture) -> ShellCommand {
+        let triple: Option<String> = cargo_triple(os, arch, self.libc, self.triple.as_deref());
+        let mut cmd: ShellCommand = cargo_command("build", &self.path, &self.mode, &self.packages, &self.features, triple.as_deref(), self.toolchain.as_deref());
+        add_feature_flags(&mut cmd, self.all_features, self.no_default_features);
+        add_lock_flags(&mut cmd, self.locked, self.offline, self.frozen);
+        set_cargo_exec(&mut cmd, self.target_dir.as_deref(), &self.cargo_bin, self.use_cross, &self.envs);
+        cmd
+    }
+
+    /// Returns the `cargo build` command that builds the examples of this target, if it builds any.
+    /// 
+    /// Cargo only builds the examples it is asked for if it is given such a flag, and then nothing else. As such, they are built with a separate command.
+    /// 
+    /// # Arguments
+    /// - `os`: The target OS that we intend to build.
+    /// - `arch`: The target architecture that we intend to build.
+    /// 
+    /// # Returns
+    /// A new ShellCommand that runs cargo like `CargoTarget::command()` but with `--examples` or `--example <name>` appended, or `None` if this target builds no examples.
+    pub fn examples_command(&self, os: OperatingSystem, arch: Architecture) -> Option<ShellCommand> {
+        if !self.all_examples && self.examples.is_empty() { return None; }
+        let mut cmd: ShellCommand = self.command(os, arch);
+        if self.all_examples {
+            cmd.add_arg("--examples");
+        } else {
+            for e in &self.examples { cmd.add_args([ "--example", e ]); }
+        }
+        Some(cmd)
+    }
+
+
+
+    /// Returns the path to the directory where this target builds.
+    #[inline]
+    pub fn path(&self) -> &Path { &self.path }
+
+    /// Returns the list of packages we're building.
+    #[inline]
+    pub fn packages(&self) -> &[String] { &self.packages }
+
+    /// Returns the mode in which we're building.
+    #[inline]
+    pub fn mode(&self) -> &CargoMode { &self.mode }
+
+    /// Returns the target triple we build for by default (either given explicitly or read from cargo's configuration), if not the host's.
+    #[inline]
+    pub fn triple(&self) -> Option<&str> { self.triple.as_deref() }
+
+    /// Returns the directory where cargo writes its artifacts, if not the default one.
+    #[inline]
+    pub fn target_dir(&self) -> Option<&Path> { self.target_dir.as_deref() }
+
+    /// Returns the C library we link against when compiling for Linux.
+    #[inline]
+    pub fn libc(&self) -> Libc { self.libc }
+
+    /// Returns the features we're enabling (either given explicitly or read from the environment).
+    #[inline]
+    pub fn features(&self) -> &[String] { &self.features }
+
+    /// Returns whether we're enabling all features.
+    #[inline]
+    pub fn all_features(&self) -> bool { self.all_features }
+
+    /// Returns whether we're disabling the default features.
+    #[inline]
+    pub fn no_default_features(&self) -> bool { self.no_default_features }
+
+    /// Returns whether cargo must fail if the `Cargo.lock` is missing or out-of-date.
+    #[inline]
+    pub fn locked(&self) -> bool { self.locked }
+
+    /// Returns whether cargo may not access the network.
+    #[inline]
+    pub fn offline(&self) -> bool { self.offline }
+
+    /// Returns whether cargo must use the `Cargo.lock` as-is and may not access the network.
+    #[inline]
+    pub fn frozen(&self) -> bool { self.frozen }
+
+    /// Returns the list of examples we're building (besides all of them if `CargoTarget::all_examples()` is true).
+    #[inline]
+    pub fn examples(&self) -> &[String] { &self.examples }
+
+    /// Returns whether we're building all examples.
+    #[inline]
+    pub fn all_examples(&self) -> bool { self.all_examples }
+
+    /// Returns the maximum time the build may take, if any.
+    #[inline]
+    pub fn timeout(&self) -> Option<Duration> { self.timeout }
+
+    /// Returns whether we fail the build if cargo emits any warnings.
+    #[inline]
+    pub fn deny_warnings(&self) -> bool { self.deny_warnings }
+
+    /// Returns the environment variables that we set for cargo, on top of the inherited ones.
+    #[inline]
+    pub fn envs(&self) -> &HashMap<String, String> { &self.envs }
+
+    /// Returns the rustup toolchain we build with, if not the default one.
+    #[inline]
+    pub fn toolchain(&self) -> Option<&str> { self.toolchain.as_deref() }
+
+    /// Returns the cargo executable that builds this target.
+    #[inline]
+    pub fn cargo_bin(&self) -> &str { &self.cargo_bin }
+
+    /// Returns whether we build with `cross` instead of the cargo executable.
+    #[inline]
+    pub fn use_cross(&self) -> bool { self.use_cross }
+
+    /// Returns whether we install missing target triples with rustup before cross-compiling.
+    #[inline]
+    pub fn auto_install_target(&self) -> bool { self.auto_install_target }
+}
+
+impl<'a> Named for CargoTarget<'a> {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+impl<'a> Target for CargoTarget<'a> {
+    fn build(&self, os: OperatingSystem, arch: Architecture, dry_run: bool) -> Result<(), TargetError> {
+        if os != OperatingSystem::host() || arch != Architecture::host() {
+            // The deduced effects only live where cargo writes for the builder's triple, so refuse to build anything else
+            let target: String = target_triple(os, arch, self.libc);
+            if self.deduced && self.triple.as_deref() != Some(target.as_str()) {
+                return Err(TargetError::BuildError{ name: self.name.clone(), err: Box::new(Error::TripleMismatch{ path: self.path.clone(), expected: self.triple.clone(), got: target }) });
+            }
+
+            // Make sure the target is installed if we're cross-compiling (unless cross takes care of that)
+            if self.auto_install_target && !self.use_cross {
+                install_target(&self.name, &self.rustup, &target, dry_run)?;
+            }
+        }
+
+        // Now run the build itself, counting warnings while at it if we have to
+        let command: ShellCommand = self.command(os, arch);
+        debug!("{}: Building with '{}'", self.name, command);
+        if self.deny_warnings {
+            let count: usize = self.count_warnings(&command, dry_run)?;
+            if count > 0 { return Err(TargetError::BuildError{ name: self.name.clone(), err: Box::new(Error::CargoBuildWarnings{ path: self.path.clone(), count }) }); }
+        } else {
+            run_cargo_command(&self.name, &command, self.timeout, dry_run)?;
+        }
+
+        // Build the examples too, if any
+        if let Some(command) = self.examples_command(os, arch) {
+            debug!("{}: Building examples with '{}'", self.name, command);
+            run_cargo_command(&self.name, &command, self.timeout, dry_run)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn kind(&self) -> &str { "cargo" }
+    #[inline]
+    fn resource_class(&self) -> &str { CARGO_RESOURCE_CLASS }
+
+    /// Returns the number of warnings that cargo emitted during the last build.
+    /// 
+    /// This is only counted if `CargoTarget::deny_warnings()` is true, and is `None` before the target has been built.
+    #[inline]
+    fn warnings(&self) -> Option<usize> { *self.warnings.lock().unwrap_or_else(|err| err.into_inner()) }
+
+
+
+    #[inline]
+    fn deps(&self) -> &[EffectView<'_>] { &self.deps }
+
+    #[inline]
+    fn effects(&self) -> &[Box<dyn Effect>] { &self.effects }
+    #[inline]
+    fn inputs(&self) -> &[Box<dyn Effect>] { &self.inputs }
+}
+
+
+
+/// Defines a target that runs `cargo test` for a package (or workspace), failing the build if any test fails.
+/// 
+/// Since the outcome of tests cannot be cached by their output, its only input and effect is the `TrueEffect` (like the `PhonyTarget`), such that it is run every time it is made.
+pub struct CargoTestTarget<'a> {
+    /// The name of this target.
+    name    : String,
+    /// The dependencies of this target.
+    deps    : Vec<EffectView<'a>>,
+    /// The effects of this target (always the `TrueEffect`).
+    effects : Vec<Box<dyn Effect>>,
+
+    /// The path of the directory where the target package (or workspace) lives.
+    path     : PathBuf,
+    /// The packages that we test in this run.
+    packages : Vec<String>,
+    /// The features that we enable in this run.
+    features : Vec<String>,
+    /// The mode (i.e., release or debug) in which we build the tests.
+    mode     : CargoMode,
+    /// The maximum time the tests may take.
+    timeout  : Option<Duration>,
+}
+
+impl<'a> CargoTestTarget<'a> {
+    /// Constructor for the CargoTestTarget.
+    /// 
+    /// By default, all packages in the given directory are tested in debug mode without any additional features (akin to running just `cargo test`).
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `deps`: An iterator with EffectViews that represent the dependencies of this target.
+    /// - `path`: The path to the package or workspace directory to test.
+    /// 
+    /// # Returns
+    /// A new CargoTestTarget instance.
+    #[inline]
+    pub fn new(name: impl Into<String>, deps: impl IntoIterator<Item = EffectView<'a>, IntoIter = impl Iterator<Item = EffectView<'a>>>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            name    : name.into(),
+            deps    : deps.into_iter().collect(),
+            effects : vec![ Box::new(TrueEffect) ],
+
+            path     : path.into(),
+            packages : vec![],
+            features : vec![],
+            mode     : CargoMode::Debug,
+            timeout  : None,
+        }
+    }
+
+
+
+    /// Adds a package to the list of packages that this target will test.
+    /// 
+    /// # Arguments
+    /// - `package`: The name/identifier of the package to test.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn package(mut self, package: impl Into<String>) -> Self {
+        self.packages.push(package.into());
+        self
+    }
+
+    /// Adds a feature to the list of features that this target enables.
+    /// 
+    /// # Arguments
+    /// - `feature`: The name of the feature to enable.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn feature(mut self, feature: impl Into<String>) -> Self {
+        self.features.push(feature.into());
+        self
+    }
+
+    /// Sets the mode in which the tests are built.
+    /// 
+    /// Defaults to `CargoMode::Debug`.
+    /// 
+    /// # Arguments
+    /// - `mode`: The mode in which to build the tests.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn mode(mut self, mode: CargoMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the maximum time that running the tests may take.
+    /// 
+    /// If cargo is still running once the timeout passes, it is killed and the build fails. By default, there is no limit.
+    /// 
+    /// # Arguments
+    /// - `timeout`: The maximum time the tests may take.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+
+
+    /// Returns the command that this target runs when built.
+    #[inline]
+    pub fn command(&self) -> ShellCommand { cargo_command("test", &self.path, &self.mode, &self.packages, &self.features, None, None) }
+}
+
+impl<'a> Named for CargoTestTarget<'a> {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+impl<'a> Target for CargoTestTarget<'a> {
+    #[inline]
+    fn build(&self, _os: OperatingSystem, _arch: Architecture, dry_run: bool) -> Result<(), TargetError> {
+        run_cargo_command(&self.name, &self.command(), self.timeout, dry_run)
+    }
+
+    #[inline]
+    fn kind(&self) -> &str { "cargo-test" }
+    #[inline]
+    fn resource_class(&self) -> &str { CARGO_RESOURCE_CLASS }
+
+
+
+    #[inline]
+    fn deps(&self) -> &[EffectView<'_>] { &self.deps }
+
+    #[inline]
+    fn effects(&self) -> &[Box<dyn Effect>] { &self.effects }
+    #[inline]
+    fn inputs(&self) -> &[Box<dyn Effect>] { &self.effects }
+}
+
+
+
+/// Defines a target that runs `cargo clippy -- -D warnings` for a package (or workspace), failing the build on any lint violation.
+/// 
+/// Like the `CargoTestTarget`, its only input and effect is the `TrueEffect`, such that it is run every time it is made.
+pub struct CargoClippyTarget<'a> {
+    /// The name of this target.
+    name    : String,
+    /// The dependencies of this target.
+    deps    : Vec<EffectView<'a>>,
+    /// The effects of this target (always the `TrueEffect`).
+    effects : Vec<Box<dyn Effect>>,
+
+    /// The path of the directory where the target package (or workspace) lives.
+    path     : PathBuf,
+    /// The packages that we lint in this run.
+    packages : Vec<String>,
+    /// The features that we enable in this run.
+    features : Vec<String>,
+    /// The mode (i.e., release or debug) in which we check the packages.
+    mode     : CargoMode,
+    /// The maximum time linting may take.
+    timeout  : Option<Duration>,
+
+    /// Whether to enable all features.
+    all_features        : bool,
+    /// Whether to disable the default features.
+    no_default_features : bool,
+
+    /// Whether to require the `Cargo.lock` to be up-to-date.
+    locked  : bool,
+    /// Whether to forbid cargo from accessing the network.
+    offline : bool,
+    /// Whether to require the `Cargo.lock` to be up-to-date and forbid cargo from accessing the network.
+    frozen  : bool,
+
+    /// The target triple to lint for when linting for the host, if not the host's.
+    triple     : Option<String>,
+    /// The C library to link against when compiling for Linux.
+    libc       : Libc,
+    /// The directory where cargo writes its artifacts, if not the default one.
+    target_dir : Option<PathBuf>,
+    /// The rustup toolchain to run cargo with, if not the default one.
+    toolchain  : Option<String>,
+    /// The cargo executable to run.
+    cargo_bin  : String,
+    /// Whether to run `cross` instead of the cargo executable.
+    use_cross  : bool,
+    /// The environment variables to set for cargo, on top of the inherited ones.
+    envs       : HashMap<String, String>,
+}
+
+impl<'a> CargoClippyTarget<'a> {
+    /// Constructor for the CargoClippyTarget.
+    /// 
+    /// By default, all packages in the given directory are linted in debug mode without any additional features (akin to running just `cargo clippy -- -D warnings`).
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `deps`: An iterator with EffectViews that represent the dependencies of this target.
+    /// - `path`: The path to the package or workspace directory to lint.
+    /// 
+    /// # Returns
+    /// A new CargoClippyTarget instance.
+    #[inline]
+    pub fn new(name: impl Into<String>, deps: impl IntoIterator<Item = EffectView<'a>, IntoIter = impl Iterator<Item = EffectView<'a>>>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            name    : name.into(),
+            deps    : deps.into_iter().collect(),
+            effects : vec![ Box::new(TrueEffect) ],
+
+            path     : path.into(),
+            packages : vec![],
+            features : vec![],
+            mode     : CargoMode::Debug,
+            timeout  : None,
+
+            all_features        : false,
+            no_default_features : false,
+
+            locked  : false,
+            offline : false,
+            frozen  : false,
+
+            triple     : None,
+            libc       : Libc::default(),
+            target_dir : None,
+            toolchain  : None,
+            cargo_bin  : "cargo".into(),
+            use_cross  : false,
+            envs       : HashMap::new(),
+        }
+    }
+
+    /// Constructor for the CargoClippyTarget that lints what the given CargoTarget builds.
+    /// 
+    /// It lints the same packages with the same features in the same mode, and runs cargo like the CargoTarget does (i.e., with the same lockfile and network restrictions, default triple, C library, target directory, toolchain, cargo executable and environment variables).
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `deps`: An iterator with EffectViews that represent the dependencies of this target.
+    /// - `target`: The CargoTarget to lint the packages of.
+    /// 
+    /// # Returns
+    /// A new CargoClippyTarget instance.
+    pub fn from_target(name: impl Into<String>, deps: impl IntoIterator<Item = EffectView<'a>, IntoIter = impl Iterator<Item = EffectView<'a>>>, target: &CargoTarget) -> Self {
+        Self {
+            name    : name.into(),
+            deps    : deps.into_iter().collect(),
+            effects : vec![ Box::new(TrueEffect) ],
+
+            path     : target.path.clone(),
+            packages : target.packages.clone(),
+            features : target.features.clone(),
+            mode     : target.mode.clone(),
+            timeout  : target.timeout,
+
+            all_features        : target.all_features,
+            no_default_features : target.no_default_features,
+
+            locked  : target.locked,
+            offline : target.offline,
+            frozen  : target.frozen,
+
+            triple     : target.triple.clone(),
+            libc       : target.libc,
+            target_dir : target.target_dir.clone(),
+            toolchain  : target.toolchain.clone(),
+            cargo_bin  : target.cargo_bin.clone(),
+            use_cross  : target.use_cross,
+            envs       : target.envs.clone(),
+        }
+    }
+
+
+
+    /// Adds a package to the list of packages that this target will lint.
+    /// 
+    /// # Arguments
+    /// - `package`: The name/identifier of the package to lint.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn package(mut self, package: impl Into<String>) -> Self {
+        self.packages.push(package.into());
+        self
+    }
+
+    /// Adds a feature to the list of features that this target enables.
+    /// 
+    /// # Arguments
+    /// - `feature`: The name of the feature to enable.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn feature(mut self, feature: impl Into<String>) -> Self {
+        self.features.push(feature.into());
+        self
+    }
+
+    /// Sets the mode in which the packages are checked.
+    /// 
+    /// Defaults to `CargoMode::Debug`.
+    /// 
+    /// # Arguments
+    /// - `mode`: The mode in which to check the packages.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn mode(mut self, mode: CargoMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the maximum time that linting may take.
+    /// 
+    /// If cargo is still running once the timeout passes, it is killed and the build fails. By default, there is no limit.
+    /// 
+    /// # Arguments
+    /// - `timeout`: The maximum time linting may take.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+
+
+    /// Returns the command that this target runs when built for the given OS and architecture.
+    /// 
+    /// Like for the `CargoTarget`, the target triple is only passed to cargo if they are not the host's (or if it has a default triple).
+    pub fn command(&self, os: OperatingSystem, arch: Architecture) -> ShellCommand {
+        let triple: Option<String> = cargo_triple(os, arch, self.libc, self.triple.as_deref());
+        let mut cmd: ShellCommand = cargo_command("clippy", &self.path, &self.mode, &self.packages, &self.features, triple.as_deref(), self.toolchain.as_deref());
+        add_feature_flags(&mut cmd, self.all_features, self.no_default_features);
+        add_lock_flags(&mut cmd, self.locked, self.offline, self.frozen);
+        set_cargo_exec(&mut cmd, self.target_dir.as_deref(), &self.cargo_bin, self.use_cross, &self.envs);
+        cmd.add_args([ "--", "-D", "warnings" ]);
+        cmd
+    }
+}
+
+impl<'a> Named for CargoClippyTarget<'a> {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+impl<'a> Target for CargoClippyTarget<'a> {
+    #[inline]
+    fn build(&self, os: OperatingSystem, arch: Architecture, dry_run: bool) -> Result<(), TargetError> {
+        run_cargo_command(&self.name, &self.command(os, arch), self.timeout, dry_run)
+    }
+
+    #[inline]
+    fn kind(&self) -> &str { "cargo-clippy" }
+    #[inline]
+    fn resource_class(&self) -> &str { CARGO_RESOURCE_CLASS }
+
+
+
+    #[inline]
+    fn deps(&self) -> &[EffectView<'_>] { &self.deps }
+
+    #[inline]
+    fn effects(&self) -> &[Box<dyn Effect>] { &self.effects }
+    #[inline]
+    fn inputs(&self) -> &[Box<dyn Effect>] { &self.effects }
+}
+
+
+
+/// Defines a target that runs `cargo fmt --check` for a package (or workspace), failing the build if its formatting drifted.
+/// 
+/// Like the `CargoTestTarget`, its only input and effect is the `TrueEffect`, such that it is run every time it is made.
+pub struct CargoFmtTarget<'a> {
+    /// The name of this target.
+    name    : String,
+    /// The dependencies of this target.
+    deps    : Vec<EffectView<'a>>,
+    /// The effects of this target (always the `TrueEffect`).
+    effects : Vec<Box<dyn Effect>>,
+
+    /// The path of the directory where the target package (or workspace) lives.
+    path     : PathBuf,
+    /// The packages that we check in this run.
+    packages : Vec<String>,
+}
+
+impl<'a> CargoFmtTarget<'a> {
+    /// Constructor for the CargoFmtTarget.
+    /// 
+    /// By default, all packages in the given directory are checked (akin to running `cargo fmt --all --check`).
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `deps`: An iterator with EffectViews that represent the dependencies of this target.
+    /// - `path`: The path to the package or workspace directory to check.
+    /// 
+    /// # Returns
+    /// A new CargoFmtTarget instance.
+    #[inline]
+    pub fn new(name: impl Into<String>, deps: impl IntoIterator<Item = EffectView<'a>, IntoIter = impl Iterator<Item = EffectView<'a>>>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            name    : name.into(),
+            deps    : deps.into_iter().collect(),
+            effects : vec![ Box::new(TrueEffect) ],
+
+            path     : path.into(),
+            packages : vec![],
+        }
+    }
+
+
+
+    /// Adds a package to the list of packages that this target will check.
+    /// 
+    /// If you specify no packages at all, then all packages in the directory are checked.
+    /// 
+    /// # Arguments
+    /// - `package`: The name/identifier of the package to check.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn package(mut self, package: impl Into<String>) -> Self {
+        self.packages.push(package.into());
+        self
+    }
+
+
+
+    /// Returns the command that this target runs when built.
+    pub fn command(&self) -> ShellCommand {
+        let mut cmd: ShellCommand = ShellCommand::with_cwd("cargo", &self.path);
+        cmd.add_arg("fmt");
+        if self.packages.is_empty() { cmd.add_arg("--all"); }
+        for p in &self.packages { cmd.add_args([ "--package", p ]); }
+        cmd.add_arg("--check");
+        cmd
+    }
+}
+
+impl<'a> Named for CargoFmtTarget<'a> {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+impl<'a> Target for CargoFmtTarget<'a> {
+    #[inline]
+    fn build(&self, _os: OperatingSystem, _arch: Architecture, dry_run: bool) -> Result<(), TargetError> {
+        run_cargo_command(&self.name, &self.command(), None, dry_run)
+    }
+
+    #[inline]
+    fn kind(&self) -> &str { "cargo-fmt" }
+
+
+
+    #[inline]
+    fn deps(&self) -> &[EffectView<'_>] { &self.deps }
+
+    #[inline]
+    fn effects(&self) -> &[Box<dyn Effect>] { &self.effects }
+    #[inline]
+    fn inputs(&self) -> &[Box<dyn Effect>] { &self.effects }
+}
+
+
+
+/// Defines the builder for the `CargoDocTarget`.
+/// 
+/// Note that you have to call at least `CargoDocTargetBuilder::path()` before calling `CargoDocTargetBuilder::build()`.
+/// 
+/// Also note that if you do not specify any effects, the generated documentation directory (`<target_dir>/doc`) is used as its only effect.
+pub struct CargoDocTargetBuilder<'a> {
+    /// The name of this target.
+    name    : String,
+    /// The dependencies of this target.
+    deps    : Vec<EffectView<'a>>,
+    /// The effects (that we care about) of this target.
+    effects : Option<Vec<Box<dyn Effect>>>,
+
+    /// The path of the directory where the target package (or workspace) lives.
+    path     : Option<PathBuf>,
+    /// The packages that we document in this run.
+    packages : Vec<String>,
+    /// The features that we enable in this run.
+    features : Vec<String>,
+    /// Whether to document private items as well.
+    private  : bool,
+}
+
+impl<'a> TargetBuilder<'a> for CargoDocTargetBuilder<'a> {
+    type Target = CargoDocTarget<'a>;
+
+
+    #[inline]
+    fn new(name: impl Into<String>) -> Self {
+        Self {
+            name    : name.into(),
+            deps    : vec![],
+            effects : None,
+
+            path     : None,
+            packages : vec![],
+            features : vec![],
+            private  : false,
+        }
+    }
+
+
+
+    #[inline]
+    fn dep(mut self, dep: EffectView<'a>) -> Self {
+        self.deps.push(dep);
+        self
+    }
+    #[inline]
+    fn deps(mut self, deps: impl IntoIterator<Item = EffectView<'a>, IntoIter = impl Iterator<Item = EffectView<'a>>>) -> Self {
+        // Collect them in a separate vector first
+        let mut new_deps: Vec<EffectView> = deps.into_iter().collect();
+        self.deps.append(&mut new_deps);
+        self
+    }
+
+    fn effect(mut self, effect: impl 'static + Effect) -> Self {
+        // Either set or add
+        if let Some(effects) = &mut self.effects {
+            effects.push(Box::new(effect));
+        } else {
+            self.effects = Some(vec![ Box::new(effect) ]);
+        }
+        self
+    }
+    fn effects(mut self, effects: impl IntoIterator<Item = impl 'static + Effect, IntoIter = impl Iterator<Item = impl 'static + Effect>>) -> Self {
+        // Collect them in a separate vector first
+        let mut new_effects: Vec<Box<dyn Effect>> = effects.into_iter().map(|e| Box::new(e) as Box<dyn Effect>).collect();
+
+        // Either set or add
+        if let Some(effects) = &mut self.effects {
+            effects.append(&mut new_effects);
+        } else {
+            self.effects = Some(new_effects);
+        }
+        self
+    }
+
+
+
+    fn build(self, cache: Arc<Cache>) -> Result<Self::Target, Box<dyn std::error::Error>> {
+        // Assert we have what we need and/or default
+        let path: PathBuf = match self.path {
+            Some(path) => path,
+            None       => { panic!("You have to call `CargoDocTargetBuilder::path()` before callign `CargoDocTargetBuilder::build()`"); },
+        };
+        let effects: Vec<Box<dyn Effect>> = match self.effects {
+            Some(effects) => effects,
+            None          => {
+                // Ask cargo itself, since the default target directory is relative to the workspace instead of where we run
+                let target_dir: PathBuf = CargoTarget::resolve_target_dir(&path, true);
+                vec![ Box::new(Directory::new(format!("{}_doc", self.name), cache.clone(), target_dir.join("doc"))) ]
+            },
         };
 
-        // The file must be a toplevel table
-        debug!("Extracting effects from '{}'...", cargo_path.display());
-        if let Value::Table(table) = cargo_toml {
-            // If there is a toplevel '[[bin]]', we can deduce the name; otherwise, assume the name
-            let names: Vec<String> = if let Some(bins) = table.get("bin") {
-                // Assert it is an array
-                let bins: &[Value] = match bins {
-                    Value::Array(bins) => bins,
-                    bins               => { return Err(Error::CargoTomlBinsTypeError{ path: cargo_path, data_type: bins.type_str() }); },  
-                };
+        // Fingerprint the configuration such that we rebuild when it changes
+        let fingerprint: Fingerprint = Fingerprint::new(format!("{}_fingerprint", self.name), cache, (&path, &self.packages, &self.features, self.private));
 
-                // Add all the binaries
-                let mut names: Vec<String> = Vec::with_capacity(bins.len());
-                for b in bins {
-                    // Assert it is a table
-                    let bin: &Map<String, Value> = match b {
-                        Value::Table(bin) => bin,
-                        b                 => { return Err(Error::CargoTomlBinTypeError{ path: cargo_path, data_type: b.type_str() }); },
-                    };
+        // Simply create a target with those properties
+        Ok(CargoDocTarget {
+            name   : self.name,
+            deps   : self.deps,
+            effects,
+            inputs : vec![ Box::new(fingerprint) ],
 
-                    // Fetch the name field to add it
-                    names.push(match bin.get("name") {
-                        Some(Value::String(name)) => name.clone(),
-                        Some(name)                => { return Err(Error::CargoTomlNameTypeError { what: "bin", path: cargo_path, data_type: name.type_str() }); },
-                        None                      => { return Err(Error::CargoTomlMissingName { table: "[bin]", path: cargo_path }); },
-                    });
-                }
-                names
+            path,
+            packages : self.packages,
+            features : self.features,
+            private  : self.private,
+        })
+    }
+}
 
-            } else if let Some(package) = table.get("package") {
-                // Attempt to find the 'name' field
-                match package.get("name") {
-                    Some(Value::String(name)) => vec![ name.clone() ],
-                    Some(name)                => { return Err(Error::CargoTomlNameTypeError{ what: "package", path: cargo_path, data_type: name.type_str() }); },
-                    None                      => { return Err(Error::CargoTomlMissingName{ table: "package", path: cargo_path }); },
-                }
+impl<'a> CargoDocTargetBuilder<'a> {
+    /// Sets the path of the directory that this CargoDocTargetBuilder operates in.
+    /// 
+    /// This function is mandatory to set before calling `CargoDocTargetBuilder::build()`.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to the package or workspace directory that this target documents.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
 
-            } else {
-                vec![]
-            };
+    /// Adds a package to the list of packages that this target will document.
+    /// 
+    /// If you specify no packages at all, then cargo's default packages are documented.
+    /// 
+    /// # Arguments
+    /// - `package`: The name/identifier of the package to document.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn package(mut self, package: impl Into<String>) -> Self {
+        self.packages.push(package.into());
+        self
+    }
 
-            // Cast the names to paths, then to (File) effects
-            let mut res: Vec<Box<dyn Effect>> = names.into_iter().map(|n| {
-                // First, create a path from that
-                let path: PathBuf = PathBuf::from("./target").join(mode.to_build_dir()).join(&n);
+    /// Adds a feature to the list of features that this target enables.
+    /// 
+    /// # Arguments
+    /// - `feature`: The name of the feature to enable.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn feature(mut self, feature: impl Into<String>) -> Self {
+        self.features.push(feature.into());
+        self
+    }
 
-                // Next, wrap it in a FileEffect
-                Box::new(File::new(format!("{}_{}", name, n), cache.clone(), path)) as Box<dyn Effect>
-            }).collect();
+    /// Sets whether private items are documented as well (akin to `cargo doc --document-private-items`).
+    /// 
+    /// Defaults to `false`.
+    /// 
+    /// # Arguments
+    /// - `private`: Whether to document private items (true) or only public ones (false).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn document_private_items(mut self, private: bool) -> Self {
+        self.private = private;
+        self
+    }
+}
 
-            // Recurse into any workspace files to handle those
-            if let Some(workspace) = table.get("workspace") {
-                // Get the list
-                let members: &[Value] = match workspace.get("members") {
-                    Some(Value::Array(members)) => members,
-                    Some(members)               => { return Err(Error::CargoTomlMembersTypeError{ path: cargo_path, data_type: members.type_str() }); },
-                    None                        => { return Err(Error::CargoTomlMissingMembers{ path: cargo_path }); },
-                };
 
-                // Unwrap the list to strings
-                let mut smembers: Vec<&String> = Vec::with_capacity(members.len());
-                for m in members {
-                    smembers.push(if let Value::String(member) = m {
-                        member
-                    } else {
-                        return Err(Error::CargoTomlMemberTypeError{ path: cargo_path, data_type: m.type_str() });
-                    });
-                }
 
-                // We can now recurse each of the members to find their package names
-                for m in smembers {
-                    res.append(&mut Self::deduce_effects(name, path.join(m), mode, cache.clone())?);
-                }
-            }
+/// Defines a target that runs `cargo doc --no-deps` for a package (or workspace).
+/// 
+/// Its effect is the generated documentation directory, such that later targets (e.g., one that archives or copies it) can depend on it.
+pub struct CargoDocTarget<'a> {
+    /// The name of this target.
+    name    : String,
+    /// The dependencies of this target.
+    deps    : Vec<EffectView<'a>>,
+    /// The effects (that we care about) of this target.
+    effects : Vec<Box<dyn Effect>>,
+    /// The inputs of this target, i.e., the fingerprint of its configuration.
+    inputs  : Vec<Box<dyn Effect>>,
 
-            // If the names are still empty, we failed
-            if res.is_empty() {
-                return Err(Error::CargoTomlEffectsDeduceError { path: cargo_path });
-            };
+    /// The path of the directory where the target package (or workspace) lives.
+    path     : PathBuf,
+    /// The packages that we document in this run.
+    packages : Vec<String>,
+    /// The features that we enable in this run.
+    features : Vec<String>,
+    /// Whether to document private items as well.
+    private  : bool,
+}
 
-            // Return that
-            debug!("Effects deduced from '{}': {:?}", cargo_path.display(), res.iter().map(|e| e.name()).collect::<Vec<&str>>());
-            Ok(res)
-        } else {
-            Err(Error::CargoTomlNotATable{ path: cargo_path })
-        }
+impl<'a> CargoDocTarget<'a> {
+    /// Returns a builder for the CargoDocTarget that can be used to fully define it.
+    /// 
+    /// Note that you have to call at least `CargoDocTargetBuilder::path()` before calling `CargoDocTargetBuilder::build()`.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target to build.
+    /// 
+    /// # Returns
+    /// A new CargoDocTargetBuilder instance.
+    #[inline]
+    pub fn builder(name: impl Into<String>) -> CargoDocTargetBuilder<'a> {
+        CargoDocTargetBuilder::new(name)
     }
 
 
 
-    /// Returns the path to the directory where this target builds.
+    /// Returns the command that this target runs when built.
+    pub fn command(&self) -> ShellCommand {
+        let mut cmd: ShellCommand = cargo_command("doc", &self.path, &CargoMode::Debug, &self.packages, &self.features, None, None);
+        cmd.add_arg("--no-deps");
+        if self.private { cmd.add_arg("--document-private-items"); }
+        cmd
+    }
+}
+
+impl<'a> Named for CargoDocTarget<'a> {
     #[inline]
-    pub fn path(&self) -> &Path { &self.path }
+    fn name(&self) -> &str { &self.name }
+}
+impl<'a> Target for CargoDocTarget<'a> {
+    #[inline]
+    fn build(&self, _os: OperatingSystem, _arch: Architecture, dry_run: bool) -> Result<(), TargetError> {
+        run_cargo_command(&self.name, &self.command(), None, dry_run)
+    }
 
-    /// Returns the list of packages we're building.
     #[inline]
-    pub fn packages(&self) -> &[String] { &self.packages }
+    fn kind(&self) -> &str { "cargo-doc" }
+    #[inline]
+    fn resource_class(&self) -> &str { CARGO_RESOURCE_CLASS }
+
+
 
-    /// Returns the mode in which we're building.
     #[inline]
-    pub fn mode(&self) -> CargoMode { self.mode }
-}
+    fn deps(&self) -> &[EffectView<'_>] { &self.deps }
 
-impl<'a> Named for CargoTarget<'a> {
     #[inline]
-    fn name(&self) -> &str { &self.name }
+    fn effects(&self) -> &[Box<dyn Effect>] { &self.effects }
+    #[inline]
+    fn inputs(&self) -> &[Box<dyn Effect>] { &self.inputs }
 }
-impl<'a> Target for CargoTarget<'a> {
-    fn build(&self, os: OperatingSystem, arch: Architecture, dry_run: bool) -> Result<(), TargetError> {
-        // Cast architectures to a suitable string
-        let arch: &str = match arch {
-            Architecture::x86_32       => "i686",
-            Architecture::x86_64       => "x86_64",
-            Architecture::Aarch32      => "arm",
-            Architecture::Aarch64      => "aarch64",
-            Architecture::PowerPc32    => "powerpc",
-            Architecture::PowerPc64    => "powerpc64",
-            Architecture::Mips         => "mips",
-            Architecture::Custom(arch) => { panic!("Custom architectures ('{}') are not supported by CargoTarget", arch); },
-        };
 
-        // Use that to prepare the cargo target string
-        let target: String = match os {
-            OperatingSystem::Windows      => { format!("{}-pc-windows-msvc", arch) },
-            OperatingSystem::MacOs        => { format!("{}-apple-darwin", arch) },
-            OperatingSystem::Linux        => { format!("{}-unknown-linux-gnu", arch) },
-            OperatingSystem::Custom(arch) => { panic!("Custom operating systems ('{}') are not supported by CargoTarget", arch); },
-        };
 
-        // Now prepare the command to run
-        
 
-        Ok(())
+/// Defines a target that runs `cargo clean` for a package (or workspace), such that build artifacts can be reset through the framework.
+/// 
+/// Like the `CargoTestTarget`, its only input and effect is the `TrueEffect`, such that it is run every time it is made.
+pub struct CargoCleanTarget<'a> {
+    /// The name of this target.
+    name    : String,
+    /// The dependencies of this target.
+    deps    : Vec<EffectView<'a>>,
+    /// The effects of this target (always the `TrueEffect`).
+    effects : Vec<Box<dyn Effect>>,
+
+    /// The path of the directory where the target package (or workspace) lives.
+    path     : PathBuf,
+    /// The packages that we clean in this run.
+    packages : Vec<String>,
+    /// The mode (i.e., release or debug) of which we clean the artifacts, if only one.
+    mode     : Option<CargoMode>,
+
+    /// Whether to require the `Cargo.lock` to be up-to-date.
+    locked  : bool,
+    /// Whether to forbid cargo from accessing the network.
+    offline : bool,
+    /// Whether to require the `Cargo.lock` to be up-to-date and forbid cargo from accessing the network.
+    frozen  : bool,
+
+    /// The target triple of which we clean the artifacts when cleaning for the host, if not the host's.
+    triple     : Option<String>,
+    /// The C library to link against when compiling for Linux.
+    libc       : Libc,
+    /// The directory where cargo writes its artifacts, if not the default one.
+    target_dir : Option<PathBuf>,
+    /// The rustup toolchain to run cargo with, if not the default one.
+    toolchain  : Option<String>,
+    /// The cargo executable to run.
+    cargo_bin  : String,
+    /// Whether to run `cross` instead of the cargo executable.
+    use_cross  : bool,
+    /// The environment variables to set for cargo, on top of the inherited ones.
+    envs       : HashMap<String, String>,
+}
+
+impl<'a> CargoCleanTarget<'a> {
+    /// Constructor for the CargoCleanTarget.
+    /// 
+    /// By default, all artifacts of the package (or workspace) in the given directory are removed (akin to running just `cargo clean`).
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `deps`: An iterator with EffectViews that represent the dependencies of this target.
+    /// - `path`: The path to the package or workspace directory to clean.
+    /// 
+    /// # Returns
+    /// A new CargoCleanTarget instance.
+    #[inline]
+    pub fn new(name: impl Into<String>, deps: impl IntoIterator<Item = EffectView<'a>, IntoIter = impl Iterator<Item = EffectView<'a>>>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            name    : name.into(),
+            deps    : deps.into_iter().collect(),
+            effects : vec![ Box::new(TrueEffect) ],
+
+            path     : path.into(),
+            packages : vec![],
+            mode     : None,
+
+            locked  : false,
+            offline : false,
+            frozen  : false,
+
+            triple     : None,
+            libc       : Libc::default(),
+            target_dir : None,
+            toolchain  : None,
+            cargo_bin  : "cargo".into(),
+            use_cross  : false,
+            envs       : HashMap::new(),
+        }
+    }
+
+    /// Constructor for the CargoCleanTarget that removes what the given CargoTarget builds.
+    /// 
+    /// It removes the artifacts of the same packages in the same mode, and runs cargo like the CargoTarget does (i.e., with the same lockfile and network restrictions, default triple, C library, target directory, toolchain, cargo executable and environment variables).
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `deps`: An iterator with EffectViews that represent the dependencies of this target.
+    /// - `target`: The CargoTarget to clean the artifacts of.
+    /// 
+    /// # Returns
+    /// A new CargoCleanTarget instance.
+    pub fn from_target(name: impl Into<String>, deps: impl IntoIterator<Item = EffectView<'a>, IntoIter = impl Iterator<Item = EffectView<'a>>>, target: &CargoTarget) -> Self {
+        Self {
+            name    : name.into(),
+            deps    : deps.into_iter().collect(),
+            effects : vec![ Box::new(TrueEffect) ],
+
+            path     : target.path.clone(),
+            packages : target.packages.clone(),
+            mode     : Some(target.mode.clone()),
+
+            locked  : target.locked,
+            offline : target.offline,
+            frozen  : target.frozen,
+
+            triple     : target.triple.clone(),
+            libc       : target.libc,
+            target_dir : target.target_dir.clone(),
+            toolchain  : target.toolchain.clone(),
+            cargo_bin  : target.cargo_bin.clone(),
+            use_cross  : target.use_cross,
+            envs       : target.envs.clone(),
+        }
+    }
+
+
+
+    /// Adds a package to the list of packages of which this target will remove the artifacts.
+    /// 
+    /// If you specify no packages at all, then the whole target directory is removed.
+    /// 
+    /// # Arguments
+    /// - `package`: The name/identifier of the package to clean.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn package(mut self, package: impl Into<String>) -> Self {
+        self.packages.push(package.into());
+        self
+    }
+
+    /// Limits cleaning to the artifacts of the given mode.
+    /// 
+    /// By default, the artifacts of all modes are removed.
+    /// 
+    /// # Arguments
+    /// - `mode`: The mode of which to remove the artifacts.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn mode(mut self, mode: CargoMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+
+
+    /// Returns the command that this target runs when built for the given OS and architecture.
+    /// 
+    /// Like for the `CargoTarget`, the target triple is only passed to cargo if they are not the host's (or if it has a default triple), such that only that triple's artifacts are removed.
+    pub fn command(&self, os: OperatingSystem, arch: Architecture) -> ShellCommand {
+        let triple: Option<String> = cargo_triple(os, arch, self.libc, self.triple.as_deref());
+        let mut cmd: ShellCommand = cargo_command("clean", &self.path, &CargoMode::Debug, &self.packages, &[], triple.as_deref(), self.toolchain.as_deref());
+        add_lock_flags(&mut cmd, self.locked, self.offline, self.frozen);
+        set_cargo_exec(&mut cmd, self.target_dir.as_deref(), &self.cargo_bin, self.use_cross, &self.envs);
+        if let Some(mode) = &self.mode {
+            cmd.add_args([ "--profile", match mode {
+                CargoMode::Release       => "release",
+                CargoMode::Debug         => "dev",
+                CargoMode::Profile(name) => name,
+            } ]);
+        }
+        cmd
+    }
+}
+
+impl<'a> Named for CargoCleanTarget<'a> {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+impl<'a> Target for CargoCleanTarget<'a> {
+    #[inline]
+    fn build(&self, os: OperatingSystem, arch: Architecture, dry_run: bool) -> Result<(), TargetError> {
+        run_cargo_command(&self.name, &self.command(os, arch), None, dry_run)
     }
 
+    #[inline]
+    fn kind(&self) -> &str { "cargo-clean" }
+
 
 
     #[inline]
-    fn deps(&self) -> &[EffectView] { &self.deps }
+    fn deps(&self) -> &[EffectView<'_>] { &self.deps }
 
     #[inline]
     fn effects(&self) -> &[Box<dyn Effect>] { &self.effects }
+    #[inline]
+    fn inputs(&self) -> &[Box<dyn Effect>] { &self.effects }
 }
@@ -14,7 +14,15 @@
 // 
 
 // Declare our targets
+pub mod alias;
 pub mod cargo;
+pub mod null;
+pub mod phony;
+pub mod retry;
 
 // Pull stuff into this namespace
-pub use cargo::{CargoTarget, CargoTargetBuilder};
+pub use alias::AliasTarget;
+pub use cargo::{CargoCleanTarget, CargoClippyTarget, CargoDocTarget, CargoDocTargetBuilder, CargoFmtTarget, CargoTarget, CargoTargetBuilder, CargoTestTarget};
+pub use null::NullTarget;
+pub use phony::PhonyTarget;
+pub use retry::RetryTarget;
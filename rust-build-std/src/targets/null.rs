@@ -0,0 +1,73 @@
+//  NULL.rs
+//    by Lut99
+// 
+//  Created:
+//    15 Oct 2026, 10:02:37
+//  Last edited:
+//    15 Oct 2026, 10:02:37
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Provides a target that does nothing at all, which is useful as a
+//!   placeholder in dependency graphs (or in tests).
+// 
+
+use rust_build::errors::TargetError;
+use rust_build::spec::{Architecture, Effect, Named, OperatingSystem, Target};
+use rust_build::view::EffectView;
+
+use crate::trace;
+
+
+/***** LIBRARY *****/
+/// Defines the Null target, which does nothing when built and produces no effects.
+/// 
+/// It may still have dependencies, which are built as usual. This makes it useful as a trivial buildable node, e.g., as a root that aggregates other targets.
+pub struct NullTarget<'a> {
+    /// The name of this target.
+    name : String,
+    /// The dependencies of this target.
+    deps : Vec<EffectView<'a>>,
+}
+
+impl<'a> NullTarget<'a> {
+    /// Constructor for the NullTarget.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `deps`: An iterator with EffectViews that represent the dependencies of this target.
+    /// 
+    /// # Returns
+    /// A new NullTarget instance.
+    #[inline]
+    pub fn new(name: impl Into<String>, deps: impl IntoIterator<Item = EffectView<'a>, IntoIter = impl Iterator<Item = EffectView<'a>>>) -> Self {
+        Self {
+            name : name.into(),
+            deps : deps.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a> Named for NullTarget<'a> {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+impl<'a> Target for NullTarget<'a> {
+    #[inline]
+    fn build(&self, _os: OperatingSystem, _arch: Architecture, _dry_run: bool) -> Result<(), TargetError> {
+        trace!("{}: Nothing to build", self.name());
+        Ok(())
+    }
+
+    #[inline]
+    fn kind(&self) -> &str { "null" }
+
+
+
+    #[inline]
+    fn deps(&self) -> &[EffectView<'_>] { &self.deps }
+
+    #[inline]
+    fn effects(&self) -> &[Box<dyn Effect>] { &[] }
+}
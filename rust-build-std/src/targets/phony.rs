@@ -0,0 +1,160 @@
+//  PHONY.rs
+//    by Lut99
+// 
+//  Created:
+//    15 Oct 2026, 10:38:51
+//  Last edited:
+//    15 Oct 2026, 10:38:51
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Provides a target that is always run, regardless of whether
+//!   anything changed.
+// 
+
+use std::error::Error as StdError;
+use std::time::Duration;
+
+use rust_build::errors::TargetError;
+use rust_build::shell::ShellCommand;
+use rust_build::spec::{Architecture, Effect, Named, OperatingSystem, Target};
+use rust_build::view::EffectView;
+
+use crate::effects::trivial::TrueEffect;
+
+
+/***** AUXILLARY *****/
+/// Defines the closure that may be run by a PhonyTarget. It is given whether we are doing a dry run or not.
+pub type PhonyClosure = dyn Send + Sync + Fn(bool) -> Result<(), Box<dyn StdError + Send + Sync>>;
+
+/// Defines what a PhonyTarget does when it is built.
+pub enum PhonyAction {
+    /// Runs the given closure, which is given whether we are doing a dry run or not.
+    Closure(Box<PhonyClosure>),
+    /// Runs the given shell command.
+    Command(ShellCommand),
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the Phony target, which runs some action every time it is made, regardless of the cache.
+/// 
+/// Its only input and effect is the `TrueEffect`, such that it (and any target depending on it) is always considered outdated. As such, it completely ignores the cache. This is useful for steps that should never be cached, such as printing a version or running a smoke test.
+pub struct PhonyTarget<'a> {
+    /// The name of this target.
+    name    : String,
+    /// The dependencies of this target.
+    deps    : Vec<EffectView<'a>>,
+    /// The effects of this target (always the `TrueEffect`).
+    effects : Vec<Box<dyn Effect>>,
+
+    /// The action to run when built.
+    action  : PhonyAction,
+    /// The maximum time the action may take (only applies to commands).
+    timeout : Option<Duration>,
+}
+
+impl<'a> PhonyTarget<'a> {
+    /// Constructor for the PhonyTarget that runs the given action.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `deps`: An iterator with EffectViews that represent the dependencies of this target.
+    /// - `action`: The PhonyAction to run whenever this target is made.
+    /// 
+    /// # Returns
+    /// A new PhonyTarget instance.
+    #[inline]
+    pub fn new(name: impl Into<String>, deps: impl IntoIterator<Item = EffectView<'a>, IntoIter = impl Iterator<Item = EffectView<'a>>>, action: PhonyAction) -> Self {
+        Self {
+            name    : name.into(),
+            deps    : deps.into_iter().collect(),
+            effects : vec![ Box::new(TrueEffect) ],
+
+            action,
+            timeout : None,
+        }
+    }
+
+    /// Constructor for the PhonyTarget that runs the given closure.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `deps`: An iterator with EffectViews that represent the dependencies of this target.
+    /// - `closure`: The closure to run whenever this target is made. It is given whether we are doing a dry run or not.
+    /// 
+    /// # Returns
+    /// A new PhonyTarget instance.
+    #[inline]
+    pub fn with_closure(name: impl Into<String>, deps: impl IntoIterator<Item = EffectView<'a>, IntoIter = impl Iterator<Item = EffectView<'a>>>, closure: impl 'static + Send + Sync + Fn(bool) -> Result<(), Box<dyn StdError + Send + Sync>>) -> Self {
+        Self::new(name, deps, PhonyAction::Closure(Box::new(closure)))
+    }
+
+    /// Constructor for the PhonyTarget that runs the given shell command.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `deps`: An iterator with EffectViews that represent the dependencies of this target.
+    /// - `command`: The ShellCommand to run whenever this target is made.
+    /// 
+    /// # Returns
+    /// A new PhonyTarget instance.
+    #[inline]
+    pub fn with_command(name: impl Into<String>, deps: impl IntoIterator<Item = EffectView<'a>, IntoIter = impl Iterator<Item = EffectView<'a>>>, command: ShellCommand) -> Self {
+        Self::new(name, deps, PhonyAction::Command(command))
+    }
+
+
+
+    /// Sets the maximum time that this target may take to build.
+    /// 
+    /// Only applies if the target runs a `PhonyAction::Command`; the command is killed once it overruns, failing the build. By default, there is no limit.
+    /// 
+    /// # Arguments
+    /// - `timeout`: The maximum time the command may take.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+impl<'a> Named for PhonyTarget<'a> {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+impl<'a> Target for PhonyTarget<'a> {
+    fn build(&self, _os: OperatingSystem, _arch: Architecture, dry_run: bool) -> Result<(), TargetError> {
+        match &self.action {
+            PhonyAction::Closure(closure) => match closure(dry_run) {
+                Ok(_)    => Ok(()),
+                Err(err) => Err(TargetError::BuildError{ name: self.name.clone(), err }),
+            },
+
+            PhonyAction::Command(command) => match command.run_dry_timeout_checked(dry_run, self.timeout) {
+                Ok(_)    => Ok(()),
+                Err(err) => Err(TargetError::BuildError{ name: self.name.clone(), err: Box::new(err) }),
+            },
+        }
+    }
+
+    #[inline]
+    fn kind(&self) -> &str { "phony" }
+
+
+
+    #[inline]
+    fn deps(&self) -> &[EffectView<'_>] { &self.deps }
+
+    #[inline]
+    fn effects(&self) -> &[Box<dyn Effect>] { &self.effects }
+    #[inline]
+    fn inputs(&self) -> &[Box<dyn Effect>] { &self.effects }
+}
@@ -0,0 +1,206 @@
+//  RETRY.rs
+//    by Lut99
+// 
+//  Created:
+//    15 Oct 2026, 15:02:44
+//  Last edited:
+//    15 Oct 2026, 15:02:44
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Provides a target that wraps another target to retry it (with
+//!   exponential backoff) if it fails.
+// 
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rust_build::errors::TargetError;
+use rust_build::observer::BuildObserver;
+use rust_build::shell::{deadline, Error as ShellCommandError};
+use rust_build::spec::{Architecture, Effect, Named, OperatingSystem, Target};
+use rust_build::view::EffectView;
+
+use crate::debug;
+
+
+/***** AUXILLARY *****/
+/// Defines the predicate that determines which errors are worth retrying.
+pub type RetryPredicate = dyn Send + Sync + Fn(&TargetError) -> bool;
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns whether the given error is the result of running out of time, either because the build's deadline passed or because a command ran into its timeout.
+/// 
+/// # Arguments
+/// - `err`: The TargetError to classify.
+/// 
+/// # Returns
+/// True if the error is a timeout, or false otherwise.
+fn is_timeout(err: &TargetError) -> bool {
+    match err {
+        TargetError::Timeout{ .. }         => true,
+        TargetError::BuildError{ err, .. } => matches!(err.downcast_ref::<ShellCommandError>(), Some(ShellCommandError::Timeout{ .. })),
+        _                                  => false,
+    }
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the Retry target, which wraps another target and re-makes it if it fails (e.g., for flaky downloads or cargo fetches).
+/// 
+/// In between attempts, it waits for a backoff time that grows exponentially. Since a failed target does not commit its effects, every retry re-checks the dependencies (which are typically up-to-date by then) and rebuilds the wrapped target.
+/// 
+/// Apart from that, it behaves exactly like the wrapped target (i.e., it has the same name, dependencies and effects).
+pub struct RetryTarget<T> {
+    /// The target that we wrap.
+    target    : T,
+    /// The number of times the target is retried after the first attempt.
+    retries   : usize,
+    /// The time to wait before the first retry.
+    backoff   : Duration,
+    /// The factor with which the backoff grows after every retry.
+    factor    : f64,
+    /// Determines which errors are retried.
+    predicate : Box<RetryPredicate>,
+}
+
+impl<T: Target> RetryTarget<T> {
+    /// Constructor for the RetryTarget.
+    /// 
+    /// By default, the target is retried after 1 second, which doubles for every next retry, and any error is retried except for timeouts (i.e., `TargetError::Timeout` or a command that was killed because it took too long).
+    /// 
+    /// # Arguments
+    /// - `target`: The Target to wrap.
+    /// - `retries`: The number of times to retry the target after it failed the first time.
+    /// 
+    /// # Returns
+    /// A new RetryTarget instance.
+    #[inline]
+    pub fn new(target: T, retries: usize) -> Self {
+        Self {
+            target,
+            retries,
+            backoff   : Duration::from_secs(1),
+            factor    : 2.0,
+            predicate : Box::new(|err| !is_timeout(err)),
+        }
+    }
+
+
+
+    /// Sets the backoff between attempts.
+    /// 
+    /// # Arguments
+    /// - `backoff`: The time to wait before the first retry.
+    /// - `factor`: The factor with which the time to wait is multiplied after every retry (e.g., `2.0` to double it).
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn backoff(mut self, backoff: Duration, factor: f64) -> Self {
+        self.backoff = backoff;
+        self.factor  = factor;
+        self
+    }
+
+    /// Sets the predicate that determines which errors are retried. Errors for which it returns 'false' are returned immediately.
+    /// 
+    /// Regardless of the predicate, the target is never retried once the deadline of the current build has passed (see `Installer::build_with_deadline()`).
+    /// 
+    /// # Arguments
+    /// - `predicate`: The closure that classifies a TargetError as retryable ('true') or not ('false').
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn retry_if(mut self, predicate: impl 'static + Send + Sync + Fn(&TargetError) -> bool) -> Self {
+        self.predicate = Box::new(predicate);
+        self
+    }
+
+
+
+    /// Returns the target wrapped by this RetryTarget.
+    #[inline]
+    pub fn inner(&self) -> &T { &self.target }
+
+
+
+    /// Makes the wrapped target with the given closure, retrying it as configured if it fails.
+    /// 
+    /// # Arguments
+    /// - `make`: The closure that makes the wrapped target (e.g., with `Target::make_observed()`).
+    /// 
+    /// # Errors
+    /// This function errors if the last attempt failed, or if the error was not retryable.
+    fn retry(&self, make: impl Fn() -> Result<(), TargetError>) -> Result<(), TargetError> {
+        let mut backoff: Duration = self.backoff;
+        let mut attempt: usize = 0;
+        loop {
+            match make() {
+                Ok(_)                                                          => { return Ok(()); },
+                Err(err) if attempt >= self.retries || !(self.predicate)(&err) => { return Err(err); },
+                Err(err)                                                       => {
+                    // Never retry once the deadline of the build passed, nor wait beyond it
+                    if deadline().map(|d| Instant::now() >= d).unwrap_or(false) { return Err(err); }
+                    let wait: Duration = match deadline() {
+                        Some(deadline) => backoff.min(deadline.saturating_duration_since(Instant::now())),
+                        None           => backoff,
+                    };
+
+                    attempt += 1;
+                    debug!("{}: Failed to make target ({}); retrying in {:.1}s ({}/{})", self.name(), err, wait.as_secs_f64(), attempt, self.retries);
+                    thread::sleep(wait);
+                    backoff = backoff.mul_f64(self.factor);
+                },
+            }
+        }
+    }
+}
+
+impl<T: Target> Named for RetryTarget<T> {
+    #[inline]
+    fn name(&self) -> &str { self.target.name() }
+}
+impl<T: Target> Target for RetryTarget<T> {
+    #[inline]
+    fn make_observed(&self, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool, observer: &dyn BuildObserver) -> Result<(), TargetError> {
+        self.retry(|| self.target.make_observed(os, arch, force, dry_run, observer))
+    }
+    #[inline]
+    fn make_ready_observed(&self, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool, observer: &dyn BuildObserver) -> Result<(), TargetError> {
+        self.retry(|| self.target.make_ready_observed(os, arch, force, dry_run, observer))
+    }
+
+    #[inline]
+    fn build_deps_observed(&self, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool, observer: &dyn BuildObserver) -> Result<bool, TargetError> { self.target.build_deps_observed(os, arch, force, dry_run, observer) }
+    #[inline]
+    fn commit(&self, dry_run: bool) -> Result<(), TargetError> { self.target.commit(dry_run) }
+
+    #[inline]
+    fn build(&self, os: OperatingSystem, arch: Architecture, dry_run: bool) -> Result<(), TargetError> { self.target.build(os, arch, dry_run) }
+    #[inline]
+    fn kind(&self) -> &str { self.target.kind() }
+    #[inline]
+    fn warnings(&self) -> Option<usize> { self.target.warnings() }
+    #[inline]
+    fn resource_class(&self) -> &str { self.target.resource_class() }
+
+
+
+    #[inline]
+    fn deps(&self) -> &[EffectView<'_>] { self.target.deps() }
+
+    #[inline]
+    fn effects(&self) -> &[Box<dyn Effect>] { self.target.effects() }
+    #[inline]
+    fn inputs(&self) -> &[Box<dyn Effect>] { self.target.inputs() }
+}
@@ -0,0 +1,1431 @@
+//  TESTS.rs
+//    by Lut99
+// 
+//  Created:
+//    15 Oct 2026, 08:12:40
+//  Last edited:
+//    15 Oct 2026, 08:12:40
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   File that contains tests for the standard effects and targets.
+// 
+
+#[cfg(feature = "download")]
+use std::collections::HashMap;
+#[cfg(feature = "download")]
+use std::io::{BufRead, BufReader, Write};
+#[cfg(feature = "download")]
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use rust_build::cache::{Cache, LastEditedTime};
+use rust_build::errors::{BuildError, TargetError};
+use rust_build::installer::Installer;
+use rust_build::report::BuildReport;
+use rust_build::shell::{Error as ShellCommandError, ShellCommand};
+use rust_build::spec::{Architecture, Effect, Named, OperatingSystem, Target, TargetBuilder};
+use rust_build::view::EffectView;
+
+use crate::effects::{Directory, File};
+#[cfg(feature = "download")]
+use crate::effects::RemoteFile;
+#[cfg(feature = "download")]
+use crate::effects::download::Error as DownloadError;
+use crate::effects::trivial::FalseEffect;
+use crate::targets::alias::AliasTarget;
+use crate::targets::cargo::{DEFAULT_FEATURES_ENV, CargoCleanTarget, CargoClippyTarget, CargoDocTarget, CargoFmtTarget, CargoMetadata, CargoMode, CargoTarget, CargoTargetBuilder, CargoTestTarget, Error as CargoError, Libc, target_triple};
+use crate::targets::null::NullTarget;
+use crate::targets::phony::PhonyTarget;
+use crate::targets::retry::RetryTarget;
+
+
+/***** CONSTANTS *****/
+/// Lock that serializes tests that read or write (cargo-related) environment variables.
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns a fresh, empty directory in the system's temporary folder for a test to play around in.
+/// 
+/// # Arguments
+/// - `name`: A name that is unique for the test using the directory.
+/// 
+/// # Returns
+/// The path to the (newly created) directory.
+/// 
+/// # Panics
+/// This function panics if we failed to (re)create the directory.
+fn test_dir(name: impl AsRef<str>) -> PathBuf {
+    let path: PathBuf = std::env::temp_dir().join(format!("rust-build-std-test-{}", name.as_ref()));
+    if path.exists() { std::fs::remove_dir_all(&path).unwrap_or_else(|err| panic!("Failed to remove old test directory '{}': {}", path.display(), err)); }
+    std::fs::create_dir_all(&path).unwrap_or_else(|err| panic!("Failed to create test directory '{}': {}", path.display(), err));
+    path
+}
+
+/// Acquires the lock on the environment variables, ignoring any poisoning by failed tests.
+/// 
+/// # Returns
+/// A guard that releases the lock when dropped.
+fn env_lock() -> MutexGuard<'static, ()> {
+    ENV_LOCK.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Returns the paths of the given effects, assuming they are all `File`s.
+/// 
+/// # Arguments
+/// - `effects`: The effects to get the paths of.
+/// 
+/// # Returns
+/// The paths of the effects, in order.
+/// 
+/// # Panics
+/// This function panics if any of the effects is not a `File`.
+fn effect_paths(effects: &[Box<dyn Effect>]) -> Vec<PathBuf> {
+    effects.iter().map(|e| e.as_any().downcast_ref::<File>().expect("Effect is not a File").path.clone()).collect()
+}
+
+/// Serves the given contents over HTTP on some local port, with an ETag of `"v1"`.
+/// 
+/// The server answers conditional requests with a matching ETag with a `304` and range requests with a `206` (unless their `If-Range` does not match).
+/// 
+/// # Arguments
+/// - `contents`: The contents of the served file.
+/// 
+/// # Returns
+/// The URL of the served file, and a list to which the `Range`-header of every request is pushed (or an empty string if it had none).
+#[cfg(feature = "download")]
+fn serve_http(contents: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener : TcpListener              = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url      : String                   = format!("http://{}/file.txt", listener.local_addr().unwrap());
+    let ranges   : Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+
+    let tranges: Arc<Mutex<Vec<String>>> = ranges.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream: TcpStream = stream.unwrap();
+
+            // Read the request line & headers
+            let mut reader : BufReader<&TcpStream>    = BufReader::new(&stream);
+            let mut line   : String                   = String::new();
+            reader.read_line(&mut line).unwrap();
+            let method: String = line.split_whitespace().next().unwrap_or("").into();
+            let mut headers: HashMap<String, String> = HashMap::new();
+            loop {
+                line.clear();
+                if reader.read_line(&mut line).unwrap() == 0 || line.trim_end().is_empty() { break; }
+                if let Some((name, value)) = line.trim_end().split_once(':') { headers.insert(name.trim().to_lowercase(), value.trim().into()); }
+            }
+            let range: Option<usize> = headers.get("range").and_then(|r| r.strip_prefix("bytes=")).and_then(|r| r.trim_end_matches('-').parse().ok());
+            tranges.lock().unwrap().push(headers.get("range").cloned().unwrap_or_default());
+
+            // Answer
+            let response: String = if headers.get("if-none-match").map(|e| e == "\"v1\"").unwrap_or(false) {
+                "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nContent-Length: 0\r\n\r\n".into()
+            } else if let (Some(start), true) = (range, headers.get("if-range").map(|e| e == "\"v1\"").unwrap_or(true)) {
+                format!("HTTP/1.1 206 Partial Content\r\nETag: \"v1\"\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\n\r\n{}", start, contents.len() - 1, contents.len(), contents.len() - start, &contents[start..])
+            } else if method == "HEAD" {
+                format!("HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\n\r\n", contents.len())
+            } else {
+                format!("HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\n\r\n{}", contents.len(), contents)
+            };
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+    (url, ranges)
+}
+
+
+
+
+
+/***** HELPER TARGETS *****/
+/// Defines a Target that does nothing except providing the effects it is given.
+struct EffectsTarget {
+    /// The name of the target.
+    name    : String,
+    /// The effects of this target.
+    effects : Vec<Box<dyn Effect>>,
+}
+
+impl Named for EffectsTarget {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+impl Target for EffectsTarget {
+    #[inline]
+    fn build(&self, _os: OperatingSystem, _arch: Architecture, _dry_run: bool) -> Result<(), TargetError> { Ok(()) }
+
+    #[inline]
+    fn deps(&self) -> &[EffectView<'_>] { &[] }
+    #[inline]
+    fn effects(&self) -> &[Box<dyn Effect>] { &self.effects }
+}
+
+
+
+/// Defines a Target that logs its name to a shared list whenever it is built.
+struct LoggingTarget {
+    /// The name of the target.
+    name : String,
+    /// The log to write to.
+    log  : Arc<Mutex<Vec<String>>>,
+}
+
+impl Named for LoggingTarget {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+impl Target for LoggingTarget {
+    #[inline]
+    fn build(&self, _os: OperatingSystem, _arch: Architecture, _dry_run: bool) -> Result<(), TargetError> {
+        self.log.lock().unwrap().push(self.name.clone());
+        Ok(())
+    }
+
+    #[inline]
+    fn deps(&self) -> &[EffectView<'_>] { &[] }
+    #[inline]
+    fn effects(&self) -> &[Box<dyn Effect>] { &[] }
+}
+
+
+
+
+
+/***** TESTS *****/
+#[test]
+fn test_effect_downcast() {
+    let dir   : PathBuf   = test_dir("effect_downcast");
+    let cache : Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // Define a target with a single File effect
+    let target: EffectsTarget = EffectsTarget {
+        name    : "test".into(),
+        effects : vec![ Box::new(File::new("file", cache, dir.join("file.txt"))) ],
+    };
+
+    // Obtain it through a view, then downcast it back to a file
+    let view: EffectView = target.view();
+    let effect: &Box<dyn Effect> = view.iter().next().unwrap();
+    let file: &File = effect.as_any().downcast_ref::<File>().expect("Failed to downcast effect to a File");
+    assert_eq!(file.path, dir.join("file.txt"));
+}
+
+#[test]
+fn test_file_layered_caches() {
+    let dir  : PathBuf = test_dir("file_layered_caches");
+    let path : PathBuf = dir.join("file.txt");
+    std::fs::write(&path, "Hello there!").unwrap();
+
+    // Populate a base cache with the file's current state, then re-open it as read-only
+    File::new("file", Arc::new(Cache::new(dir.join("base"), true).unwrap()), &path).commit_change(false).unwrap();
+    let base    : Arc<Cache> = Arc::new(Cache::read_only(dir.join("base")).unwrap());
+    let overlay : Arc<Cache> = Arc::new(Cache::new(dir.join("overlay"), true).unwrap());
+
+    // The overlay has no entry, so the base should be consulted and report no change
+    let file: File = File::with_caches("file", vec![ overlay.clone(), base.clone() ], &path);
+    assert!(!file.has_changed().unwrap());
+
+    // Committing should end up in the overlay, and never in the base
+    file.commit_change(false).unwrap();
+    assert!(overlay.get_file(&path).unwrap().is_some());
+    assert!(base.update_file(&path, base.get_file(&path).unwrap().unwrap(), false).is_err());
+
+    // A file with only read-only caches cannot be committed
+    assert!(File::with_caches("file", vec![ base ], &path).commit_change(false).is_err());
+}
+
+#[test]
+fn test_cargo_target_dir_env() {
+    let dir: PathBuf = test_dir("cargo_target_dir_env");
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"foo\"\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // Without the variable, we should default to `./target`
+    let _lock = env_lock();
+    std::env::remove_var("CARGO_TARGET_DIR");
+    std::env::remove_var("CARGO_BUILD_TARGET_DIR");
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("foo", &dir, CargoTarget::resolve_target_dir(&dir, false), &CargoMode::Release, None, &[], cache.clone()).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ PathBuf::from("./target/release/foo") ]);
+
+    // With the variable, the effect should follow it
+    std::env::set_var("CARGO_TARGET_DIR", dir.join("out"));
+    let effects: Result<Vec<Box<dyn Effect>>, _> = CargoTarget::deduce_effects("foo", &dir, CargoTarget::resolve_target_dir(&dir, false), &CargoMode::Release, None, &[], cache);
+    std::env::remove_var("CARGO_TARGET_DIR");
+    assert_eq!(effect_paths(&effects.unwrap()), vec![ dir.join("out").join("release").join("foo") ]);
+}
+
+#[test]
+fn test_cargo_missing_toml() {
+    let dir: PathBuf = test_dir("cargo_missing_toml");
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // A directory without a Cargo.toml should report it as missing
+    let res: Result<Vec<Box<dyn Effect>>, CargoError> = CargoTarget::deduce_effects("foo", &dir, dir.join("target"), &CargoMode::Release, None, &[], cache);
+    assert!(matches!(res, Err(CargoError::MissingCargoToml{ path }) if path == dir.join("Cargo.toml")));
+}
+
+#[test]
+fn test_cargo_target_dir_custom() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_target_dir_custom");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n").unwrap();
+    std::fs::write(dir.join("src").join("main.rs"), "fn main() {}").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // The directory should be passed to cargo, and the effects should live in it (relative to the package)
+    let cargo: CargoTarget = CargoTarget::builder("foo").path(&dir).target_dir("out").build(cache.clone()).unwrap();
+    assert_eq!(cargo.target_dir(), Some(PathBuf::from("out").as_path()));
+    assert_eq!(&cargo.command(OperatingSystem::host(), Architecture::host()).args()[1..], &[ "--release", "--target-dir", "out" ]);
+    assert_eq!(effect_paths(cargo.effects()), vec![ dir.join("out").join("release").join("foo") ]);
+
+    // Absolute ones are used as-is, also when consulting cargo metadata
+    let shared: PathBuf = dir.join("shared");
+    let cargo: CargoTarget = CargoTarget::builder("foo").path(&dir).metadata(true).target_dir(&shared).build(cache).unwrap();
+    assert_eq!(effect_paths(cargo.effects()), vec![ shared.join("release").join("foo") ]);
+}
+
+#[test]
+fn test_cargo_target_dir_metadata() {
+    let dir: PathBuf = test_dir("cargo_target_dir_metadata");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::create_dir_all(dir.join(".cargo")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n\n[workspace]\n").unwrap();
+    std::fs::write(dir.join("src").join("main.rs"), "fn main() {}\n").unwrap();
+    std::fs::write(dir.join(".cargo").join("config.toml"), "[build]\ntarget-dir = \"custom\"\n").unwrap();
+
+    // Only cargo metadata knows about the config file
+    let _lock = env_lock();
+    std::env::remove_var("CARGO_TARGET_DIR");
+    std::env::remove_var("CARGO_BUILD_TARGET_DIR");
+    assert_eq!(CargoTarget::resolve_target_dir(&dir, false), PathBuf::from("./target"));
+    assert!(CargoTarget::resolve_target_dir(&dir, true).ends_with("custom"));
+
+    // If metadata is unavailable, we fall back to the heuristics
+    assert_eq!(CargoTarget::resolve_target_dir(test_dir("cargo_target_dir_metadata_none"), true), PathBuf::from("./target"));
+}
+
+#[test]
+fn test_cargo_mode() {
+    assert_eq!(CargoMode::Release.to_args(), vec![ "--release" ]);
+    assert!(CargoMode::Debug.to_args().is_empty());
+    assert_eq!(CargoMode::Release.to_build_dir(), "release");
+    assert_eq!(CargoMode::Debug.to_build_dir(), "debug");
+    assert_eq!(CargoMode::Profile("dist".into()).to_args(), vec![ "--profile", "dist" ]);
+    assert_eq!(CargoMode::Profile("dist".into()).to_build_dir(), "dist");
+    assert_eq!(CargoMode::Profile("dev".into()).to_build_dir(), "debug");
+
+    // The arguments should end up as-is in the command
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_mode");
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"foo\"\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let cargo: CargoTarget = CargoTarget::builder("foo").path(&dir).mode(CargoMode::Release).effect(FalseEffect).build(cache.clone()).unwrap();
+    assert_eq!(cargo.command(OperatingSystem::host(), Architecture::host()).args(), &[ "build", "--release" ]);
+    let cargo: CargoTarget = CargoTarget::builder("foo").path(&dir).mode(CargoMode::Profile("dist".into())).effect(FalseEffect).build(cache.clone()).unwrap();
+    assert_eq!(cargo.command(OperatingSystem::host(), Architecture::host()).args(), &[ "build", "--profile", "dist" ]);
+
+    // Custom profiles are built in their own directory
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("foo", &dir, dir.join("target"), &CargoMode::Profile("dist".into()), None, &[], cache).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ dir.join("target").join("dist").join("foo") ]);
+}
+
+#[test]
+fn test_cargo_fingerprint() {
+    let dir   : PathBuf   = test_dir("cargo_fingerprint");
+    let cache : Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let make = |mode: CargoMode| CargoTarget::builder("foo").path(&dir).mode(mode).effect(FalseEffect).build(cache.clone()).unwrap();
+
+    // Without anything in the cache, the target is outdated
+    let target: CargoTarget = make(CargoMode::Debug);
+    assert!(target.build_deps(OperatingSystem::host(), Architecture::host(), false, false).unwrap());
+    target.commit(false).unwrap();
+
+    // After committing, it isn't, until the configuration changes
+    assert!(!make(CargoMode::Debug).build_deps(OperatingSystem::host(), Architecture::host(), false, false).unwrap());
+    assert!(make(CargoMode::Release).build_deps(OperatingSystem::host(), Architecture::host(), false, false).unwrap());
+}
+
+#[test]
+fn test_null_target() {
+    // Register a null target depending on another one
+    let leaf: &'static NullTarget = Box::leak(Box::new(NullTarget::new("leaf", [])));
+    let installer: Installer = Installer::builder()
+        .add_target(leaf)
+        .add_target(NullTarget::new("root", [ leaf.view() ]))
+        .build();
+
+    // Building it should work, but unknown targets do not
+    assert_eq!(leaf.kind(), "null");
+    installer.build("root", OperatingSystem::host(), Architecture::host(), true, false).unwrap();
+    assert!(matches!(installer.build("foo", OperatingSystem::host(), Architecture::host(), true, false), Err(BuildError::UnknownTarget{ .. })));
+}
+
+#[test]
+fn test_alias_target() {
+    let log: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+    let a: LoggingTarget = LoggingTarget{ name: "a".into(), log: log.clone() };
+    let b: LoggingTarget = LoggingTarget{ name: "b".into(), log: log.clone() };
+    let c: LoggingTarget = LoggingTarget{ name: "c".into(), log: log.clone() };
+
+    // Building the alias should build all members once, in order
+    let all: AliasTarget = AliasTarget::new("all", [ &a as &dyn Target, &b, &c ]);
+    all.make(OperatingSystem::host(), Architecture::host(), true, false).unwrap();
+    assert_eq!(all.kind(), "alias");
+    assert!(all.effects().is_empty());
+    assert_eq!(*log.lock().unwrap(), vec![ "a".to_string(), "b".to_string(), "c".to_string() ]);
+}
+
+#[test]
+fn test_phony_target() {
+    let runs: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    let phony: PhonyTarget = {
+        let runs: Arc<AtomicUsize> = runs.clone();
+        PhonyTarget::with_closure("phony", [], move |_| { runs.fetch_add(1, Ordering::SeqCst); Ok(()) })
+    };
+
+    // It should run every time, even when not forced
+    phony.make(OperatingSystem::host(), Architecture::host(), false, false).unwrap();
+    phony.make(OperatingSystem::host(), Architecture::host(), false, false).unwrap();
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_cargo_examples() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_examples");
+    std::fs::create_dir_all(dir.join("examples").join("multi")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"foo\"\n\n[[example]]\nname = \"custom\"\npath = \"other/custom.rs\"\n").unwrap();
+    std::fs::write(dir.join("examples").join("simple.rs"), "fn main() {}\n").unwrap();
+    std::fs::write(dir.join("examples").join("multi").join("main.rs"), "fn main() {}\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    std::env::remove_var("CARGO_TARGET_DIR");
+    std::env::remove_var("CARGO_BUILD_TARGET_DIR");
+
+    // A named example should be deduced next to the binary
+    let target: CargoTarget = CargoTarget::builder("foo").path(&dir).mode(CargoMode::Debug).example("simple").build(cache.clone()).unwrap();
+    assert_eq!(effect_paths(target.effects()), vec![ PathBuf::from("./target/debug/foo"), PathBuf::from("./target/debug/examples/simple") ]);
+
+    // All examples should be found when asked for
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_example_effects("foo", &dir, "out", &CargoMode::Release, None, &[], true, cache.clone()).unwrap();
+    assert_eq!(effect_paths(&effects), [ "custom", "multi", "simple" ].into_iter().map(|e| PathBuf::from("out/release/examples").join(e)).collect::<Vec<PathBuf>>());
+
+    // Unknown examples are an error
+    assert!(CargoTarget::builder("foo").path(&dir).example("nope").build(cache).is_err());
+}
+
+#[test]
+fn test_file_missing() {
+    let dir   : PathBuf   = test_dir("file_missing");
+    let cache : Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // A file that has yet to be produced is simply changed, unless it should have existed
+    assert!(File::new("file", cache.clone(), dir.join("file.txt")).has_changed().unwrap());
+    assert!(File::new("file", cache, dir.join("file.txt")).expect_exists(true).has_changed().is_err());
+}
+
+#[test]
+fn test_file_corrupt_cache() {
+    let dir  : PathBuf = test_dir("file_corrupt_cache");
+    let path : PathBuf = dir.join("file.txt");
+    std::fs::write(&path, "Hello there!").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // Commit the file, then corrupt its entry
+    let file: File = File::new("file", cache.clone(), &path);
+    file.commit_change(false).unwrap();
+    assert!(!file.has_changed().unwrap());
+    std::fs::write(dir.join("cache").join(format!("{}", Cache::hash(&path))), "garbage").unwrap();
+
+    // It should now be rebuild instead of erroring
+    assert!(cache.get_file(&path).is_err());
+    assert!(file.has_changed().unwrap());
+}
+
+#[test]
+fn test_file_last_changed() {
+    let dir   : PathBuf   = test_dir("file_last_changed");
+    let path  : PathBuf   = dir.join("file.txt");
+    let cache : Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // A file that does not exist has no time yet
+    let file: File = File::new("file", cache, &path);
+    assert!(file.last_changed().unwrap().is_none());
+
+    // Once it does, it should be its modification time
+    std::fs::write(&path, "Hello there!").unwrap();
+    let last_changed: LastEditedTime = file.last_changed().unwrap().unwrap();
+    let mtime: Duration = std::fs::metadata(&path).unwrap().modified().unwrap().duration_since(UNIX_EPOCH).unwrap();
+    assert_eq!((last_changed.unix_seconds(), last_changed.nanoseconds()), (mtime.as_secs() as i64, mtime.subsec_nanos()));
+}
+
+#[test]
+fn test_file_hashing() {
+    let dir   : PathBuf   = test_dir("file_hashing");
+    let path  : PathBuf   = dir.join("file.txt");
+    let cache : Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    std::fs::write(&path, "Hello there!").unwrap();
+    let touch = |path: &PathBuf, secs: u64| std::fs::File::options().write(true).open(path).unwrap().set_modified(UNIX_EPOCH + Duration::from_secs(secs)).unwrap();
+
+    // Commit both a hashing and non-hashing version of the file
+    let plain  : File = File::new("file", cache.clone(), &path);
+    let hashed : File = File::new("file", cache.clone(), &path).with_hashing(true);
+    hashed.commit_change(false).unwrap();
+    assert!(cache.get_file_hash(&path).unwrap().is_some());
+
+    // Touching the file should only mark the non-hashing version as changed
+    touch(&path, 1000000000);
+    assert!(plain.has_changed().unwrap());
+    assert!(!hashed.has_changed().unwrap());
+
+    // But actually changing the contents should mark both as changed
+    std::fs::write(&path, "General Kenobi!").unwrap();
+    touch(&path, 1000000001);
+    assert!(plain.has_changed().unwrap());
+    assert!(hashed.has_changed().unwrap());
+
+    // Without a hash in the cache, the last edited time is used
+    plain.commit_change(false).unwrap();
+    touch(&path, 1000000002);
+    assert!(hashed.has_changed().unwrap());
+}
+
+#[test]
+fn test_file_command_hash() {
+    let dir   : PathBuf   = test_dir("file_command_hash");
+    let path  : PathBuf   = dir.join("binary");
+    let cache : Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    std::fs::write(&path, "Hello there!").unwrap();
+
+    // Commit the file as produced by some command
+    let debug   : File = File::new("binary", cache.clone(), &path).with_command_hash(Some(ShellCommand::with_args("cargo", [ "build" ]).command_hash()));
+    let release : File = File::new("binary", cache.clone(), &path).with_command_hash(Some(ShellCommand::with_args("cargo", [ "build", "--release" ]).command_hash()));
+    let plain   : File = File::new("binary", cache.clone(), &path);
+    debug.commit_change(false).unwrap();
+    assert!(!debug.has_changed().unwrap());
+    assert!(!plain.has_changed().unwrap());
+
+    // The same file is outdated for another command, until that one commits it
+    assert!(release.has_changed().unwrap());
+    release.commit_change(false).unwrap();
+    assert!(!release.has_changed().unwrap());
+    assert!(debug.has_changed().unwrap());
+}
+
+#[test]
+fn test_file_remap() {
+    let dir   : PathBuf   = test_dir("file_remap");
+    let src   : PathBuf   = dir.join("app");
+    let dst   : PathBuf   = dir.join("bin").join("app");
+    std::fs::create_dir_all(dir.join("bin")).unwrap();
+    std::fs::write(&src, "Hello there!").unwrap();
+    let cache : Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // The remapped effect should point at the destination, not the source
+    let file: File = File::new("app", cache, &src);
+    file.commit_change(false).unwrap();
+    let copied: File = file.remap(&dst);
+    assert_eq!(copied.name(), "app");
+    assert_eq!(copied.path, dst);
+    assert_eq!(file.path, src);
+
+    // It is tracked separately from the source
+    assert!(copied.has_changed().unwrap());
+    std::fs::copy(&src, &dst).unwrap();
+    assert!(copied.has_changed().unwrap());
+    copied.commit_change(false).unwrap();
+    assert!(!copied.has_changed().unwrap());
+    assert!(!file.has_changed().unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_phony_timeout() {
+    let start: Instant = Instant::now();
+
+    // A command that overruns its timeout should fail the build
+    let phony: PhonyTarget = PhonyTarget::with_command("phony", [], ShellCommand::with_args("sleep", [ "5" ])).timeout(Duration::from_millis(100));
+    assert!(matches!(phony.make(OperatingSystem::host(), Architecture::host(), false, false), Err(TargetError::BuildError{ .. })));
+    assert!(start.elapsed() < Duration::from_secs(4));
+
+    // But one that is in time should not
+    let phony: PhonyTarget = PhonyTarget::with_command("phony", [], ShellCommand::with_args("sleep", [ "0" ])).timeout(Duration::from_secs(5));
+    phony.make(OperatingSystem::host(), Architecture::host(), false, false).unwrap();
+}
+
+#[cfg(feature = "download")]
+#[test]
+fn test_remote_file() {
+    let dir           : PathBuf                  = test_dir("remote_file");
+    let path          : PathBuf                  = dir.join("file.txt");
+    let cache         : Arc<Cache>                = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let (url, ranges) : (String, Arc<Mutex<Vec<String>>>) = serve_http("Hello there, this is a remote file!");
+
+    // Before downloading, the file is changed
+    let file: RemoteFile = RemoteFile::new("file", cache.clone(), &url, &path);
+    assert!(file.has_changed().unwrap());
+    file.download(false).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "Hello there, this is a remote file!");
+    file.commit_change(false).unwrap();
+
+    // Afterwards, the server reports it is not modified
+    assert!(!file.has_changed().unwrap());
+    assert!(!RemoteFile::new("file", cache, &url, &path).has_changed().unwrap());
+
+    // Unless the local copy is tampered with, which we should notice without asking the server
+    std::fs::write(&path, "Hello there!").unwrap();
+    assert!(file.has_changed().unwrap());
+    assert_eq!(ranges.lock().unwrap().len(), 3);
+}
+
+#[cfg(feature = "download")]
+#[test]
+fn test_remote_file_resume() {
+    let dir           : PathBuf                  = test_dir("remote_file_resume");
+    let path          : PathBuf                  = dir.join("file.txt");
+    let cache         : Arc<Cache>                = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let (url, ranges) : (String, Arc<Mutex<Vec<String>>>) = serve_http("Hello there, this is a remote file!");
+
+    // Write a partial download of the current version, then resume it
+    let file: RemoteFile = RemoteFile::new("file", cache, &url, &path);
+    std::fs::write(file.part_path(), "Hello there").unwrap();
+    std::fs::write(file.part_etag_path(), "\"v1\"").unwrap();
+    file.download(false).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "Hello there, this is a remote file!");
+    assert!(!file.part_path().exists());
+    assert!(!file.part_etag_path().exists());
+    assert_eq!(*ranges.lock().unwrap(), vec![ "bytes=11-".to_string() ]);
+
+    // A partial download of another version should be downloaded again completely
+    std::fs::write(file.part_path(), "Goodbye").unwrap();
+    std::fs::write(file.part_etag_path(), "\"v0\"").unwrap();
+    file.download(false).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "Hello there, this is a remote file!");
+
+    // As should one of which we don't know the version at all
+    std::fs::write(file.part_path(), "Goodbye").unwrap();
+    file.download(false).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "Hello there, this is a remote file!");
+    assert_eq!(*ranges.lock().unwrap(), vec![ "bytes=11-".to_string(), "bytes=7-".to_string(), String::new() ]);
+}
+
+#[cfg(feature = "download")]
+#[test]
+fn test_remote_file_timeout() {
+    let dir   : PathBuf    = test_dir("remote_file_timeout");
+    let cache : Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // A server that never answers should time out instead of blocking the build
+    let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let file: RemoteFile = RemoteFile::new("file", cache, format!("http://{}/file.txt", listener.local_addr().unwrap()), dir.join("file.txt")).with_timeout(Duration::from_millis(100));
+    let start: Instant = Instant::now();
+    assert!(matches!(file.download(false), Err(DownloadError::Timeout{ .. })));
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_cargo_build() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_build");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::create_dir_all(dir.join("examples")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"built\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").unwrap();
+    std::fs::write(dir.join("src").join("main.rs"), "fn main() {}\n").unwrap();
+    std::fs::write(dir.join("examples").join("demo.rs"), "fn main() {}\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let cargo: CargoTarget = CargoTarget::builder("cargo").path(&dir).mode(CargoMode::Debug).metadata(true).example("demo").build(cache).unwrap();
+    assert_eq!(&cargo.examples_command(OperatingSystem::host(), Architecture::host()).unwrap().args()[1..], &[ "--example", "demo" ]);
+
+    // A dry run should not produce anything
+    let binary  : PathBuf = dir.join("target").join("debug").join(format!("built{}", std::env::consts::EXE_SUFFIX));
+    let example : PathBuf = dir.join("target").join("debug").join("examples").join(format!("demo{}", std::env::consts::EXE_SUFFIX));
+    cargo.build(OperatingSystem::host(), Architecture::host(), true).unwrap();
+    assert!(!binary.exists());
+
+    // But an actual one should produce both the binary and the example, after which they are up-to-date
+    cargo.make(OperatingSystem::host(), Architecture::host(), false, false).unwrap();
+    assert!(binary.is_file());
+    assert!(example.is_file());
+    assert!(!cargo.effects().iter().any(|e| e.has_changed().unwrap()));
+
+    // Build failures should be reported as such
+    std::fs::write(dir.join("src").join("main.rs"), "fn main() { oops }\n").unwrap();
+    assert!(matches!(cargo.build(OperatingSystem::host(), Architecture::host(), false), Err(TargetError::BuildError{ .. })));
+}
+
+#[test]
+fn test_cargo_deny_warnings() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_deny_warnings");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"warns\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").unwrap();
+    std::fs::write(dir.join("src").join("main.rs"), "fn main() {\n    let unused: i32 = 42;\n}\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // Without denying warnings, nothing changes
+    let cargo: CargoTarget = CargoTarget::builder("cargo").path(&dir).mode(CargoMode::Debug).build(cache.clone()).unwrap();
+    cargo.build(OperatingSystem::host(), Architecture::host(), false).unwrap();
+    assert_eq!(cargo.warnings(), None);
+
+    // With, the unused variable should fail the build
+    let cargo: CargoTarget = CargoTarget::builder("cargo").path(&dir).mode(CargoMode::Debug).deny_warnings(true).build(cache.clone()).unwrap();
+    assert!(matches!(cargo.build(OperatingSystem::host(), Architecture::host(), false), Err(TargetError::BuildError{ .. })));
+    assert_eq!(cargo.warnings(), Some(1));
+
+    // The count should end up in the build report
+    let report: PathBuf = dir.join("report.json");
+    let cargo: &'static CargoTarget = Box::leak(Box::new(CargoTarget::builder("cargo").path(&dir).mode(CargoMode::Debug).deny_warnings(true).build(cache.clone()).unwrap()));
+    let installer: Installer = Installer::builder().add_target(cargo).report(&report).build();
+    assert!(installer.build("cargo", OperatingSystem::host(), Architecture::host(), true, false).is_err());
+    let report: BuildReport = serde_json::from_reader(std::fs::File::open(&report).unwrap()).unwrap();
+    assert_eq!(report.targets[0].warnings, Some(1));
+
+    // Counting should still respect the timeout
+    let cargo: CargoTarget = CargoTarget::builder("cargo").path(&dir).mode(CargoMode::Debug).deny_warnings(true).timeout(Duration::ZERO).build(cache).unwrap();
+    assert!(matches!(cargo.build(OperatingSystem::host(), Architecture::host(), false), Err(TargetError::BuildError{ err, .. }) if matches!(err.downcast_ref::<ShellCommandError>(), Some(ShellCommandError::Timeout{ .. }))));
+}
+
+#[test]
+fn test_cargo_test_target() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_test_target");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"tested\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[features]\nfail = []\n").unwrap();
+    std::fs::write(dir.join("src").join("lib.rs"), "#[test]\nfn passes() {}\n\n#[cfg(feature = \"fail\")]\n#[test]\nfn fails() { panic!(\"Failing on purpose\"); }\n").unwrap();
+
+    // Passing tests should pass the build
+    let test: CargoTestTarget = CargoTestTarget::new("test", [], &dir);
+    test.make(OperatingSystem::host(), Architecture::host(), false, false).unwrap();
+
+    // But failing tests should fail it
+    let test: CargoTestTarget = CargoTestTarget::new("test", [], &dir).feature("fail");
+    assert_eq!(&test.command().args()[1..], &[ "--features", "fail" ]);
+    assert!(matches!(test.make(OperatingSystem::host(), Architecture::host(), false, false), Err(TargetError::BuildError{ .. })));
+
+    // Unless we're doing a dry run
+    test.make(OperatingSystem::host(), Architecture::host(), false, true).unwrap();
+}
+
+#[test]
+fn test_cargo_clippy_target() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_clippy_target");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"linted\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").unwrap();
+
+    // Clean code should pass the build
+    std::fs::write(dir.join("src").join("lib.rs"), "pub fn has_none(v: &[u8]) -> bool { v.is_empty() }\n").unwrap();
+    let clippy: CargoClippyTarget = CargoClippyTarget::new("clippy", [], &dir);
+    assert_eq!(&clippy.command(OperatingSystem::host(), Architecture::host()).args()[1..], &[ "--", "-D", "warnings" ]);
+    clippy.make(OperatingSystem::host(), Architecture::host(), false, false).unwrap();
+
+    // But a lint violation should fail it
+    std::fs::write(dir.join("src").join("lib.rs"), "pub fn has_none(v: &[u8]) -> bool { v.len() == 0 }\n").unwrap();
+    assert!(matches!(clippy.make(OperatingSystem::host(), Architecture::host(), false, false), Err(TargetError::BuildError{ .. })));
+}
+
+#[test]
+fn test_cargo_tool_targets_from_target() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_tool_targets_from_target");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n").unwrap();
+    std::fs::write(dir.join("src").join("main.rs"), "fn main() {}").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let cargo: CargoTarget = CargoTarget::builder("foo").path(&dir).triple("x86_64-unknown-linux-musl").libc(Libc::Musl).toolchain("nightly").cargo_bin("my-cargo").env("FOO", "bar").all_features(true).no_default_features(true).locked(true).offline(true).frozen(true).build(cache).unwrap();
+
+    // Linting and cleaning should run cargo the same way as building does
+    let commands: [ ShellCommand; 2 ] = [
+        CargoClippyTarget::from_target("clippy", [], &cargo).command(OperatingSystem::host(), Architecture::host()),
+        CargoCleanTarget::from_target("clean", [], &cargo).command(OperatingSystem::host(), Architecture::host()),
+    ];
+    for command in &commands {
+        assert_eq!(command.exec(), "my-cargo");
+        assert_eq!(command.args()[0], "+nightly");
+        assert!(command.args().windows(2).any(|w| w == [ "--target", "x86_64-unknown-linux-musl" ]));
+        assert_eq!(command.envs().get("FOO").map(String::as_str), Some("bar"));
+        for flag in [ "--locked", "--offline", "--frozen" ] { assert!(command.args().iter().any(|arg| arg == flag)); }
+    }
+
+    // Linting should also select the same features, but cleaning has no notion of features
+    for flag in [ "--all-features", "--no-default-features" ] {
+        assert!(commands[0].args().iter().any(|arg| arg == flag));
+        assert!(!commands[1].args().iter().any(|arg| arg == flag));
+    }
+
+    // Including the C library when cross-compiling
+    let clippy: CargoClippyTarget = CargoClippyTarget::from_target("clippy", [], &cargo);
+    assert!(clippy.command(OperatingSystem::Linux, Architecture::Aarch32).args().windows(2).any(|w| w == [ "--target", "armv7-unknown-linux-musleabihf" ]));
+}
+
+#[test]
+fn test_cargo_fmt_target() {
+    let dir: PathBuf = test_dir("cargo_fmt_target");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"formatted\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").unwrap();
+
+    // Well-formatted code should pass the build
+    std::fs::write(dir.join("src").join("lib.rs"), "pub fn answer() -> u32 {\n    42\n}\n").unwrap();
+    let fmt: CargoFmtTarget = CargoFmtTarget::new("fmt", [], &dir);
+    fmt.make(OperatingSystem::host(), Architecture::host(), false, false).unwrap();
+
+    // But misformatted code should fail it (except when doing a dry run)
+    std::fs::write(dir.join("src").join("lib.rs"), "pub fn answer()->u32{42}\n").unwrap();
+    assert!(matches!(fmt.make(OperatingSystem::host(), Architecture::host(), false, false), Err(TargetError::BuildError{ .. })));
+    fmt.make(OperatingSystem::host(), Architecture::host(), false, true).unwrap();
+}
+
+#[test]
+fn test_cargo_doc_target() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_doc_target");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"documented\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").unwrap();
+    std::fs::write(dir.join("src").join("lib.rs"), "//! Some documented crate.\n\n/// The answer.\npub fn answer() -> u32 { 42 }\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // The effect should be the documentation directory in the package's target directory
+    let doc: CargoDocTarget = CargoDocTarget::builder("doc").path(&dir).document_private_items(true).build(cache).unwrap();
+    assert_eq!(&doc.command().args()[1..], &[ "--no-deps", "--document-private-items" ]);
+    let docs: &Directory = doc.effects()[0].as_any().downcast_ref::<Directory>().unwrap();
+    assert_eq!(docs.path, dir.join("target").join("doc"));
+
+    // Building should produce it, after which it is up-to-date
+    assert!(docs.has_changed().unwrap());
+    doc.make(OperatingSystem::host(), Architecture::host(), false, false).unwrap();
+    assert!(docs.path.join("documented").join("index.html").exists());
+    assert!(!docs.has_changed().unwrap());
+}
+
+#[test]
+fn test_cargo_clean_target() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_clean_target");
+    for p in [ "kept", "cleaned" ] {
+        std::fs::create_dir_all(dir.join(p).join("src")).unwrap();
+        std::fs::write(dir.join(p).join("Cargo.toml"), format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n", p)).unwrap();
+        std::fs::write(dir.join(p).join("src").join("main.rs"), "fn main() {}\n").unwrap();
+    }
+    std::fs::write(dir.join("Cargo.toml"), "[workspace]\nmembers = [ \"kept\", \"cleaned\" ]\nresolver = \"2\"\n").unwrap();
+    assert!(std::process::Command::new("cargo").args([ "build", "--quiet" ]).current_dir(&dir).status().unwrap().success());
+    assert!(dir.join("target").join("debug").join("kept").exists());
+    assert!(dir.join("target").join("debug").join("cleaned").exists());
+
+    // A dry run should not remove anything
+    let clean: CargoCleanTarget = CargoCleanTarget::new("clean", [], &dir).package("cleaned");
+    clean.make(OperatingSystem::host(), Architecture::host(), false, true).unwrap();
+    assert!(dir.join("target").join("debug").join("cleaned").exists());
+
+    // But a real one should only remove the scoped package
+    clean.make(OperatingSystem::host(), Architecture::host(), false, false).unwrap();
+    assert!(dir.join("target").join("debug").join("kept").exists());
+    assert!(!dir.join("target").join("debug").join("cleaned").exists());
+}
+
+#[test]
+fn test_cargo_lockfile() {
+    let dir: PathBuf = test_dir("cargo_lockfile");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"locked\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").unwrap();
+    std::fs::write(dir.join("Cargo.lock"), "version = 3\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // The lockfile should be tracked as an input, unless disabled
+    let cargo: CargoTarget = CargoTarget::builder("cargo").path(&dir).effect(FalseEffect).build(cache.clone()).unwrap();
+    assert_eq!(cargo.inputs().iter().map(|e| e.name()).collect::<Vec<&str>>(), vec![ "cargo_fingerprint", "cargo_lockfile" ]);
+    let untracked: CargoTarget = CargoTarget::builder("cargo").path(&dir).effect(FalseEffect).track_lockfile(false).build(cache.clone()).unwrap();
+    assert_eq!(untracked.inputs().len(), 1);
+
+    // Once committed, the target is up-to-date...
+    cargo.commit(false).unwrap();
+    assert!(!cargo.build_deps(OperatingSystem::host(), Architecture::host(), false, false).unwrap());
+
+    // ...until the lockfile is edited
+    std::fs::write(dir.join("Cargo.lock"), "version = 3\n\n[[package]]\nname = \"locked\"\nversion = \"0.1.0\"\n").unwrap();
+    std::fs::File::options().write(true).open(dir.join("Cargo.lock")).unwrap().set_modified(SystemTime::now() + Duration::from_secs(1)).unwrap();
+    assert!(cargo.build_deps(OperatingSystem::host(), Architecture::host(), false, false).unwrap());
+}
+
+#[test]
+fn test_rename_effect() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("rename_effect");
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"hello-world\"\nversion = \"0.1.0\"\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // Rename the deduced effect to something friendlier
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).rename_effect("hello-world_hello-world", "binary").build(cache.clone()).unwrap();
+    assert_eq!(cargo.effects().iter().map(|e| e.name()).collect::<Vec<&str>>(), vec![ "binary" ]);
+
+    // Depending on the new name should find it, and it should still be the same file underneath
+    let view: EffectView = cargo.view_names(vec![ "binary".to_string() ]);
+    assert_eq!(view.iter().count(), 1);
+    assert_eq!(effect_paths(cargo.effects()), vec![ PathBuf::from("./target").join("release").join("hello-world") ]);
+
+    // Files can also be renamed directly
+    assert_eq!(File::new("file", cache, dir.join("file.txt")).with_name("renamed").name(), "renamed");
+}
+
+#[test]
+fn test_retry_target() {
+    /// Creates a PhonyTarget that fails the first two times it is built.
+    fn flaky(runs: Arc<AtomicUsize>) -> PhonyTarget<'static> {
+        PhonyTarget::with_closure("flaky", [], move |_| {
+            runs.fetch_add(1, Ordering::SeqCst);
+            if runs.load(Ordering::SeqCst) <= 2 { Err("flaky failure".into()) } else { Ok(()) }
+        })
+    }
+
+    // Enough retries should make it succeed eventually
+    let runs: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    let retry: RetryTarget<PhonyTarget> = RetryTarget::new(flaky(runs.clone()), 3).backoff(Duration::from_millis(10), 2.0);
+    assert_eq!(retry.name(), "flaky");
+    retry.make(OperatingSystem::host(), Architecture::host(), false, false).unwrap();
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+
+    // Too few retries should still fail
+    let runs: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    let retry: RetryTarget<PhonyTarget> = RetryTarget::new(flaky(runs.clone()), 1).backoff(Duration::from_millis(10), 2.0);
+    assert!(retry.make(OperatingSystem::host(), Architecture::host(), false, false).is_err());
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+    // Errors that are not retryable should fail immediately
+    let runs: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+    let retry: RetryTarget<PhonyTarget> = RetryTarget::new(flaky(runs.clone()), 3).backoff(Duration::from_millis(10), 2.0).retry_if(|_| false);
+    assert!(retry.make(OperatingSystem::host(), Architecture::host(), false, false).is_err());
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_retry_target_timeout() {
+    let dir: PathBuf = test_dir("retry_target_timeout");
+
+    // Commands that ran into their timeout should not be retried by default
+    let slow: PhonyTarget = PhonyTarget::with_command("slow", [], ShellCommand::with_args("sh", [ "-c", &format!("echo run >> '{}'; sleep 5", dir.join("runs").display()) ])).timeout(Duration::from_millis(100));
+    let retry: RetryTarget<PhonyTarget> = RetryTarget::new(slow, 3).backoff(Duration::from_millis(10), 2.0);
+    assert!(matches!(retry.make(OperatingSystem::host(), Architecture::host(), true, false), Err(TargetError::BuildError{ .. })));
+    assert_eq!(std::fs::read_to_string(dir.join("runs")).unwrap().lines().count(), 1);
+
+    // Nor should we keep waiting for a retry once the deadline of the build passed
+    let failing: PhonyTarget = PhonyTarget::with_closure("failing", [], |_| Err("failure".into()));
+    let installer: Installer = Installer::builder().add_target(RetryTarget::new(failing, 3).backoff(Duration::from_secs(10), 2.0)).build();
+    let start: Instant = Instant::now();
+    assert!(installer.build_with_deadline(start + Duration::from_millis(200), "failing", OperatingSystem::host(), Architecture::host(), true, false).is_err());
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_directory_ignore() {
+    let dir: PathBuf = test_dir("directory_ignore");
+    std::fs::create_dir_all(dir.join("tree").join("src")).unwrap();
+    std::fs::create_dir_all(dir.join("tree").join("target")).unwrap();
+    std::fs::write(dir.join("tree").join("src").join("main.rs"), "fn main() {}").unwrap();
+    std::fs::write(dir.join("tree").join("target").join("main"), "binary").unwrap();
+    std::fs::write(dir.join("tree").join("main.rs.swp"), "swap").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let tree: Directory = Directory::new("tree", cache.clone(), dir.join("tree"));
+    tree.commit_change(false).unwrap();
+    assert!(!tree.has_changed().unwrap());
+
+    // A change under `target/` should not matter
+    let later: SystemTime = SystemTime::now() + Duration::from_secs(10);
+    std::fs::File::options().write(true).open(dir.join("tree").join("target").join("main")).unwrap().set_modified(later).unwrap();
+    assert!(!tree.has_changed().unwrap());
+
+    // ...unless it's not ignored, and custom patterns should apply too
+    assert!(Directory::new("tree", cache.clone(), dir.join("tree")).ignore(Vec::<String>::new()).has_changed().unwrap());
+    let tree: Directory = tree.ignore([ "target/**", "**/*.swp" ]);
+    tree.commit_change(false).unwrap();
+    std::fs::File::options().write(true).open(dir.join("tree").join("main.rs.swp")).unwrap().set_modified(later + Duration::from_secs(10)).unwrap();
+    assert!(!tree.has_changed().unwrap());
+
+    // A change in the sources should
+    std::fs::File::options().write(true).open(dir.join("tree").join("src").join("main.rs")).unwrap().set_modified(later).unwrap();
+    assert!(tree.has_changed().unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_directory_removals() {
+    let dir  : PathBuf = test_dir("directory_removals");
+    let tree : PathBuf = dir.join("tree");
+    std::fs::create_dir_all(tree.join("src")).unwrap();
+    std::fs::write(tree.join("src").join("a.rs"), "").unwrap();
+    std::fs::write(tree.join("src").join("b.rs"), "").unwrap();
+    std::os::unix::fs::symlink(&tree, tree.join("src").join("loop")).unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // Pin all times such that only the number of files may change
+    let pin = || for path in [ tree.join("src").join("a.rs"), tree.join("src"), tree.clone() ] {
+        std::fs::File::open(&path).unwrap().set_modified(UNIX_EPOCH + Duration::from_secs(1000000000)).unwrap();
+    };
+    pin();
+    let directory: Directory = Directory::new("tree", cache.clone(), &tree);
+    directory.commit_change(false).unwrap();
+    assert!(!directory.has_changed().unwrap());
+
+    // Removing a file should be detected, even if the times are the same
+    std::fs::remove_file(tree.join("src").join("b.rs")).unwrap();
+    pin();
+    assert!(directory.has_changed().unwrap());
+    assert_eq!(directory.last_changed().unwrap(), LastEditedTime::from_path_recursive(&tree).ok());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cargo_auto_install_target() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let dir: PathBuf = test_dir("cargo_auto_install_target");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"hello-world\"\nversion = \"0.1.0\"\n").unwrap();
+    std::fs::write(dir.join("src").join("main.rs"), "fn main() {}\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // Mock rustup such that it reports a single installed target and logs what it's asked to do
+    let rustup: PathBuf = dir.join("rustup");
+    let log: PathBuf = dir.join("rustup.log");
+    std::fs::write(&rustup, format!("#!/bin/sh\necho \"$@\" >> '{}'\nif [ \"$2\" = \"list\" ]; then echo armv7-unknown-linux-gnueabihf; fi\n", log.display())).unwrap();
+    std::fs::set_permissions(&rustup, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let make = |triple: Option<&str>| {
+        let builder: CargoTargetBuilder = CargoTarget::builder("hello-world").path(&dir).auto_install_target(true).rustup(rustup.to_string_lossy());
+        match triple {
+            Some(triple) => builder.triple(triple),
+            None         => builder,
+        }.build(cache.clone()).unwrap()
+    };
+
+    // Only missing targets should be installed, and nothing should be checked for host builds (cross-compiling itself may fail, since the targets are not actually installed)
+    make(None).build(OperatingSystem::host(), Architecture::host(), false).unwrap();
+    assert!(!log.exists());
+    let _ = make(Some("armv7-unknown-linux-gnueabihf")).build(OperatingSystem::Linux, Architecture::Aarch32, false);
+    let _ = make(Some("x86_64-pc-windows-msvc")).build(OperatingSystem::Windows, Architecture::x86_64, false);
+    assert_eq!(std::fs::read_to_string(&log).unwrap(), "target list --installed\ntarget list --installed\ntarget add x86_64-pc-windows-msvc\n");
+}
+
+#[test]
+fn test_cargo_feature_flags() {
+    let dir: PathBuf = test_dir("cargo_feature_flags");
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"hello-world\"\nversion = \"0.1.0\"\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // The feature flags should all land in the command
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).features([ "a", "b" ]).all_features(true).no_default_features(true).build(cache.clone()).unwrap();
+    assert!(cargo.all_features());
+    assert!(cargo.no_default_features());
+    assert_eq!(&cargo.command(OperatingSystem::host(), Architecture::host()).args()[1..], &[ "--release", "--features", "a,b", "--all-features", "--no-default-features" ]);
+
+    // ...and be absent if not asked for
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).feature("a").build(cache).unwrap();
+    assert_eq!(&cargo.command(OperatingSystem::host(), Architecture::host()).args()[1..], &[ "--release", "--features", "a" ]);
+}
+
+#[test]
+fn test_cargo_lock_flags() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_lock_flags");
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"hello-world\"\nversion = \"0.1.0\"\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // All flags should be passed as given, even if frozen implies the others
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).locked(true).offline(true).frozen(true).build(cache.clone()).unwrap();
+    assert!(cargo.locked() && cargo.offline() && cargo.frozen());
+    assert_eq!(&cargo.command(OperatingSystem::host(), Architecture::host()).args()[1..], &[ "--release", "--locked", "--offline", "--frozen" ]);
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).frozen(true).build(cache).unwrap();
+    assert_eq!(&cargo.command(OperatingSystem::host(), Architecture::host()).args()[1..], &[ "--release", "--frozen" ]);
+}
+
+#[test]
+fn test_cargo_toolchain() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_toolchain");
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"hello-world\"\nversion = \"0.1.0\"\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // The toolchain should come before the subcommand, and be absent if not given
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).toolchain("nightly").build(cache.clone()).unwrap();
+    assert_eq!(cargo.toolchain(), Some("nightly"));
+    assert_eq!(cargo.command(OperatingSystem::host(), Architecture::host()).args(), &[ "+nightly", "build", "--release" ]);
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).build(cache.clone()).unwrap();
+    assert_eq!(cargo.command(OperatingSystem::host(), Architecture::host()).args(), &[ "build", "--release" ]);
+    assert_eq!(cargo.command(OperatingSystem::host(), Architecture::host()).exec(), "cargo");
+}
+
+#[test]
+fn test_cargo_bin() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_bin");
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"hello-world\"\nversion = \"0.1.0\"\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // The executable should be replaced, but not the arguments
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).cargo_bin("cross").toolchain("nightly").build(cache).unwrap();
+    assert_eq!(cargo.cargo_bin(), "cross");
+    let command: ShellCommand = cargo.command(OperatingSystem::host(), Architecture::host());
+    assert_eq!(command.exec(), "cross");
+    assert_eq!(command.args(), &[ "+nightly", "build", "--release" ]);
+}
+
+#[test]
+fn test_target_triple() {
+    assert_eq!(target_triple(OperatingSystem::Linux, Architecture::x86_64, Libc::Gnu), "x86_64-unknown-linux-gnu");
+    assert_eq!(target_triple(OperatingSystem::Linux, Architecture::x86_64, Libc::Musl), "x86_64-unknown-linux-musl");
+    assert_eq!(target_triple(OperatingSystem::Linux, Architecture::Aarch64, Libc::Musl), "aarch64-unknown-linux-musl");
+    assert_eq!(target_triple(OperatingSystem::Linux, Architecture::Aarch32, Libc::Gnu), "armv7-unknown-linux-gnueabihf");
+    assert_eq!(target_triple(OperatingSystem::Linux, Architecture::Aarch32, Libc::Musl), "armv7-unknown-linux-musleabihf");
+
+    // Other operating systems ignore the libc
+    assert_eq!(target_triple(OperatingSystem::Windows, Architecture::x86_64, Libc::Musl), "x86_64-pc-windows-msvc");
+    assert_eq!(target_triple(OperatingSystem::MacOs, Architecture::Aarch64, Libc::Musl), "aarch64-apple-darwin");
+}
+
+#[test]
+fn test_cargo_libc() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_libc");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"hello-world\"\nversion = \"0.1.0\"\n").unwrap();
+    std::fs::write(dir.join("src").join("main.rs"), "fn main() {}").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // Cross builds should use the musl triple...
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).target_dir("target").libc(Libc::Musl).build(cache).unwrap();
+    assert_eq!(cargo.libc(), Libc::Musl);
+    assert_eq!(&cargo.command(OperatingSystem::Linux, Architecture::Aarch64).args()[1..4], &[ "--release", "--target", "aarch64-unknown-linux-musl" ]);
+
+    // ...and so should (Linux) host builds, including their effects
+    let host: String = target_triple(OperatingSystem::host(), Architecture::host(), Libc::Musl);
+    assert_eq!(cargo.triple(), Some(host.as_str()));
+    assert_eq!(&cargo.command(OperatingSystem::host(), Architecture::host()).args()[1..4], &[ "--release", "--target", host.as_str() ]);
+    assert_eq!(effect_paths(cargo.effects()), vec![ dir.join("target").join(&host).join("release").join("hello-world") ]);
+}
+
+#[test]
+fn test_cargo_use_cross() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_use_cross");
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"hello-world\"\nversion = \"0.1.0\"\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // Cross should be run instead, with the same arguments
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).use_cross(true).build(cache).unwrap();
+    assert!(cargo.use_cross());
+    let command: ShellCommand = cargo.command(OperatingSystem::Linux, Architecture::Aarch64);
+    assert_eq!(command.exec(), "cross");
+    assert_eq!(&command.args()[1..], &[ "--release", "--target", "aarch64-unknown-linux-gnu" ]);
+}
+
+#[test]
+fn test_cargo_envs() {
+    let dir: PathBuf = test_dir("cargo_envs");
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"hello-world\"\nversion = \"0.1.0\"\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // The variables should be set in the command, on top of the inherited environment
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).env("PKG_CONFIG_ALLOW_CROSS", "1").envs([ ("CC_aarch64_unknown_linux_gnu", "aarch64-linux-gnu-gcc") ]).build(cache.clone()).unwrap();
+    let command: ShellCommand = cargo.command(OperatingSystem::host(), Architecture::host());
+    assert_eq!(command.envs().get("PKG_CONFIG_ALLOW_CROSS").map(String::as_str), Some("1"));
+    assert_eq!(command.envs().get("CC_aarch64_unknown_linux_gnu").map(String::as_str), Some("aarch64-linux-gnu-gcc"));
+    assert_eq!(command.envs().len(), 2);
+    assert_eq!(cargo.envs().len(), 2);
+
+    // Rustflags should be accumulated in a single variable
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).rustflags("-C target-cpu=native").rustflags("-C link-arg=-s").build(cache).unwrap();
+    let command: ShellCommand = cargo.command(OperatingSystem::host(), Architecture::host());
+    assert_eq!(command.envs().get("RUSTFLAGS").map(String::as_str), Some("-C target-cpu=native -C link-arg=-s"));
+}
+
+#[test]
+fn test_cargo_features_env() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_features_env");
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"hello-world\"\nversion = \"0.1.0\"\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+
+    // Features should be read from the environment if none are given...
+    std::env::set_var(DEFAULT_FEATURES_ENV, "a, b");
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).build(cache.clone()).unwrap();
+    assert_eq!(&cargo.command(OperatingSystem::host(), Architecture::host()).args()[1..], &[ "--release", "--features", "a,b" ]);
+    assert_eq!(cargo.command(OperatingSystem::host(), Architecture::host()).cwd(), Some(dir.as_path()));
+
+    // ...but explicit ones take precedence
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).feature("c").build(cache.clone()).unwrap();
+    assert_eq!(cargo.features(), &[ "c".to_string() ]);
+    std::env::remove_var(DEFAULT_FEATURES_ENV);
+
+    // The variable can be changed
+    std::env::set_var("CUSTOM_FEATURES", "d");
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).features_env("CUSTOM_FEATURES").build(cache).unwrap();
+    std::env::remove_var("CUSTOM_FEATURES");
+    assert_eq!(cargo.features(), &[ "d".to_string() ]);
+}
+
+#[test]
+fn test_cargo_autobins() {
+    let dir: PathBuf = test_dir("cargo_autobins");
+    std::fs::create_dir_all(dir.join("src").join("bin").join("two")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"multi\"\nversion = \"0.1.0\"\n").unwrap();
+    std::fs::write(dir.join("src").join("bin").join("one.rs"), "fn main() {}").unwrap();
+    std::fs::write(dir.join("src").join("bin").join("two").join("main.rs"), "fn main() {}").unwrap();
+    std::fs::write(dir.join("src").join("bin").join("README.md"), "Not a binary").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let target_dir: PathBuf = dir.join("target");
+
+    // Both autobins should be found, without a binary for the package itself
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("multi", &dir, &target_dir, &CargoMode::Release, None, &[], cache.clone()).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ target_dir.join("release").join("one"), target_dir.join("release").join("two") ]);
+
+    // ...which is there if there is a `src/main.rs`
+    std::fs::write(dir.join("src").join("main.rs"), "fn main() {}").unwrap();
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("multi", &dir, &target_dir, &CargoMode::Release, None, &[], cache.clone()).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ target_dir.join("release").join("multi"), target_dir.join("release").join("one"), target_dir.join("release").join("two") ]);
+
+    // Explicit binaries are combined with them, unless autobins are disabled
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"multi\"\nversion = \"0.1.0\"\n\n[[bin]]\nname = \"three\"\npath = \"src/main.rs\"\n").unwrap();
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("multi", &dir, &target_dir, &CargoMode::Release, None, &[], cache.clone()).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ target_dir.join("release").join("three"), target_dir.join("release").join("one"), target_dir.join("release").join("two") ]);
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"multi\"\nversion = \"0.1.0\"\nautobins = false\n\n[[bin]]\nname = \"three\"\npath = \"src/main.rs\"\n").unwrap();
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("multi", &dir, &target_dir, &CargoMode::Release, None, &[], cache).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ target_dir.join("release").join("three") ]);
+}
+
+#[test]
+fn test_cargo_bin_path() {
+    let dir: PathBuf = test_dir("cargo_bin_path");
+    std::fs::create_dir_all(dir.join("src").join("bin").join("b")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"auto\"\nversion = \"0.1.0\"\n\n[[bin]]\nname = \"tool\"\npath = \"src/bin/a.rs\"\n").unwrap();
+    std::fs::write(dir.join("src").join("main.rs"), "fn main() {}").unwrap();
+    std::fs::write(dir.join("src").join("bin").join("a.rs"), "fn main() {}").unwrap();
+    std::fs::write(dir.join("src").join("bin").join("b").join("main.rs"), "fn main() {}").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let target_dir: PathBuf = dir.join("target");
+
+    // A binary with a custom path should replace the one discovered for that file, but leave the others (including `src/main.rs`)
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("auto", &dir, &target_dir, &CargoMode::Release, None, &[], cache).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ target_dir.join("release").join("tool"), target_dir.join("release").join("auto"), target_dir.join("release").join("b") ]);
+}
+
+#[test]
+fn test_cargo_workspace_members() {
+    let dir: PathBuf = test_dir("cargo_workspace_members");
+    let ws: PathBuf = dir.join("ws");
+    for member in [ "foo", "bar" ] {
+        std::fs::create_dir_all(ws.join("crates").join(member).join("src")).unwrap();
+        std::fs::write(ws.join("crates").join(member).join("Cargo.toml"), format!("[package]\nname = \"{}\"\nversion = \"0.1.0\"\n", member)).unwrap();
+        std::fs::write(ws.join("crates").join(member).join("src").join("main.rs"), "fn main() {}").unwrap();
+    }
+    std::fs::write(ws.join("Cargo.toml"), "[workspace]\nmembers = [ \"crates/foo\", \"crates/bar\" ]\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let target_dir: PathBuf = ws.join("target");
+
+    // Each member should be found relative to the workspace, sharing its target directory
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("ws", &ws, &target_dir, &CargoMode::Release, None, &[], cache).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ target_dir.join("release").join("foo"), target_dir.join("release").join("bar") ]);
+}
+
+#[test]
+fn test_cargo_metadata_effects() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_metadata_effects");
+    std::fs::create_dir_all(dir.join("src").join("bin")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"meta\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[features]\nextra = []\n\n[lib]\ncrate-type = [\"rlib\", \"cdylib\"]\n\n[[bin]]\nname = \"gated\"\npath = \"src/gated.rs\"\nrequired-features = [\"extra\"]\n").unwrap();
+    std::fs::write(dir.join("src").join("main.rs"), "fn main() {}").unwrap();
+    std::fs::write(dir.join("src").join("lib.rs"), "pub fn hello() {}").unwrap();
+    std::fs::write(dir.join("src").join("gated.rs"), "fn main() {}").unwrap();
+    std::fs::write(dir.join("src").join("bin").join("extra.rs"), "fn main() {}").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    std::env::remove_var("CARGO_TARGET_DIR");
+    std::env::remove_var("CARGO_BUILD_TARGET_DIR");
+    let metadata: CargoMetadata = CargoMetadata::from_path(&dir).unwrap();
+    let target_dir: PathBuf = metadata.target_directory.clone();
+    assert_eq!(target_dir.file_name().and_then(|n| n.to_str()), Some("target"));
+    let sorted = |effects: &[Box<dyn Effect>]| { let mut paths: Vec<PathBuf> = effect_paths(effects); paths.sort(); paths };
+
+    // Cargo should report the auto-discovered binaries and the library, but not the gated binary...
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects_metadata("meta", &dir, &metadata, &CargoMode::Debug, Some("x86_64-unknown-linux-gnu"), &[], cache.clone()).unwrap();
+    let build_dir: PathBuf = target_dir.join("x86_64-unknown-linux-gnu").join("debug");
+    assert_eq!(sorted(&effects), vec![ build_dir.join("extra"), build_dir.join("libmeta.rlib"), build_dir.join("libmeta.so"), build_dir.join("meta") ]);
+
+    // ...unless its features are enabled
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects_metadata("meta", &dir, &metadata, &CargoMode::Debug, Some("x86_64-unknown-linux-gnu"), &[ "extra".to_string() ], cache.clone()).unwrap();
+    assert_eq!(sorted(&effects), vec![ build_dir.join("extra"), build_dir.join("gated"), build_dir.join("libmeta.rlib"), build_dir.join("libmeta.so"), build_dir.join("meta") ]);
+
+    // The builder should use it when told to do so
+    let cargo: CargoTarget = CargoTarget::builder("meta").path(&dir).mode(CargoMode::Debug).metadata(true).build(cache).unwrap();
+    assert_eq!(cargo.effects().len(), 4);
+}
+
+#[test]
+fn test_cargo_lib() {
+    let dir: PathBuf = test_dir("cargo_lib");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"pure-lib\"\nversion = \"0.1.0\"\n").unwrap();
+    std::fs::write(dir.join("src").join("lib.rs"), "pub fn hello() {}").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let target_dir: PathBuf = dir.join("target");
+
+    // A pure library should only produce its rlib
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("lib", &dir, &target_dir, &CargoMode::Release, None, &[], cache.clone()).unwrap();
+    assert_eq!(effects.iter().map(|e| e.name()).collect::<Vec<&str>>(), vec![ "lib_libpure_lib.rlib" ]);
+    assert_eq!(effect_paths(&effects), vec![ target_dir.join("release").join("libpure_lib.rlib") ]);
+
+    // The name and crate types can be customized
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"pure-lib\"\nversion = \"0.1.0\"\n\n[lib]\nname = \"custom\"\ncrate-type = [\"rlib\", \"cdylib\"]\n").unwrap();
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("lib", &dir, &target_dir, &CargoMode::Debug, None, &[], cache).unwrap();
+    assert_eq!(effect_paths(&effects), vec![
+        target_dir.join("debug").join("libcustom.rlib"),
+        target_dir.join("debug").join("libcustom.so"),
+    ]);
+}
+
+#[test]
+fn test_cargo_lib_naming() {
+    let dir: PathBuf = test_dir("cargo_lib_naming");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"my-lib\"\nversion = \"0.1.0\"\n\n[lib]\ncrate-type = [\"cdylib\", \"staticlib\"]\n").unwrap();
+    std::fs::write(dir.join("src").join("lib.rs"), "pub fn hello() {}").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let target_dir: PathBuf = dir.join("target");
+
+    // Every OS names its dynamic and static libraries differently
+    for (triple, files) in [
+        ("x86_64-unknown-linux-gnu", [ "libmy_lib.so", "libmy_lib.a" ]),
+        ("aarch64-apple-darwin", [ "libmy_lib.dylib", "libmy_lib.a" ]),
+        ("x86_64-pc-windows-msvc", [ "my_lib.dll", "my_lib.lib" ]),
+    ] {
+        let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("lib", &dir, &target_dir, &CargoMode::Release, Some(triple), &[], cache.clone()).unwrap();
+        assert_eq!(effect_paths(&effects), files.iter().map(|f| target_dir.join(triple).join("release").join(f)).collect::<Vec<PathBuf>>());
+    }
+}
+
+#[test]
+fn test_cargo_windows_effects() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_windows_effects");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::create_dir_all(dir.join("examples")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n\n[lib]\ncrate-type = [\"rlib\", \"cdylib\", \"staticlib\"]\n").unwrap();
+    std::fs::write(dir.join("src").join("main.rs"), "fn main() {}").unwrap();
+    std::fs::write(dir.join("src").join("lib.rs"), "pub fn hello() {}").unwrap();
+    std::fs::write(dir.join("examples").join("demo.rs"), "fn main() {}").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let target_dir: PathBuf = dir.join("target");
+    let win_dir: PathBuf = target_dir.join("x86_64-pc-windows-msvc");
+
+    // Executables should get their extension, and libraries should be named the Windows way
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("foo", &dir, &target_dir, &CargoMode::Release, Some("x86_64-pc-windows-msvc"), &[], cache.clone()).unwrap();
+    assert_eq!(effect_paths(&effects), vec![
+        win_dir.join("release").join("foo.exe"),
+        win_dir.join("release").join("libfoo.rlib"),
+        win_dir.join("release").join("foo.dll"),
+        win_dir.join("release").join("foo.lib"),
+    ]);
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_example_effects("foo", &dir, &target_dir, &CargoMode::Release, Some("x86_64-pc-windows-msvc"), &[], true, cache.clone()).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ win_dir.join("release").join("examples").join("demo.exe") ]);
+
+    // The builder should do so when cross-compiling to Windows
+    std::env::remove_var("CARGO_TARGET_DIR");
+    std::env::remove_var("CARGO_BUILD_TARGET_DIR");
+    let cargo: CargoTarget = CargoTarget::builder("foo").path(&dir).mode(CargoMode::Release).triple("x86_64-pc-windows-msvc").build(cache).unwrap();
+    assert_eq!(effect_paths(cargo.effects())[0], PathBuf::from("./target/x86_64-pc-windows-msvc/release/foo.exe"));
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_cargo_cross_triple() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_cross_triple");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n").unwrap();
+    std::fs::write(dir.join("src").join("main.rs"), "fn main() {}").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    std::env::remove_var("CARGO_TARGET_DIR");
+    std::env::remove_var("CARGO_BUILD_TARGET_DIR");
+
+    // Cross-compiling effects deduced for the host would leave them where cargo never writes, so it should be refused
+    let host: &'static CargoTarget = Box::leak(Box::new(CargoTarget::builder("host").path(&dir).mode(CargoMode::Release).build(cache.clone()).unwrap()));
+    assert_eq!(effect_paths(host.effects()), vec![ PathBuf::from("./target/release/foo") ]);
+    let installer: Installer = Installer::builder().add_target(host).build();
+    match installer.build("host", OperatingSystem::Windows, Architecture::x86_64, true, true) {
+        Err(BuildError::MakeError{ err: TargetError::BuildError{ err, .. }, .. }) => assert!(matches!(err.downcast_ref::<CargoError>(), Some(CargoError::TripleMismatch{ expected: None, got, .. }) if got == "x86_64-pc-windows-msvc")),
+        res => panic!("Expected a triple mismatch, got {:?}", res),
+    }
+
+    // But it should work if the effects were deduced for that triple (with the proper extension)
+    let windows: CargoTarget = CargoTarget::builder("windows").path(&dir).mode(CargoMode::Release).triple("x86_64-pc-windows-msvc").build(cache).unwrap();
+    assert_eq!(effect_paths(windows.effects()), vec![ PathBuf::from("./target/x86_64-pc-windows-msvc/release/foo.exe") ]);
+    windows.build(OperatingSystem::Windows, Architecture::x86_64, true).unwrap();
+}
+
+#[test]
+fn test_cargo_triple_effects() {
+    let dir: PathBuf = test_dir("cargo_triple_effects");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::create_dir_all(dir.join("examples")).unwrap();
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"foo\"\nversion = \"0.1.0\"\n").unwrap();
+    std::fs::write(dir.join("src").join("main.rs"), "fn main() {}").unwrap();
+    std::fs::write(dir.join("examples").join("demo.rs"), "fn main() {}").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let target_dir: PathBuf = dir.join("target");
+
+    // Host builds write directly to the mode directory
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("foo", &dir, &target_dir, &CargoMode::Debug, None, &[], cache.clone()).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ target_dir.join("debug").join("foo") ]);
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_example_effects("foo", &dir, &target_dir, &CargoMode::Debug, None, &[], true, cache.clone()).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ target_dir.join("debug").join("examples").join("demo") ]);
+
+    // Cross builds nest it in a directory named after the triple
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("foo", &dir, &target_dir, &CargoMode::Debug, Some("aarch64-unknown-linux-gnu"), &[], cache.clone()).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ target_dir.join("aarch64-unknown-linux-gnu").join("debug").join("foo") ]);
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_example_effects("foo", &dir, &target_dir, &CargoMode::Debug, Some("aarch64-unknown-linux-gnu"), &[], true, cache).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ target_dir.join("aarch64-unknown-linux-gnu").join("debug").join("examples").join("demo") ]);
+}
+
+#[test]
+fn test_cargo_required_features() {
+    let dir: PathBuf = test_dir("cargo_required_features");
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"gated\"\nversion = \"0.1.0\"\n\n[features]\ndefault = [\"std\"]\nstd = []\nfull = [\"extra\"]\nextra = []\n\n[[bin]]\nname = \"always\"\nrequired-features = [\"std\"]\n\n[[bin]]\nname = \"sometimes\"\nrequired-features = [\"extra\"]\n").unwrap();
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let target_dir: PathBuf = dir.join("target");
+
+    // Without the feature, only the binary gated by a default feature should be there
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("gated", &dir, &target_dir, &CargoMode::Release, None, &[], cache.clone()).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ target_dir.join("release").join("always") ]);
+
+    // With it (even if enabled through another feature), both should be
+    let effects: Vec<Box<dyn Effect>> = CargoTarget::deduce_effects("gated", &dir, &target_dir, &CargoMode::Release, None, &[ "full".to_string() ], cache.clone()).unwrap();
+    assert_eq!(effect_paths(&effects), vec![ target_dir.join("release").join("always"), target_dir.join("release").join("sometimes") ]);
+
+    // The builder should take its own features into account
+    let cargo: CargoTarget = CargoTarget::builder("gated").path(&dir).feature("extra").build(cache).unwrap();
+    assert_eq!(cargo.effects().len(), 2);
+}
+
+#[test]
+fn test_cargo_config_target() {
+    let _lock : MutexGuard<()> = env_lock();
+    let dir   : PathBuf        = test_dir("cargo_config_target");
+    std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"hello-world\"\nversion = \"0.1.0\"\n").unwrap();
+    std::fs::create_dir_all(dir.join(".cargo")).unwrap();
+    std::fs::write(dir.join(".cargo").join("config.toml"), "[build]\ntarget = \"x86_64-unknown-linux-musl\"\n").unwrap();
+    let cache      : Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap());
+    let target_dir : PathBuf   = CargoTarget::resolve_target_dir(&dir, false);
+
+    // The config's target should be used for both the command and the effects...
+    assert_eq!(CargoTarget::find_config_target(&dir), Some("x86_64-unknown-linux-musl".to_string()));
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).build(cache.clone()).unwrap();
+    assert_eq!(cargo.triple(), Some("x86_64-unknown-linux-musl"));
+    assert_eq!(&cargo.command(OperatingSystem::host(), Architecture::host()).args()[1..], &[ "--release", "--target", "x86_64-unknown-linux-musl" ]);
+    assert_eq!(effect_paths(cargo.effects()), vec![ target_dir.join("x86_64-unknown-linux-musl").join("release").join("hello-world") ]);
+
+    // ...unless the builder overrides it
+    let cargo: CargoTarget = CargoTarget::builder("hello-world").path(&dir).triple("aarch64-unknown-linux-gnu").build(cache).unwrap();
+    assert_eq!(effect_paths(cargo.effects()), vec![ target_dir.join("aarch64-unknown-linux-gnu").join("release").join("hello-world") ]);
+}
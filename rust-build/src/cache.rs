@@ -13,23 +13,159 @@
 //!   various things.
 // 
 
+use std::collections::{HashMap, HashSet};
 use std::collections::hash_map::DefaultHasher;
-use std::fmt::{Formatter, Result as FResult};
+use std::fmt::{Display, Formatter, Result as FResult};
 use std::fs::{self, File, Metadata};
 use std::hash::{Hash, Hasher};
+use std::io::{Read, Write};
 use std::ops::{Deref, DerefMut};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
 use filetime::FileTime;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use serde::de::{self, Visitor};
+use serde::de::{self, DeserializeOwned, Visitor};
 use serde::ser::SerializeSeq;
+use sha2::{Digest, Sha256};
 
-use crate::debug;
+use crate::{debug, warn};
 pub use crate::errors::{CacheError as Error, LastEditedTimeError};
 
 
+/***** CONSTANTS *****/
+/// The version of the format written by `Cache::export()`. Bump this whenever the format of the blob itself changes (see `FORMAT_VERSION` for the format of the entries).
+pub const EXPORT_VERSION: u32 = 1;
+
+/// The name of the file in the cache directory that is used to lock the cache between processes (see `Cache::lock()`).
+pub const LOCK_FILE: &str = ".lock";
+
+/// The version of the format of the entries written by a Cache by default (see `Cache::with_version()`). Bump this whenever the format of (any of) the entries changes, such that old entries are treated as a miss instead of misread.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The minimum number of entries that `FsBackend` reads or writes per thread in batch operations (see `Cache::get_files()`). Smaller batches are not worth spawning threads for.
+const PARALLEL_CHUNK_SIZE: usize = 16;
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines the format of an exported cache (see `Cache::export()`).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct ExportedCache {
+    /// The version of the format.
+    version : u32,
+    /// The entries in the cache, by identifier (see `Cache::hash()`).
+    entries : HashMap<u64, serde_json::Value>,
+}
+
+
+
+/// Applies the given function to all items in parallel, in chunks of at least `PARALLEL_CHUNK_SIZE` items per thread.
+/// 
+/// # Arguments
+/// - `items`: The items to apply the function to.
+/// - `f`: The function to apply.
+/// 
+/// # Returns
+/// The results of the function, in the same order as the items.
+/// 
+/// # Errors
+/// This function errors if the function errored for any of the items.
+fn par_map<T: Sync, R: Send>(items: &[T], f: impl Sync + Fn(&T) -> Result<R, Error>) -> Result<Vec<R>, Error> {
+    let threads    : usize = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let chunk_size : usize = items.len().div_ceil(threads).max(PARALLEL_CHUNK_SIZE);
+    if items.len() <= chunk_size { return items.iter().map(f).collect(); }
+
+    // Run every chunk in a separate thread, then collect the results in-order
+    std::thread::scope(|scope| {
+        let f: &(dyn Sync + Fn(&T) -> Result<R, Error>) = &f;
+        let handles: Vec<thread::ScopedJoinHandle<Result<Vec<R>, Error>>> = items.chunks(chunk_size).map(|chunk| scope.spawn(move || chunk.iter().map(f).collect())).collect();
+        let mut res: Vec<R> = Vec::with_capacity(items.len());
+        for handle in handles {
+            match handle.join() {
+                Ok(chunk) => { res.extend(chunk?); },
+                Err(err)  => std::panic::resume_unwind(err),
+            }
+        }
+        Ok(res)
+    })
+}
+
+
+
+/// Defines how a CacheEntry (or DirectoryEntry) is stored on disk, i.e., together with the path of the file it tracks.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct StoredCacheEntry<T = CacheEntry> {
+    /// The path of the tracked file. May be missing for entries written by older versions.
+    #[serde(default)]
+    path  : Option<PathBuf>,
+    /// The entry itself.
+    #[serde(flatten)]
+    entry : T,
+}
+
+
+
+/// Keeps track of how a Cache is used (see `Cache::stats()`).
+#[derive(Debug, Default)]
+struct CacheCounters {
+    /// The number of lookups that found an entry.
+    hits   : AtomicUsize,
+    /// The number of lookups that did not find an entry.
+    misses : AtomicUsize,
+    /// The number of entries written.
+    writes : AtomicUsize,
+}
+
+
+
+
+
 /***** LIBRARY *****/
+/// Summarizes how a Cache has been used so far (see `Cache::stats()`), e.g., to find out why a build rebuilds too much.
+/// 
+/// Its Display implementation prints it as `42 hits, 3 misses, 5 writes`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    /// The number of file (or directory) lookups that found an entry.
+    pub hits   : usize,
+    /// The number of file (or directory) lookups that did not find an entry.
+    pub misses : usize,
+    /// The number of file (or directory) entries written.
+    pub writes : usize,
+}
+
+impl Display for CacheStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "{} hit{}, {} miss{}, {} write{}", self.hits, if self.hits != 1 { "s" } else { "" }, self.misses, if self.misses != 1 { "es" } else { "" }, self.writes, if self.writes != 1 { "s" } else { "" })
+    }
+}
+
+
+
+/// Holds the lock on a Cache (see `Cache::lock()`), which is released once it is dropped.
+#[derive(Debug)]
+pub struct CacheGuard<'c> {
+    /// The lock file that we have locked, if the cache has any.
+    file   : Option<Arc<File>>,
+    /// The guard of the lock between threads of this process, which is released after the file is unlocked.
+    _guard : MutexGuard<'c, ()>,
+}
+
+impl Drop for CacheGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(file) = &self.file {
+            if let Err(_err) = file.unlock() { warn!("Failed to unlock cache lock file: {}", _err); }
+        }
+    }
+}
+
+
+
 /// Defines a custom wrapper around a FileTime to implement serialize & deserialize for it.
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub struct LastEditedTime(FileTime);
@@ -61,7 +197,61 @@ impl LastEditedTime {
         };
 
         // Return the LastEditedTime
-        Ok(Self(FileTime::from_last_modification_time(&metadata)))
+        Ok(Self::from_metadata(&metadata))
+    }
+
+    /// Constructor for the LastEditedTime that retrieves it from already read metadata (e.g., to not follow a symlink with `fs::symlink_metadata()`).
+    /// 
+    /// # Arguments
+    /// - `metadata`: The metadata of the file or directory to retrieve the last edited time for.
+    /// 
+    /// # Returns
+    /// A new LastEditedTime instance that represents the last modification time in the metadata.
+    #[inline]
+    pub fn from_metadata(metadata: &Metadata) -> Self {
+        Self(FileTime::from_last_modification_time(metadata))
+    }
+
+    /// Constructor for the LastEditedTime that retrieves the most recent one of the given directory and anything nested in it.
+    /// 
+    /// Symlinks inside the directory are not followed (only their own last edited time is considered), such that symlink loops are harmless. If the path is not a directory, this is equivalent to `LastEditedTime::from_path()`.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the directory (or file) to retrieve the last edited time for.
+    /// 
+    /// # Returns
+    /// A new LastEditedTime instance that represents the most recent point in time anything in the given directory was edited.
+    /// 
+    /// # Errors
+    /// This function may error if the given directory doesn't exist or if it or anything in it couldn't be read.
+    pub fn from_path_recursive(path: impl AsRef<Path>) -> Result<Self, LastEditedTimeError> {
+        let path: &Path = path.as_ref();
+        let mut res: Self = Self::from_path(path)?;
+        if !path.is_dir() { return Ok(res); }
+
+        // Walk the tree without following symlinks
+        let mut todo: Vec<PathBuf> = vec![ path.into() ];
+        while let Some(dir) = todo.pop() {
+            let entries: fs::ReadDir = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(err)    => { return Err(LastEditedTimeError::DirReadError{ path: dir, err }); },
+            };
+            for entry in entries {
+                let entry: fs::DirEntry = match entry {
+                    Ok(entry) => entry,
+                    Err(err)  => { return Err(LastEditedTimeError::DirReadError{ path: dir, err }); },
+                };
+                let metadata: Metadata = match fs::symlink_metadata(entry.path()) {
+                    Ok(metadata) => metadata,
+                    Err(err)     => { return Err(LastEditedTimeError::PathMetadataReadError{ path: entry.path(), err }); },
+                };
+
+                // Update the time and recurse into actual directories
+                res = res.max(Self::from_metadata(&metadata));
+                if metadata.is_dir() { todo.push(entry.path()); }
+            }
+        }
+        Ok(res)
     }
 }
 
@@ -203,7 +393,13 @@ impl DerefMut for LastEditedTime {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct CacheEntry {
     /// The last time the file was edited.
-    pub last_edited : LastEditedTime,
+    pub last_edited  : LastEditedTime,
+    /// The SHA-256 hash of the file's contents, if it is tracked by content (see `Cache::update_file_hash()`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash         : Option<String>,
+    /// The hash of the command that produced the file, if it is an artifact of one (see `ShellCommand::command_hash()`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_hash : Option<u64>,
 }
 
 impl AsRef<CacheEntry> for CacheEntry {
@@ -233,129 +429,1386 @@ impl From<&mut CacheEntry> for CacheEntry {
 
 
 
-/// The Cache struct is used to interact with the build cache, which stores information about whether things have been updated since last calls.
-#[derive(Clone, Debug)]
-pub struct Cache {
-    /// The path where this cache lives.
-    path : PathBuf,
+/// The DirectoryEntry struct provides cached information about a whole directory tree.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DirectoryEntry {
+    /// The most recent time anything in the tree was edited.
+    pub last_edited : LastEditedTime,
+    /// The number of files (including directories and symlinks) in the tree, such that removals are detected even if they do not affect the last edited time.
+    pub count       : usize,
 }
 
-impl Cache {
-    /// Constructor for the Cache.
+
+
+/// The FingerprintEntry struct provides cached information about the configuration of something (typically, a target).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FingerprintEntry {
+    /// The hash of the configuration.
+    pub fingerprint : u64,
+}
+
+
+
+/// The DownloadEntry struct provides cached information about a file downloaded from somewhere.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DownloadEntry {
+    /// The ETag that the server reported for the file, if any.
+    pub etag          : Option<String>,
+    /// The Last-Modified time that the server reported for the file, if any.
+    pub last_modified : Option<String>,
+    /// The SHA-256 hash of the downloaded file's contents (see `Cache::hash_file()`).
+    pub content_hash  : String,
+}
+
+
+
+/// Defines how the entries of a Cache are stored.
+/// 
+/// Entries are identified by their ID (see `Cache::hash()`) and stored as JSON values. The Cache takes care of (de)serializing, versioning and locking them, so backends only have to store them.
+pub trait CacheBackend: std::fmt::Debug + Send + Sync {
+    /// Returns where the entry with the given ID is stored, for use in errors and messages.
     /// 
     /// # Arguments
-    /// - `path`: The path to the build cache directory that we will use / have used last time. Obviously, it should make sense to try and keep this in the same location.
-    /// - `create_path`: Whether to attempt to create the directory if it does not exist (true) or just error instead (false).
+    /// - `hash`: The ID of the entry.
     /// 
     /// # Returns
-    /// A new Cache instance.
+    /// The (possibly fictional) path of the entry.
+    fn location(&self, hash: u64) -> PathBuf;
+
+    /// Reads the entry with the given ID.
+    /// 
+    /// # Arguments
+    /// - `hash`: The ID of the entry.
+    /// 
+    /// # Returns
+    /// The entry, or `None` if there is none.
     /// 
     /// # Errors
-    /// This function errors if any sanity checks about the path failed (whether it exists and is a directory and such).
-    pub fn new(path: impl Into<PathBuf>, create_path: bool) -> Result<Self, Error> {
-        let path: PathBuf = path.into();
-
-        // Do some path sanity checks
-        if !path.exists() {
-            // Create it or sniff it
-            if create_path {
-                if let Err(err) = fs::create_dir_all(&path) { return Err(Error::CacheDirCreateError { path, err }); }
-            } else {
-                return Err(Error::CacheDirNotFound { path });
-            }
-        }
-        if !path.is_dir() {
-            return Err(Error::CacheDirNotADir { path });
-        }
+    /// This function errors if the entry exists but could not be read or parsed.
+    fn get(&self, hash: u64) -> Result<Option<serde_json::Value>, Error>;
 
-        // It checks out
-        debug!("Cache location at: '{}'", path.display());
-        Ok(Self {
-            path : path.into(),
-        })
+    /// Reads the entries with the given IDs at once.
+    /// 
+    /// By default, this simply reads them one-by-one. Backends may override it to amortize the cost of many reads.
+    /// 
+    /// # Arguments
+    /// - `hashes`: The IDs of the entries.
+    /// 
+    /// # Returns
+    /// The entries (or `None` if there is none), in the same order as the given IDs.
+    /// 
+    /// # Errors
+    /// This function errors if any of the entries exists but could not be read or parsed.
+    fn get_many(&self, hashes: &[u64]) -> Result<Vec<Option<serde_json::Value>>, Error> {
+        hashes.iter().map(|hash| self.get(*hash)).collect()
     }
 
+    /// Writes the entry with the given ID, replacing any existing one.
+    /// 
+    /// # Arguments
+    /// - `hash`: The ID of the entry.
+    /// - `value`: The entry to write.
+    /// 
+    /// # Errors
+    /// This function errors if the entry could not be written.
+    fn put(&self, hash: u64, value: &serde_json::Value) -> Result<(), Error>;
 
+    /// Writes the given entries at once, replacing any existing ones.
+    /// 
+    /// By default, this simply writes them one-by-one. Backends may override it to amortize the cost of many writes. The IDs are guaranteed to be unique.
+    /// 
+    /// # Arguments
+    /// - `entries`: The IDs and entries to write.
+    /// 
+    /// # Errors
+    /// This function errors if any of the entries could not be written.
+    fn put_many(&self, entries: &[(u64, serde_json::Value)]) -> Result<(), Error> {
+        for (hash, value) in entries { self.put(*hash, value)?; }
+        Ok(())
+    }
 
-    /// A bit of an odd function that hashes a given source identifier to a cache identifier.
+    /// Removes the entry with the given ID, if any.
     /// 
     /// # Arguments
-    /// - `source`: The source identifier (i.e., path, Docker image name, ...) to convert into a proper cache ID.
+    /// - `hash`: The ID of the entry.
     /// 
     /// # Returns
-    /// The hash of the path, as a raw u64 number.
-    pub fn hash(source: impl Hash) -> u64 {
-        let mut hasher: DefaultHasher = DefaultHasher::new();
-        source.hash(&mut hasher);
-        hasher.finish()
+    /// Whether there was an entry to remove.
+    /// 
+    /// # Errors
+    /// This function errors if the entry could not be removed.
+    fn remove(&self, hash: u64) -> Result<bool, Error>;
+
+    /// Returns the IDs of all entries.
+    /// 
+    /// # Errors
+    /// This function errors if the entries could not be listed.
+    fn ids(&self) -> Result<Vec<u64>, Error>;
+
+    /// Removes all entries.
+    /// 
+    /// # Returns
+    /// The number of entries that were removed.
+    /// 
+    /// # Errors
+    /// This function errors if the entries could not be listed or removed.
+    fn clear(&self) -> Result<usize, Error> {
+        let mut removed: usize = 0;
+        for hash in self.ids()? {
+            if self.remove(hash)? { removed += 1; }
+        }
+        Ok(removed)
     }
+}
 
 
 
-    /// Returns the cache entry for the given file if there is any.
+/// Stores cache entries as one JSON file per entry in a directory. This is the backend used by `Cache::new()`.
+#[derive(Clone, Debug)]
+pub struct FsBackend {
+    /// The directory where the entries are stored.
+    path : PathBuf,
+}
+
+impl FsBackend {
+    /// Constructor for the FsBackend.
     /// 
     /// # Arguments
-    /// - `file`: The file to cache. Note that its path acts as a unique identifier.
+    /// - `path`: The (existing) directory to store the entries in.
     /// 
     /// # Returns
-    /// The CacheEntry if we were able to find one. Otherwise, returns `None`.
+    /// A new FsBackend instance.
+    #[inline]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Returns the IDs of all files (or other things) in the directory that are named like an entry.
     /// 
     /// # Errors
-    /// This function errors if the make cache was ill-formed or if we encounter disk IO errors.
-    pub fn get_file(&self, file: impl AsRef<Path>) -> Result<Option<CacheEntry>, Error> {
-        let file: &Path = file.as_ref();
+    /// This function errors if we failed to read the directory.
+    fn names(&self) -> Result<Vec<u64>, Error> {
+        let entries: fs::ReadDir = match fs::read_dir(&self.path) {
+            Ok(entries) => entries,
+            Err(err)    => { return Err(Error::CacheDirReadError{ path: self.path.clone(), err }); },
+        };
+        let mut res: Vec<u64> = vec![];
+        for entry in entries {
+            let entry: fs::DirEntry = match entry {
+                Ok(entry) => entry,
+                Err(err)  => { return Err(Error::CacheDirReadError{ path: self.path.clone(), err }); },
+            };
+            if let Some(hash) = entry.file_name().to_str().and_then(|name| name.parse().ok()) { res.push(hash); }
+        }
+        Ok(res)
+    }
+}
 
-        // Hash the filename to use as identifier
-        let hash  : u64    = Self::hash(file);
-        let shash : String = format!("{}", hash);
-        debug!("get_file(): File '{}' ID: {}", file.display(), shash);
+impl CacheBackend for FsBackend {
+    #[inline]
+    fn location(&self, hash: u64) -> PathBuf { self.path.join(format!("{}", hash)) }
 
+    fn get(&self, hash: u64) -> Result<Option<serde_json::Value>, Error> {
         // Attempt to find the file with that information
-        let file_path: PathBuf = self.path.join(shash);
+        let file_path: PathBuf = self.location(hash);
         if !file_path.exists() { return Ok(None); }
         if !file_path.is_file() { return Err(Error::CacheEntryNotAFile{ path: file_path }); }
 
         // Attempt to read it using serde
         match File::open(&file_path) {
             Ok(handle) => match serde_json::from_reader(handle) {
-                Ok(entry) => Ok(Some(entry)),
+                Ok(value) => Ok(Some(value)),
                 Err(err)  => Err(Error::CacheEntryParseError{ path: file_path, err }),
             },
             Err(err) => Err(Error::CacheEntryOpenError{ path: file_path, err }),
         }
     }
 
-    /// Updates the cache entry for a given file if there is any.
+    /// Lists the directory once to find which entries exist, and then reads those in parallel.
+    fn get_many(&self, hashes: &[u64]) -> Result<Vec<Option<serde_json::Value>>, Error> {
+        let present: HashSet<u64> = self.names()?.into_iter().collect();
+        par_map(hashes, |hash| if present.contains(hash) { self.get(*hash) } else { Ok(None) })
+    }
+
+    /// Writes the entry to a temporary file in the directory first, which is then renamed to the entry. This is atomic (on the same filesystem), such that an interrupted write never leaves a corrupt entry behind.
+    fn put(&self, hash: u64, value: &serde_json::Value) -> Result<(), Error> {
+        let file_path: PathBuf = self.location(hash);
+        let temp_path: PathBuf = self.path.join(format!(".{}.{}.tmp", hash, std::process::id()));
+        let res: Result<(), Error> = match File::create(&temp_path) {
+            Ok(handle) => match serde_json::to_writer(handle, value) {
+                Ok(_)    => Ok(()),
+                Err(err) => Err(Error::CacheEntryWriteError{ path: temp_path.clone(), err }),
+            },
+            Err(err) => Err(Error::CacheEntryCreateError{ path: temp_path.clone(), err }),
+        };
+
+        // Move it in place, or clean up if anything failed
+        let res: Result<(), Error> = res.and_then(|_| fs::rename(&temp_path, &file_path).map_err(|err| Error::CacheEntryRenameError{ from: temp_path.clone(), to: file_path, err }));
+        if res.is_err() { let _ = fs::remove_file(&temp_path); }
+        res
+    }
+
+    /// Writes the entries in parallel.
+    fn put_many(&self, entries: &[(u64, serde_json::Value)]) -> Result<(), Error> {
+        par_map(entries, |(hash, value)| self.put(*hash, value)).map(|_| ())
+    }
+
+    fn remove(&self, hash: u64) -> Result<bool, Error> {
+        let file_path: PathBuf = self.location(hash);
+        if !file_path.is_file() { return Ok(false); }
+        match fs::remove_file(&file_path) {
+            Ok(_)    => Ok(true),
+            Err(err) => Err(Error::CacheEntryRemoveError{ path: file_path, err }),
+        }
+    }
+
+    /// Only files named like an ID are considered, such that other files in the directory (e.g., the lock file) are left alone.
+    fn ids(&self) -> Result<Vec<u64>, Error> {
+        Ok(self.names()?.into_iter().filter(|hash| self.location(*hash).is_file()).collect())
+    }
+}
+
+
+
+/// Stores cache entries in memory only, which is useful for tests or for builds that should not leave a cache behind.
+#[derive(Debug, Default)]
+pub struct InMemoryBackend {
+    /// The entries, by ID.
+    entries : Mutex<HashMap<u64, serde_json::Value>>,
+}
+
+impl InMemoryBackend {
+    /// Constructor for the InMemoryBackend that starts without any entries.
+    /// 
+    /// # Returns
+    /// A new InMemoryBackend instance.
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+    /// Returns the entries, ignoring poisoning since a panic cannot leave the map in an invalid state.
+    #[inline]
+    fn entries(&self) -> MutexGuard<'_, HashMap<u64, serde_json::Value>> { self.entries.lock().unwrap_or_else(|err| err.into_inner()) }
+}
+
+impl CacheBackend for InMemoryBackend {
+    #[inline]
+    fn location(&self, hash: u64) -> PathBuf { PathBuf::from(format!("<memory>/{}", hash)) }
+
+    #[inline]
+    fn get(&self, hash: u64) -> Result<Option<serde_json::Value>, Error> { Ok(self.entries().get(&hash).cloned()) }
+
+    #[inline]
+    fn put(&self, hash: u64, value: &serde_json::Value) -> Result<(), Error> {
+        self.entries().insert(hash, value.clone());
+        Ok(())
+    }
+
+    #[inline]
+    fn remove(&self, hash: u64) -> Result<bool, Error> { Ok(self.entries().remove(&hash).is_some()) }
+
+    #[inline]
+    fn ids(&self) -> Result<Vec<u64>, Error> { Ok(self.entries().keys().copied().collect()) }
+
+    #[inline]
+    fn clear(&self) -> Result<usize, Error> {
+        let mut entries: MutexGuard<HashMap<u64, serde_json::Value>> = self.entries();
+        let removed: usize = entries.len();
+        entries.clear();
+        Ok(removed)
+    }
+}
+
+
+
+/// The Cache struct is used to interact with the build cache, which stores information about whether things have been updated since last calls.
+/// 
+/// The Cache may be used from multiple threads at once, e.g., when targets are build in parallel; reads and writes of the same entry are serialized. As such, it is typically shared as an `Arc<Cache>`. Clones of a Cache share the same locks.
+#[derive(Clone, Debug)]
+pub struct Cache {
+    /// The path where this cache lives.
+    path      : PathBuf,
+    /// The storage of the entries.
+    backend   : Arc<dyn CacheBackend>,
+    /// Whether this cache may only be read from (true) or also written to (false).
+    read_only : bool,
+    /// The version of the format of the entries. Entries with another version are treated as if they don't exist.
+    version   : u32,
+    /// The locks of the entries that are (or have been) in use, by identifier (see `Cache::hash()`).
+    locks     : Arc<Mutex<HashMap<u64, Arc<Mutex<()>>>>>,
+    /// The file used to lock the cache as a whole between processes, if we could open it (see `Cache::lock()`).
+    lock_file : Option<Arc<File>>,
+    /// The lock of the cache as a whole between threads, since the lock file is shared between them.
+    lock      : Arc<Mutex<()>>,
+    /// The (canonical) directory that paths are stored relative to, if any (see `Cache::with_root()`).
+    root      : Option<PathBuf>,
+    /// The statistics of how this cache has been used (see `Cache::stats()`).
+    counters  : Arc<CacheCounters>,
+}
+
+impl Cache {
+    /// Constructor for the Cache.
     /// 
     /// # Arguments
-    /// - `file`: The file to update the cache for. Note that its path acts as a unique identifier.
-    /// - `info`: The CacheEntry with the info to update the file to.
-    /// - `dry_run`: If true, does not actually update the file physically but rather just prints it would.
+    /// - `path`: The path to the build cache directory that we will use / have used last time. Obviously, it should make sense to try and keep this in the same location.
+    /// - `create_path`: Whether to attempt to create the directory if it does not exist (true) or just error instead (false).
+    /// 
+    /// # Returns
+    /// A new Cache instance.
     /// 
     /// # Errors
-    /// This function errors if we failed to update the cache entry. This is typically due to IO errors.
-    pub fn update_file(&self, file: impl AsRef<Path>, info: impl AsRef<CacheEntry>, dry_run: bool) -> Result<(), Error> {
-        let file : &Path       = file.as_ref();
-        let info : &CacheEntry = info.as_ref();
+    /// This function errors if any sanity checks about the path failed (whether it exists and is a directory and such).
+    #[inline]
+    pub fn new(path: impl Into<PathBuf>, create_path: bool) -> Result<Self, Error> { Self::open(path.into(), create_path, false) }
 
-        // Hash the filename to use as identifier
-        let hash  : u64    = Self::hash(file);
-        let shash : String = format!("{}", hash);
-        debug!("update_file(): File '{}' ID: {}", file.display(), shash);
+    /// Opens the cache in the given directory, which backs both `Cache::new()` and `Cache::read_only()`.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to the build cache directory.
+    /// - `create_path`: Whether to attempt to create the directory if it does not exist (true) or just error instead (false).
+    /// - `read_only`: Whether to open the cache in read-only mode, in which case nothing in the directory is created or opened for writing (including the lock file).
+    /// 
+    /// # Returns
+    /// A new Cache instance.
+    /// 
+    /// # Errors
+    /// This function errors if any sanity checks about the path failed (whether it exists and is a directory and such).
+    fn open(path: PathBuf, create_path: bool, read_only: bool) -> Result<Self, Error> {
 
-        // Attempt to write the cache entry to that file
-        let file_path: PathBuf = self.path.join(shash);
-        if !dry_run {
-            match File::create(&file_path) {
-                Ok(handle) => match serde_json::to_writer(handle, info) {
-                    Ok(_)    => Ok(()),
-                    Err(err) => Err(Error::CacheEntryWriteError{ path: file_path, err }),
-                },
-                Err(err) => Err(Error::CacheEntryCreateError{ path: file_path, err }),
+        // Do some path sanity checks
+        if !path.exists() {
+            // Create it or sniff it
+            if create_path {
+                if let Err(err) = fs::create_dir_all(&path) { return Err(Error::CacheDirCreateError { path, err }); }
+            } else {
+                return Err(Error::CacheDirNotFound { path });
             }
-        } else {
-            println!("[dry_run] File '{}' would be updated of change", file_path.display());
-            Ok(())
         }
+        if !path.is_dir() {
+            return Err(Error::CacheDirNotADir { path });
+        }
+
+        // Open the lock file (only for reading if we may not write, falling back to that for caches that we cannot write to)
+        let lock_path: PathBuf = path.join(LOCK_FILE);
+        let lock_file: Result<File, std::io::Error> = if read_only {
+            File::open(&lock_path)
+        } else {
+            fs::OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&lock_path).or_else(|_| File::open(&lock_path))
+        };
+        let lock_file: Option<Arc<File>> = match lock_file {
+            Ok(file)  => Some(Arc::new(file)),
+            Err(_err) => {
+                debug!("Failed to open cache lock file '{}': {}", lock_path.display(), _err);
+                None
+            },
+        };
+
+        // It checks out
+        debug!("Cache location at: '{}'", path.display());
+        let backend: Arc<dyn CacheBackend> = Arc::new(FsBackend::new(&path));
+        Ok(Self {
+            path,
+            backend,
+            read_only,
+            version   : FORMAT_VERSION,
+            locks     : Arc::new(Mutex::new(HashMap::new())),
+            lock_file,
+            lock      : Arc::new(Mutex::new(())),
+            root      : std::env::current_dir().ok().map(Self::resolve),
+            counters  : Arc::new(CacheCounters::default()),
+        })
+    }
+
+    /// Constructor for the Cache that stores its entries in the given backend instead of in a directory (e.g., an `InMemoryBackend` for tests).
+    /// 
+    /// Since there is no cache directory, the cache is only locked between threads of this process (see `Cache::lock()`).
+    /// 
+    /// # Arguments
+    /// - `backend`: The CacheBackend that stores the entries.
+    /// 
+    /// # Returns
+    /// A new Cache instance.
+    pub fn with_backend(backend: impl 'static + CacheBackend) -> Self {
+        Self {
+            path      : PathBuf::new(),
+            backend   : Arc::new(backend),
+            read_only : false,
+            version   : FORMAT_VERSION,
+            locks     : Arc::new(Mutex::new(HashMap::new())),
+            lock_file : None,
+            lock      : Arc::new(Mutex::new(())),
+            root      : std::env::current_dir().ok().map(Self::resolve),
+            counters  : Arc::new(CacheCounters::default()),
+        }
+    }
+
+    /// Sets the version of the format of the entries in this cache.
+    /// 
+    /// Every entry is written with this version, and entries with another version (or without any, i.e., written by older versions of this crate) are treated as if they don't exist. As such, bumping the version effectively invalidates all existing entries. By default, `FORMAT_VERSION` is used.
+    /// 
+    /// # Arguments
+    /// - `version`: The version of the format of the entries.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the directory that the paths of files are stored relative to.
+    /// 
+    /// Paths of files in this directory are stored relative to it, such that the cache remains valid if the directory is moved or shared (e.g., between checkouts; see `Cache::export()`). Paths of files outside of it are stored as absolute paths. By default, this is the current working directory at the time the Cache is created.
+    /// 
+    /// # Arguments
+    /// - `root`: The directory to store paths relative to.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
+    #[inline]
+    pub fn with_root(mut self, root: impl AsRef<Path>) -> Self {
+        self.root = Some(Self::resolve(root));
+        self
+    }
+
+    /// Constructor for the Cache that opens an existing cache in read-only mode.
+    /// 
+    /// This is useful for layered caches, where a shared base cache is consulted but never updated (see, for example, `File::with_caches()` in the standard library).
+    /// 
+    /// # Arguments
+    /// - `path`: The path to the existing build cache directory.
+    /// 
+    /// # Returns
+    /// A new Cache instance on which any call to `Cache::update_file()` will fail.
+    /// 
+    /// # Errors
+    /// This function errors if the path does not exist or is not a directory.
+    #[inline]
+    pub fn read_only(path: impl Into<PathBuf>) -> Result<Self, Error> { Self::open(path.into(), false, true) }
+
+    /// Constructor for the Cache that starts fresh, i.e., removes any existing entries in the cache directory.
+    /// 
+    /// This is the programmatic equivalent of removing the cache directory, which is useful when a stale cache would cause confusing behaviour (e.g., after switching branches or Rust versions). The directory itself is kept, as are any files in it that are not cache entries (see `Cache::clear()`). The cache is locked while doing so (see `Cache::lock()`).
+    /// 
+    /// # Arguments
+    /// - `path`: The path to the build cache directory that we will use / have used last time.
+    /// - `create_path`: Whether to attempt to create the directory if it does not exist (true) or just error instead (false).
+    /// 
+    /// # Returns
+    /// A new Cache instance without any entries.
+    /// 
+    /// # Errors
+    /// This function errors if any sanity checks about the path failed (see `Cache::new()`), or if we failed to lock the cache or remove the existing entries.
+    pub fn new_clean(path: impl Into<PathBuf>, create_path: bool) -> Result<Self, Error> {
+        let cache: Self = Self::new(path, create_path)?;
+        let _removed: usize = {
+            let _lock: CacheGuard = cache.lock()?;
+            cache.clear()?
+        };
+        debug!("Removed {} entries from cache '{}'", _removed, cache.path.display());
+        Ok(cache)
+    }
+
+
+
+    /// Returns whether this Cache may only be read from.
+    /// 
+    /// # Returns
+    /// 'true' if the cache was opened with `Cache::read_only()`, or 'false' otherwise.
+    #[inline]
+    pub fn is_read_only(&self) -> bool { self.read_only }
+
+    /// Returns statistics about how this cache has been used so far.
+    /// 
+    /// Lookups and writes of files and directories are counted (e.g., by `Cache::get_file()` and `Cache::update_file()`), such that all effects that use them are included automatically. Clones of a Cache share the same statistics.
+    /// 
+    /// # Returns
+    /// A CacheStats with the number of hits, misses and writes.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits   : self.counters.hits.load(Ordering::Relaxed),
+            misses : self.counters.misses.load(Ordering::Relaxed),
+            writes : self.counters.writes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Locks the cache as a whole, blocking until any other process (or thread) holding the lock releases it.
+    /// 
+    /// This uses an advisory lock on a lock file in the cache directory (see `LOCK_FILE`), such that multiple installers sharing the same cache directory (e.g., CI jobs) do not race on its entries. It is typically held while committing changes (see, for example, `File` in the standard library). Note that it is not reentrant, i.e., locking a cache that is already locked by the same thread deadlocks.
+    /// 
+    /// # Returns
+    /// A CacheGuard that releases the lock once dropped.
+    /// 
+    /// If the cache has no lock file (e.g., because it could not be opened, or because it is not stored on disk; see `Cache::with_backend()`), it is only locked between threads of this process.
+    /// 
+    /// # Errors
+    /// This function errors if we could not lock the lock file.
+    pub fn lock(&self) -> Result<CacheGuard<'_>, Error> {
+        let guard: MutexGuard<()> = self.lock.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(file) = &self.lock_file {
+            debug!("Locking cache '{}'", self.path.display());
+            if let Err(err) = file.lock() { return Err(Error::CacheLockError{ path: self.path.join(LOCK_FILE), err }); }
+        }
+        Ok(CacheGuard{ file: self.lock_file.clone(), _guard: guard })
+    }
+
+    /// Returns the version of the format of the entries in this cache (see `Cache::with_version()`).
+    #[inline]
+    pub fn version(&self) -> u32 { self.version }
+
+
+
+    /// A bit of an odd function that hashes a given source identifier to a cache identifier.
+    /// 
+    /// # Arguments
+    /// - `source`: The source identifier (i.e., path, Docker image name, ...) to convert into a proper cache ID.
+    /// 
+    /// # Returns
+    /// The hash of the path, as a raw u64 number.
+    pub fn hash(source: impl Hash) -> u64 {
+        let mut hasher: DefaultHasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Computes the SHA-256 hash of the contents of the given file.
+    /// 
+    /// The file is streamed through the hasher, so this is also suitable for large binaries.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the file to hash.
+    /// 
+    /// # Returns
+    /// The hash as a lowercase hexadecimal string.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to open or read the file.
+    pub fn hash_file(path: impl AsRef<Path>) -> Result<String, Error> {
+        let path: &Path = path.as_ref();
+        let mut handle: File = match File::open(path) {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::FileHashError{ path: path.into(), err }); },
+        };
+
+        // Stream it through the hasher
+        let mut hasher: Sha256 = Sha256::new();
+        let mut buf: Vec<u8> = vec![ 0; 64 * 1024 ];
+        loop {
+            match handle.read(&mut buf) {
+                Ok(0)    => { break; },
+                Ok(n)    => { hasher.update(&buf[..n]); },
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => { continue; },
+                Err(err) => { return Err(Error::FileHashError{ path: path.into(), err }); },
+            }
+        }
+        Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+    }
+
+    /// Normalizes the given path lexically, i.e., by removing `.` components and resolving `..` components against the ones before it.
+    /// 
+    /// This does not touch the filesystem, so it also works for files that don't exist yet. Note that this means that `..` after a symlink is not resolved like the OS would. See `Cache::key()` for how paths are actually identified in the cache.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to normalize.
+    /// 
+    /// # Returns
+    /// The normalized path (e.g., `target/debug/app` for `./target/release/../debug/app`).
+    pub fn normalize(path: impl AsRef<Path>) -> PathBuf {
+        let mut res: PathBuf = PathBuf::new();
+        for comp in path.as_ref().components() {
+            match comp {
+                Component::CurDir    => {},
+                Component::ParentDir => match res.components().next_back() {
+                    Some(Component::Normal(_))                      => { res.pop(); },
+                    Some(Component::RootDir | Component::Prefix(_)) => {},
+                    _                                               => { res.push(".."); },
+                },
+                comp                 => { res.push(comp); },
+            }
+        }
+        if res.as_os_str().is_empty() { res.push("."); }
+        res
+    }
+
+
+
+    /// Returns the lock of the cache entry with the given ID, such that only one thread reads or writes it at a time.
+    /// 
+    /// # Arguments
+    /// - `hash`: The identifier of the cache entry (see `Cache::hash()`).
+    /// 
+    /// # Returns
+    /// The lock of the entry, which should be held while accessing it.
+    fn entry_lock(&self, hash: u64) -> Arc<Mutex<()>> {
+        // A panic while holding the lock does not leave the map in an invalid state, so we may ignore poisoning
+        let mut locks: MutexGuard<HashMap<u64, Arc<Mutex<()>>>> = self.locks.lock().unwrap_or_else(|err| err.into_inner());
+        locks.entry(hash).or_default().clone()
+    }
+
+    /// Returns the locks that serialize access to the cache entries with the given IDs (see `Cache::entry_lock()`).
+    /// 
+    /// The locks are returned in a fixed order (and without duplicates), such that locking them in-order cannot deadlock with other batch operations.
+    /// 
+    /// # Arguments
+    /// - `hashes`: The identifiers of the cache entries.
+    /// 
+    /// # Returns
+    /// The locks of the given entries.
+    fn entry_locks(&self, hashes: impl IntoIterator<Item = u64>) -> Vec<Arc<Mutex<()>>> {
+        let mut hashes: Vec<u64> = hashes.into_iter().collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        hashes.into_iter().map(|hash| self.entry_lock(hash)).collect()
+    }
+
+    /// Resolves the given path to an absolute, canonical path, i.e., relative to the current working directory and with all symlinks resolved.
+    /// 
+    /// Since canonicalization only works for existing paths, the longest existing ancestor of a path that doesn't exist (yet) is canonicalized and the rest is appended lexically (see `Cache::normalize()`).
+    /// 
+    /// # Arguments
+    /// - `path`: The path to resolve.
+    /// 
+    /// # Returns
+    /// The resolved path. If we could not even find the current working directory, the path is only normalized.
+    fn resolve(path: impl AsRef<Path>) -> PathBuf {
+        let path: &Path = path.as_ref();
+        let path: PathBuf = if path.is_absolute() {
+            path.into()
+        } else {
+            match std::env::current_dir() {
+                Ok(cwd) => cwd.join(path),
+                Err(_)  => { return Self::normalize(path); },
+            }
+        };
+        if let Ok(path) = path.canonicalize() { return path; }
+
+        // Find the longest part that does exist
+        let path: PathBuf = Self::normalize(path);
+        let mut ancestor: &Path = &path;
+        while let Some(parent) = ancestor.parent() {
+            ancestor = parent;
+            if let Ok(canonical) = ancestor.canonicalize() {
+                return canonical.join(path.strip_prefix(ancestor).unwrap_or(&path));
+            }
+        }
+        path
+    }
+
+    /// Returns the path by which the given file is identified in the cache, such that different spellings of the same file (e.g., relative and absolute, or through a symlink) result in the same cache entry.
+    /// 
+    /// The path is first made absolute and canonicalized as far as it exists, and then made relative to the root of the cache if it is in it (see `Cache::with_root()`).
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the file to identify.
+    /// 
+    /// # Returns
+    /// The path that identifies the file (e.g., `target/debug/app` for both `./target/release/../debug/app` and `/path/to/root/target/debug/app`).
+    pub fn key(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path: PathBuf = Self::resolve(path);
+        match self.root.as_ref().and_then(|root| path.strip_prefix(root).ok()) {
+            Some(rel) => Self::normalize(rel),
+            None      => path,
+        }
+    }
+
+
+
+    /// Reads the cache entry with the given ID from the backend.
+    /// 
+    /// # Arguments
+    /// - `hash`: The identifier of the cache entry (see `Cache::hash()`).
+    /// 
+    /// # Returns
+    /// The deserialized entry if we were able to find one with the version of this cache. Otherwise, returns `None`.
+    /// 
+    /// # Errors
+    /// This function errors if the make cache was ill-formed or if we encounter disk IO errors.
+    fn read_entry<T: DeserializeOwned>(&self, hash: u64) -> Result<Option<T>, Error> {
+        // Attempt to find the entry
+        let value: serde_json::Value = {
+            let lock: Arc<Mutex<()>> = self.entry_lock(hash);
+            let _guard: MutexGuard<()> = lock.lock().unwrap_or_else(|err| err.into_inner());
+            match self.backend.get(hash)? {
+                Some(value) => value,
+                None        => { return Ok(None); },
+            }
+        };
+        self.decode_entry(hash, value)
+    }
+
+    /// Deserializes the given cache entry as read from the backend.
+    /// 
+    /// # Arguments
+    /// - `hash`: The identifier of the cache entry (see `Cache::hash()`).
+    /// - `value`: The entry as read from the backend.
+    /// 
+    /// # Returns
+    /// The deserialized entry if it has the version of this cache. Otherwise, returns `None`.
+    /// 
+    /// # Errors
+    /// This function errors if the entry was ill-formed.
+    fn decode_entry<T: DeserializeOwned>(&self, hash: u64, value: serde_json::Value) -> Result<Option<T>, Error> {
+        let file_path: PathBuf = self.backend.location(hash);
+
+        // Entries of other versions are treated as a miss, since we cannot trust their format
+        let version: Option<u64> = value.get("version").and_then(|version| version.as_u64());
+        if version != Some(self.version as u64) {
+            debug!("Treating cache entry '{}' of version {} as a cache miss (expected version {})", file_path.display(), version.map(|v| v.to_string()).unwrap_or_else(|| "<none>".into()), self.version);
+            return Ok(None);
+        }
+        match serde_json::from_value(value) {
+            Ok(entry) => Ok(Some(entry)),
+            Err(err)  => Err(Error::CacheEntryParseError{ path: file_path, err }),
+        }
+    }
+
+    /// Removes the cache entry with the given ID from the backend, if any.
+    /// 
+    /// # Arguments
+    /// - `hash`: The identifier of the cache entry (see `Cache::hash()`).
+    /// 
+    /// # Returns
+    /// Whether there was an entry to remove.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to remove the entry.
+    fn remove_entry(&self, hash: u64) -> Result<bool, Error> {
+        let lock: Arc<Mutex<()>> = self.entry_lock(hash);
+        let _guard: MutexGuard<()> = lock.lock().unwrap_or_else(|err| err.into_inner());
+        self.backend.remove(hash)
+    }
+
+    /// Turns errors that indicate a corrupt cache entry into a cache miss, such that the entry is simply rebuild. Other errors (i.e., true IO errors) are propagated as-is.
+    /// 
+    /// # Arguments
+    /// - `res`: The result of reading a cache entry.
+    /// 
+    /// # Returns
+    /// The same result, except that any corrupt entry is returned as `None`.
+    /// 
+    /// # Errors
+    /// This function errors if the given result was an error that does not indicate corruption.
+    fn lenient<T>(res: Result<Option<T>, Error>) -> Result<Option<T>, Error> {
+        match res {
+            Ok(entry) => Ok(entry),
+            Err(Error::CacheEntryNotAFile{ path: _path }) | Err(Error::CacheEntryParseError{ path: _path, .. }) => {
+                debug!("Treating corrupt cache entry '{}' as a cache miss", _path.display());
+                Ok(None)
+            },
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Tallies the result of looking up a file (or directory) entry in the statistics of this cache.
+    /// 
+    /// # Arguments
+    /// - `res`: The result of the lookup. Errors are not counted.
+    /// 
+    /// # Returns
+    /// The same result, for chaining purposes.
+    fn count_read<T>(&self, res: Result<Option<T>, Error>) -> Result<Option<T>, Error> {
+        match &res {
+            Ok(Some(_)) => { self.counters.hits.fetch_add(1, Ordering::Relaxed); },
+            Ok(None)    => { self.counters.misses.fetch_add(1, Ordering::Relaxed); },
+            Err(_)      => {},
+        }
+        res
+    }
+
+    /// Tallies the result of writing file (or directory) entries in the statistics of this cache.
+    /// 
+    /// # Arguments
+    /// - `res`: The result of the write. Errors and dry runs are not counted.
+    /// - `count`: The number of entries written.
+    /// - `dry_run`: Whether the write was a dry run.
+    /// 
+    /// # Returns
+    /// The same result, for chaining purposes.
+    fn count_writes(&self, res: Result<(), Error>, count: usize, dry_run: bool) -> Result<(), Error> {
+        if res.is_ok() && !dry_run { self.counters.writes.fetch_add(count, Ordering::Relaxed); }
+        res
+    }
+
+    /// Writes the cache entry with the given ID to the backend, tagged with the version of this cache.
+    /// 
+    /// For the default backend, this is atomic (see `FsBackend`), such that an interrupted write never leaves a corrupt entry behind.
+    /// 
+    /// # Arguments
+    /// - `hash`: The identifier of the cache entry (see `Cache::hash()`).
+    /// - `info`: The entry to write.
+    /// - `dry_run`: If true, does not actually update the file physically but rather just prints it would.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to update the cache entry. This is typically due to IO errors, or because the cache is read-only.
+    fn write_entry<T: Serialize>(&self, hash: u64, info: &T, dry_run: bool) -> Result<(), Error> {
+        if self.read_only { return Err(Error::CacheReadOnly{ path: self.path.clone() }); }
+        let value: serde_json::Value = self.encode_entry(hash, info)?;
+
+        // Attempt to write the cache entry
+        if !dry_run {
+            let lock: Arc<Mutex<()>> = self.entry_lock(hash);
+            let _guard: MutexGuard<()> = lock.lock().unwrap_or_else(|err| err.into_inner());
+            self.backend.put(hash, &value)
+        } else {
+            println!("[dry_run] File '{}' would be updated of change", self.backend.location(hash).display());
+            Ok(())
+        }
+    }
+
+    /// Serializes the given cache entry for writing it to the backend, tagged with the version of this cache.
+    /// 
+    /// # Arguments
+    /// - `hash`: The identifier of the cache entry (see `Cache::hash()`).
+    /// - `info`: The entry to serialize.
+    /// 
+    /// # Returns
+    /// The entry as it should be written to the backend.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to serialize the entry.
+    fn encode_entry<T: Serialize>(&self, hash: u64, info: &T) -> Result<serde_json::Value, Error> {
+        let mut value: serde_json::Value = match serde_json::to_value(info) {
+            Ok(value) => value,
+            Err(err)  => { return Err(Error::CacheEntryWriteError{ path: self.backend.location(hash), err }); },
+        };
+        if let Some(fields) = value.as_object_mut() { fields.insert("version".into(), self.version.into()); }
+        Ok(value)
+    }
+
+
+
+    /// Returns the cache entry for the given file if there is any.
+    /// 
+    /// # Arguments
+    /// - `file`: The file to cache. Note that its path (see `Cache::key()`) acts as a unique identifier.
+    /// 
+    /// # Returns
+    /// The CacheEntry if we were able to find one. Otherwise, returns `None`.
+    /// 
+    /// # Errors
+    /// This function errors if the make cache was ill-formed or if we encounter disk IO errors.
+    pub fn get_file(&self, file: impl AsRef<Path>) -> Result<Option<CacheEntry>, Error> {
+        let file: PathBuf = self.key(file);
+
+        // Hash the filename to use as identifier
+        let hash: u64 = Self::hash(&file);
+        debug!("get_file(): File '{}' ID: {}", file.display(), hash);
+
+        // Read the entry
+        self.count_read(self.read_entry(hash))
+    }
+
+    /// Returns the cache entry for the given file if there is any, treating corrupt entries as if there is none.
+    /// 
+    /// This is the version typically used by effects, since a corrupt entry should simply result in a rebuild instead of aborting it.
+    /// 
+    /// # Arguments
+    /// - `file`: The file to cache. Note that its path (see `Cache::key()`) acts as a unique identifier.
+    /// 
+    /// # Returns
+    /// The CacheEntry if we were able to find a valid one. Otherwise, returns `None`.
+    /// 
+    /// # Errors
+    /// This function errors if we encounter disk IO errors.
+    #[inline]
+    pub fn get_file_lenient(&self, file: impl AsRef<Path>) -> Result<Option<CacheEntry>, Error> {
+        Self::lenient(self.get_file(file))
+    }
+
+    /// Updates the cache entry for a given file if there is any.
+    /// 
+    /// # Arguments
+    /// - `file`: The file to update the cache for. Note that its path (see `Cache::key()`) acts as a unique identifier.
+    /// - `info`: The CacheEntry with the info to update the file to.
+    /// - `dry_run`: If true, does not actually update the file physically but rather just prints it would.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to update the cache entry. This is typically due to IO errors, or because the cache is read-only.
+    pub fn update_file(&self, file: impl AsRef<Path>, info: impl AsRef<CacheEntry>, dry_run: bool) -> Result<(), Error> {
+        let file : PathBuf     = self.key(file);
+        let info : &CacheEntry = info.as_ref();
+
+        // Hash the filename to use as identifier
+        let hash: u64 = Self::hash(&file);
+        debug!("update_file(): File '{}' ID: {}", file.display(), hash);
+
+        // Write the entry, together with the path such that it can be listed later
+        self.count_writes(self.write_entry(hash, &StoredCacheEntry{ path: Some(file), entry: info.clone() }, dry_run), 1, dry_run)
+    }
+
+    /// Returns the cache entries for the given files at once.
+    /// 
+    /// This is equivalent to calling `Cache::get_file()` for every file, but amortizes the cost of looking them up (e.g., the default backend lists the cache directory only once and reads the entries in parallel). As such, prefer this when checking many files at once.
+    /// 
+    /// # Arguments
+    /// - `files`: The files to find the cache entries of. Note that their paths (see `Cache::key()`) act as unique identifiers.
+    /// 
+    /// # Returns
+    /// The CacheEntry for every file if we were able to find one, or `None` otherwise. These are in the same order as the given files.
+    /// 
+    /// # Errors
+    /// This function errors if any of the entries was ill-formed or if we encounter disk IO errors.
+    pub fn get_files(&self, files: &[PathBuf]) -> Result<Vec<Option<CacheEntry>>, Error> {
+        let hashes: Vec<u64> = files.iter().map(|file| Self::hash(self.key(file))).collect();
+        debug!("get_files(): Reading {} entries", hashes.len());
+
+        // Read all entries while we have them locked
+        let values: Vec<Option<serde_json::Value>> = {
+            let locks: Vec<Arc<Mutex<()>>> = self.entry_locks(hashes.iter().copied());
+            let _guards: Vec<MutexGuard<()>> = locks.iter().map(|lock| lock.lock().unwrap_or_else(|err| err.into_inner())).collect();
+            self.backend.get_many(&hashes)?
+        };
+
+        // Decode them
+        let mut res: Vec<Option<CacheEntry>> = Vec::with_capacity(values.len());
+        for (hash, value) in hashes.into_iter().zip(values) {
+            res.push(match value {
+                Some(value) => self.count_read(self.decode_entry(hash, value))?,
+                None        => self.count_read(Ok(None))?,
+            });
+        }
+        Ok(res)
+    }
+
+    /// Updates the cache entries for the given files at once.
+    /// 
+    /// This is equivalent to calling `Cache::update_file()` for every file, but amortizes the cost of writing them (e.g., the default backend writes the entries in parallel). If a file occurs multiple times, the last entry for it is kept.
+    /// 
+    /// # Arguments
+    /// - `files`: The files to update the cache for, together with the CacheEntry with the info to update them to. Note that their paths (see `Cache::key()`) act as unique identifiers.
+    /// - `dry_run`: If true, does not actually update the files physically but rather just prints it would.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to update any of the cache entries. This is typically due to IO errors, or because the cache is read-only.
+    pub fn update_files(&self, files: &[(PathBuf, CacheEntry)], dry_run: bool) -> Result<(), Error> {
+        if self.read_only { return Err(Error::CacheReadOnly{ path: self.path.clone() }); }
+
+        // Prepare the entries, keeping only the last one of every file
+        let mut entries : Vec<(u64, serde_json::Value)> = Vec::with_capacity(files.len());
+        let mut seen    : HashSet<u64>                  = HashSet::with_capacity(files.len());
+        for (file, info) in files.iter().rev() {
+            let file: PathBuf = self.key(file);
+            let hash: u64 = Self::hash(&file);
+            if !seen.insert(hash) { continue; }
+            entries.push((hash, self.encode_entry(hash, &StoredCacheEntry{ path: Some(file), entry: info.clone() })?));
+        }
+        entries.reverse();
+        debug!("update_files(): Writing {} entries", entries.len());
+
+        // Write them while we have them locked
+        if !dry_run {
+            let locks: Vec<Arc<Mutex<()>>> = self.entry_locks(entries.iter().map(|(hash, _)| *hash));
+            let _guards: Vec<MutexGuard<()>> = locks.iter().map(|lock| lock.lock().unwrap_or_else(|err| err.into_inner())).collect();
+            self.count_writes(self.backend.put_many(&entries), entries.len(), dry_run)
+        } else {
+            for (hash, _) in &entries { println!("[dry_run] File '{}' would be updated of change", self.backend.location(*hash).display()); }
+            Ok(())
+        }
+    }
+
+    /// Returns the cache entry for the given directory if there is any.
+    /// 
+    /// Directories are identified like files, so they share the same entry (i.e., a directory entry is also a valid file entry, but not vice versa).
+    /// 
+    /// # Arguments
+    /// - `dir`: The directory to get the entry of. Note that its path (see `Cache::key()`) acts as a unique identifier.
+    /// 
+    /// # Returns
+    /// The DirectoryEntry if we were able to find one. Otherwise, returns `None`.
+    /// 
+    /// # Errors
+    /// This function errors if the make cache was ill-formed (including if the entry was written for a file instead) or if we encounter disk IO errors.
+    pub fn get_directory(&self, dir: impl AsRef<Path>) -> Result<Option<DirectoryEntry>, Error> {
+        let dir: PathBuf = self.key(dir);
+
+        // Hash the path to use as identifier
+        let hash: u64 = Self::hash(&dir);
+        debug!("get_directory(): Directory '{}' ID: {}", dir.display(), hash);
+
+        // Read the entry
+        self.count_read(self.read_entry(hash))
+    }
+
+    /// Returns the cache entry for the given directory if there is any, treating corrupt entries (or entries written for a file) as if there is none.
+    /// 
+    /// # Arguments
+    /// - `dir`: The directory to get the entry of. Note that its path (see `Cache::key()`) acts as a unique identifier.
+    /// 
+    /// # Returns
+    /// The DirectoryEntry if we were able to find a valid one. Otherwise, returns `None`.
+    /// 
+    /// # Errors
+    /// This function errors if we encounter disk IO errors.
+    #[inline]
+    pub fn get_directory_lenient(&self, dir: impl AsRef<Path>) -> Result<Option<DirectoryEntry>, Error> {
+        Self::lenient(self.get_directory(dir))
+    }
+
+    /// Updates the cache entry for a given directory.
+    /// 
+    /// # Arguments
+    /// - `dir`: The directory to update the cache for. Note that its path (see `Cache::key()`) acts as a unique identifier.
+    /// - `info`: The DirectoryEntry with the info to update the directory to.
+    /// - `dry_run`: If true, does not actually update the file physically but rather just prints it would.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to update the cache entry. This is typically due to IO errors, or because the cache is read-only.
+    pub fn update_directory(&self, dir: impl AsRef<Path>, info: &DirectoryEntry, dry_run: bool) -> Result<(), Error> {
+        let dir: PathBuf = self.key(dir);
+
+        // Hash the path to use as identifier
+        let hash: u64 = Self::hash(&dir);
+        debug!("update_directory(): Directory '{}' ID: {}", dir.display(), hash);
+
+        // Write the entry, together with the path such that it can be listed later
+        self.count_writes(self.write_entry(hash, &StoredCacheEntry{ path: Some(dir), entry: info.clone() }, dry_run), 1, dry_run)
+    }
+
+    /// Returns the content hash stored for the given file if there is any.
+    /// 
+    /// Corrupt entries are treated as if there is none (see `Cache::get_file_lenient()`).
+    /// 
+    /// # Arguments
+    /// - `file`: The file to get the hash of. Note that its path (see `Cache::key()`) acts as a unique identifier.
+    /// 
+    /// # Returns
+    /// The SHA-256 hash of the file's contents when it was last updated with `Cache::update_file_hash()`, or `None` if there is no such entry.
+    /// 
+    /// # Errors
+    /// This function errors if we encounter disk IO errors.
+    #[inline]
+    pub fn get_file_hash(&self, file: impl AsRef<Path>) -> Result<Option<String>, Error> {
+        Ok(self.get_file_lenient(file)?.and_then(|entry| entry.hash))
+    }
+
+    /// Updates the cache entry for a given file like `Cache::update_file()`, but also stores the hash of its current contents.
+    /// 
+    /// # Arguments
+    /// - `file`: The file to update the cache for. Note that its path (see `Cache::key()`) acts as a unique identifier.
+    /// - `info`: The CacheEntry with the info to update the file to. Its `hash` is overwritten.
+    /// - `dry_run`: If true, does not actually update the file physically but rather just prints it would.
+    /// 
+    /// # Returns
+    /// The SHA-256 hash of the file's contents (see `Cache::hash_file()`).
+    /// 
+    /// # Errors
+    /// This function errors if we failed to hash the file or to update the cache entry.
+    pub fn update_file_hash(&self, file: impl AsRef<Path>, info: impl AsRef<CacheEntry>, dry_run: bool) -> Result<String, Error> {
+        let file: &Path = file.as_ref();
+        let hash: String = Self::hash_file(file)?;
+        self.update_file(file, CacheEntry{ hash: Some(hash.clone()), ..info.as_ref().clone() }, dry_run)?;
+        Ok(hash)
+    }
+
+
+
+    /// Returns the cached fingerprint for the given identifier if there is any.
+    /// 
+    /// # Arguments
+    /// - `id`: The identifier of the thing that is fingerprinted (e.g., a target name).
+    /// 
+    /// # Returns
+    /// The fingerprint if we were able to find one. Otherwise, returns `None`.
+    /// 
+    /// # Errors
+    /// This function errors if the make cache was ill-formed or if we encounter disk IO errors.
+    pub fn get_fingerprint(&self, id: impl AsRef<str>) -> Result<Option<u64>, Error> {
+        let id: &str = id.as_ref();
+
+        // Hash the identifier (separated from paths) to use as identifier
+        let hash: u64 = Self::hash(("fingerprint", id));
+        debug!("get_fingerprint(): Fingerprint '{}' ID: {}", id, hash);
+
+        // Read the entry
+        Ok(self.read_entry::<FingerprintEntry>(hash)?.map(|e| e.fingerprint))
+    }
+
+    /// Returns the cached fingerprint for the given identifier if there is any, treating corrupt entries as if there is none.
+    /// 
+    /// This is the version typically used by effects, since a corrupt entry should simply result in a rebuild instead of aborting it.
+    /// 
+    /// # Arguments
+    /// - `id`: The identifier of the thing that is fingerprinted (e.g., a target name).
+    /// 
+    /// # Returns
+    /// The fingerprint if we were able to find a valid one. Otherwise, returns `None`.
+    /// 
+    /// # Errors
+    /// This function errors if we encounter disk IO errors.
+    #[inline]
+    pub fn get_fingerprint_lenient(&self, id: impl AsRef<str>) -> Result<Option<u64>, Error> {
+        Self::lenient(self.get_fingerprint(id))
+    }
+
+    /// Updates the cached fingerprint for the given identifier.
+    /// 
+    /// # Arguments
+    /// - `id`: The identifier of the thing that is fingerprinted (e.g., a target name).
+    /// - `fingerprint`: The new fingerprint to store.
+    /// - `dry_run`: If true, does not actually update the file physically but rather just prints it would.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to update the cache entry. This is typically due to IO errors, or because the cache is read-only.
+    pub fn update_fingerprint(&self, id: impl AsRef<str>, fingerprint: u64, dry_run: bool) -> Result<(), Error> {
+        let id: &str = id.as_ref();
+
+        // Hash the identifier (separated from paths) to use as identifier
+        let hash: u64 = Self::hash(("fingerprint", id));
+        debug!("update_fingerprint(): Fingerprint '{}' ID: {}", id, hash);
+
+        // Write the entry
+        self.write_entry(hash, &FingerprintEntry{ fingerprint }, dry_run)
+    }
+
+
+
+    /// Returns the cached information about the download from the given URL if there is any.
+    /// 
+    /// Corrupt entries are treated as if there is none.
+    /// 
+    /// # Arguments
+    /// - `url`: The URL of the downloaded file.
+    /// 
+    /// # Returns
+    /// The DownloadEntry if we were able to find a valid one. Otherwise, returns `None`.
+    /// 
+    /// # Errors
+    /// This function errors if we encounter disk IO errors.
+    pub fn get_download(&self, url: impl AsRef<str>) -> Result<Option<DownloadEntry>, Error> {
+        let url: &str = url.as_ref();
+
+        // Hash the URL (separated from paths) to use as identifier
+        let hash: u64 = Self::hash(("download", url));
+        debug!("get_download(): URL '{}' ID: {}", url, hash);
+
+        // Read the entry
+        Self::lenient(self.read_entry(hash))
+    }
+
+    /// Updates the cached information about the download from the given URL.
+    /// 
+    /// # Arguments
+    /// - `url`: The URL of the downloaded file.
+    /// - `info`: The DownloadEntry with the info to update the download to.
+    /// - `dry_run`: If true, does not actually update the file physically but rather just prints it would.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to update the cache entry. This is typically due to IO errors, or because the cache is read-only.
+    pub fn update_download(&self, url: impl AsRef<str>, info: &DownloadEntry, dry_run: bool) -> Result<(), Error> {
+        let url: &str = url.as_ref();
+
+        // Hash the URL (separated from paths) to use as identifier
+        let hash: u64 = Self::hash(("download", url));
+        debug!("update_download(): URL '{}' ID: {}", url, hash);
+
+        // Write the entry
+        self.write_entry(hash, info, dry_run)
+    }
+
+
+
+    /// Lists all files tracked in the cache, together with their entries.
+    /// 
+    /// Entries that cannot be read, and entries written by older versions (that did not store the tracked path), are skipped with a warning. Other kinds of entries (i.e., fingerprints and downloads) are not listed.
+    /// 
+    /// # Returns
+    /// A list of the tracked paths and their CacheEntries, sorted by path.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read the cache directory itself.
+    pub fn list(&self) -> Result<Vec<(PathBuf, CacheEntry)>, Error> {
+        // Go through all entries in the cache
+        let mut res: Vec<(PathBuf, CacheEntry)> = vec![];
+        for hash in self.backend.ids()? {
+            // Read it as a generic value first, to filter out other kinds of entries
+            let value: serde_json::Value = match self.read_entry(hash) {
+                Ok(Some(value)) => value,
+                Ok(None)        => { continue; },
+                Err(_err)       => {
+                    warn!("Skipping unreadable cache entry: {}", _err);
+                    continue;
+                },
+            };
+            if value.get("last_edited").is_none() { continue; }
+            match serde_json::from_value::<StoredCacheEntry>(value) {
+                Ok(StoredCacheEntry{ path: Some(path), entry }) => { res.push((path, entry)); },
+                Ok(StoredCacheEntry{ path: None, .. })          => { warn!("Skipping cache entry '{}' because it does not store its path (written by an older version?)", self.backend.location(hash).display()); },
+                Err(_err)                                       => { warn!("Skipping unreadable cache entry '{}': {}", self.backend.location(hash).display(), _err); },
+            }
+        }
+
+        // Done
+        res.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+        Ok(res)
+    }
+
+    /// Exports all entries in the cache to a single JSON blob, such that it can be restored elsewhere with `Cache::import()` (e.g., to share the cache between CI runs).
+    /// 
+    /// The blob is versioned (see `EXPORT_VERSION`), such that incompatible blobs are refused instead of misinterpreted. Entries that cannot be read are skipped with a warning.
+    /// 
+    /// # Arguments
+    /// - `to`: The writer to write the blob to.
+    /// 
+    /// # Returns
+    /// The number of entries exported.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read the cache directory or write the blob.
+    pub fn export(&self, to: impl Write) -> Result<usize, Error> {
+        // Collect all entries in the cache
+        let mut export: ExportedCache = ExportedCache{ version: EXPORT_VERSION, entries: HashMap::new() };
+        for hash in self.backend.ids()? {
+            match self.read_entry(hash) {
+                Ok(Some(value)) => { export.entries.insert(hash, value); },
+                Ok(None)        => {},
+                Err(_err)       => { warn!("Not exporting unreadable cache entry: {}", _err); },
+            }
+        }
+
+        // Write it
+        debug!("Exporting {} entries from cache '{}'", export.entries.len(), self.path.display());
+        match serde_json::to_writer(to, &export) {
+            Ok(_)    => Ok(export.entries.len()),
+            Err(err) => Err(Error::CacheExportError{ path: self.path.clone(), err }),
+        }
+    }
+
+    /// Imports the entries in a blob written by `Cache::export()` into this cache.
+    /// 
+    /// Entries already in the cache are overwritten if they are also in the blob, but kept otherwise.
+    /// 
+    /// # Arguments
+    /// - `from`: The reader to read the blob from.
+    /// 
+    /// # Returns
+    /// The number of entries imported.
+    /// 
+    /// # Errors
+    /// This function errors if the cache is read-only, if we failed to read or parse the blob, if it has an unsupported version or if we failed to write any of the entries.
+    pub fn import(&self, from: impl Read) -> Result<usize, Error> {
+        if self.read_only { return Err(Error::CacheReadOnly{ path: self.path.clone() }); }
+
+        // Read the blob
+        let import: ExportedCache = match serde_json::from_reader(from) {
+            Ok(import) => import,
+            Err(err)   => { return Err(Error::CacheImportParseError{ path: self.path.clone(), err }); },
+        };
+        if import.version != EXPORT_VERSION { return Err(Error::CacheImportVersionError{ path: self.path.clone(), got: import.version, expected: EXPORT_VERSION }); }
+
+        // Write all entries
+        debug!("Importing {} entries into cache '{}'", import.entries.len(), self.path.display());
+        for (hash, value) in &import.entries {
+            self.write_entry(*hash, value, false)?;
+        }
+        Ok(import.entries.len())
+    }
+
+    /// Garbage-collects the cache by removing the entries of files (or directories) that no longer exist on disk.
+    /// 
+    /// Unlike `Cache::gc()`, this does not need to know which files are still tracked. Every file entry stores the path it tracks (see `Cache::key()`), which is resolved against the root of the cache to see if it still exists. Entries that do not store their path (i.e., written by older versions) and other kinds of entries (i.e., fingerprints and downloads) are left untouched.
+    /// 
+    /// The cache is locked while collecting (see `Cache::lock()`), such that entries committed concurrently by other installers are not lost. As such, do not call this while already holding the lock.
+    /// 
+    /// # Returns
+    /// The number of entries that were removed.
+    /// 
+    /// # Errors
+    /// This function errors if the cache is read-only, or if we failed to lock the cache, read its entries or remove any of them.
+    pub fn gc_missing(&self) -> Result<usize, Error> {
+        if self.read_only { return Err(Error::CacheReadOnly{ path: self.path.clone() }); }
+        let _lock: CacheGuard = self.lock()?;
+
+        // Go through all entries in the cache
+        let mut removed: usize = 0;
+        for hash in self.backend.ids()? {
+            // Only consider file entries whose path is gone
+            let path: PathBuf = match Self::lenient(self.read_entry::<StoredCacheEntry>(hash))? {
+                Some(StoredCacheEntry{ path: Some(path), .. }) => path,
+                _                                              => { continue; },
+            };
+            let path: PathBuf = match &self.root {
+                Some(root) => root.join(path),
+                None       => path,
+            };
+            if path.symlink_metadata().is_ok() { continue; }
+
+            // Remove it
+            debug!("gc_missing(): Removing entry '{}' of missing file '{}'", self.backend.location(hash).display(), path.display());
+            if self.remove_entry(hash)? { removed += 1; }
+        }
+
+        // Done
+        debug!("Removed {} stale entries from cache '{}'", removed, self.path.display());
+        Ok(removed)
+    }
+
+    /// Garbage-collects the cache by removing the entries of files that are no longer tracked.
+    /// 
+    /// Since entries are identified by the hash of their path, the live set is hashed the same way as `Cache::get_file()` does and any file entry with another hash is removed. Other kinds of entries (i.e., fingerprints and downloads) are left untouched.
+    /// 
+    /// Typically, this is called after a build with the paths of all effects that are currently tracked. Like `Cache::gc_missing()`, the cache is locked while doing so.
+    /// 
+    /// # Arguments
+    /// - `live`: The paths of the files (or directories) whose entries should be kept.
+    /// 
+    /// # Returns
+    /// The number of entries that were removed.
+    /// 
+    /// # Errors
+    /// This function errors if the cache is read-only, or if we failed to lock the cache, read its entries or remove any of them.
+    pub fn gc(&self, live: &HashSet<PathBuf>) -> Result<usize, Error> {
+        if self.read_only { return Err(Error::CacheReadOnly{ path: self.path.clone() }); }
+        let _lock: CacheGuard = self.lock()?;
+        let live: HashSet<u64> = live.iter().map(|path| Self::hash(self.key(path))).collect();
+
+        // Go through all entries in the cache
+        let mut removed: usize = 0;
+        for hash in self.backend.ids()? {
+            // Only consider file entries that are not live
+            if live.contains(&hash) { continue; }
+            if Self::lenient(self.read_entry::<CacheEntry>(hash))?.is_none() { continue; }
+
+            // Remove it
+            debug!("gc(): Removing stale entry '{}'", self.backend.location(hash).display());
+            if self.remove_entry(hash)? { removed += 1; }
+        }
+
+        // Done
+        debug!("Removed {} stale entries from cache '{}'", removed, self.path.display());
+        Ok(removed)
+    }
+
+    /// Removes all entries from the cache, forcing everything that uses it to be rebuild (e.g., after the build environment changed).
+    /// 
+    /// Unlike `Cache::gc()` and `Cache::gc_missing()`, this removes every kind of entry (i.e., files, fingerprints and downloads). The cache directory itself is left intact, as are any files in it that are not cache entries.
+    /// 
+    /// # Returns
+    /// The number of entries that were removed.
+    /// 
+    /// # Errors
+    /// This function errors if the cache is read-only, or if we failed to read the cache directory or remove any entries.
+    pub fn clear(&self) -> Result<usize, Error> {
+        if self.read_only { return Err(Error::CacheReadOnly{ path: self.path.clone() }); }
+        debug!("clear(): Removing all entries from cache '{}'", self.path.display());
+        self.backend.clear()
+    }
+
+    /// Removes the cache entry for the given file if there is any, forcing it to be considered changed.
+    /// 
+    /// # Arguments
+    /// - `file`: The file to remove the entry of. Note that its path (see `Cache::key()`) acts as a unique identifier.
+    /// 
+    /// # Errors
+    /// This function errors if the cache is read-only, or if we failed to remove the entry.
+    pub fn clear_entry(&self, file: impl AsRef<Path>) -> Result<(), Error> {
+        if self.read_only { return Err(Error::CacheReadOnly{ path: self.path.clone() }); }
+        let file: PathBuf = self.key(file);
+
+        // Hash the filename to find the entry
+        let hash: u64 = Self::hash(&file);
+        debug!("clear_entry(): File '{}' ID: {}", file.display(), hash);
+
+        // Remove it if it exists
+        self.remove_entry(hash).map(|_| ())
     }
 }
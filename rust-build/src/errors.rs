@@ -21,7 +21,17 @@ use std::path::PathBuf;
 /// The toplevel error of the crate.
 #[derive(Debug)]
 pub enum BuildError {
-    Temp,
+    /// The given target is not registered in the installer.
+    UnknownTarget{ name: String },
+    /// Failed to make the given target.
+    MakeError{ name: String, err: TargetError },
+    /// The deadline passed before the given target was made.
+    Timeout{ name: String },
+
+    /// Failed to create the file to write the build report to.
+    ReportCreateError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write the build report.
+    ReportWriteError{ path: PathBuf, err: serde_json::Error },
 }
 
 impl Display for BuildError {
@@ -29,7 +39,12 @@ impl Display for BuildError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         use BuildError::*;
         match self {
-            Temp => write!(f, "TEMP"),
+            UnknownTarget{ name }  => write!(f, "Unknown target '{}'", name),
+            MakeError{ name, err } => write!(f, "Failed to make target '{}': {}", name, err),
+            Timeout{ name }        => write!(f, "Deadline passed before target '{}' was made", name),
+
+            ReportCreateError{ path, err } => write!(f, "Failed to create build report file '{}': {}", path.display(), err),
+            ReportWriteError{ path, err }  => write!(f, "Failed to write and serialize build report file '{}' as JSON: {}", path.display(), err),
         }
     }
 }
@@ -44,13 +59,15 @@ pub enum TargetError {
     /// Failed to build a dependency.
     DependencyBuildError{ name: String, err: Box<Self> },
     /// Failed to check if an effect has changed.
-    HasChangedError{ effect_name: String, err: Box<dyn Error> },
+    HasChangedError{ effect_name: String, err: Box<dyn Error + Send + Sync> },
 
     /// Failed to build the target itself.
-    BuildError{ name: String, err: Box<dyn Error> },
+    BuildError{ name: String, err: Box<dyn Error + Send + Sync> },
+    /// The target was not built because the deadline of the build passed.
+    Timeout{ name: String },
 
     /// Failed to commit a resulting effect.
-    CommitError{ effect_name: String, err: Box<dyn Error> },
+    CommitError{ effect_name: String, err: Box<dyn Error + Send + Sync> },
 }
 
 impl Display for TargetError {
@@ -61,6 +78,7 @@ impl Display for TargetError {
             HasChangedError{ effect_name, err } => write!(f, "Failed to check if effect '{}' has changed: {}", effect_name, err),
 
             BuildError{ name, err } => write!(f, "Failed to build target '{}': {}", name, err),
+            Timeout{ name }         => write!(f, "Did not build target '{}' because the deadline passed", name),
 
             CommitError{ effect_name, err } => write!(f, "Failed to commit changed of effect '{}': {}", effect_name, err),
         }
@@ -80,6 +98,12 @@ pub enum CacheError {
     CacheDirNotADir{ path: PathBuf },
     /// Failed to create a new directory.
     CacheDirCreateError{ path: PathBuf, err: std::io::Error },
+    /// Attempted to write to a read-only cache.
+    CacheReadOnly{ path: PathBuf },
+    /// Failed to read the entries in the cache directory.
+    CacheDirReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to open or lock the lock file of the cache.
+    CacheLockError{ path: PathBuf, err: std::io::Error },
 
     /// The given path existed but was not a file.
     CacheEntryNotAFile{ path: PathBuf, },
@@ -92,6 +116,20 @@ pub enum CacheError {
     CacheEntryCreateError{ path: PathBuf, err: std::io::Error },
     /// Failed to write to a cache entry file.
     CacheEntryWriteError{ path: PathBuf, err: serde_json::Error },
+    /// Failed to move a newly written cache entry file in place.
+    CacheEntryRenameError{ from: PathBuf, to: PathBuf, err: std::io::Error },
+    /// Failed to remove a cache entry file.
+    CacheEntryRemoveError{ path: PathBuf, err: std::io::Error },
+
+    /// Failed to read a file to compute the hash of its contents.
+    FileHashError{ path: PathBuf, err: std::io::Error },
+
+    /// Failed to write the exported cache.
+    CacheExportError{ path: PathBuf, err: serde_json::Error },
+    /// Failed to read and parse an exported cache.
+    CacheImportParseError{ path: PathBuf, err: serde_json::Error },
+    /// The exported cache was written with an unsupported version of the format.
+    CacheImportVersionError{ path: PathBuf, got: u32, expected: u32 },
 }
 
 impl Display for CacheError {
@@ -101,13 +139,24 @@ impl Display for CacheError {
             CacheDirNotFound{ path }         => write!(f, "Given make cache directory '{}' does not exist", path.display()),
             CacheDirNotADir{ path }          => write!(f, "Given make cache directory '{}' exists but is not a directory", path.display()),
             CacheDirCreateError{ path, err } => write!(f, "Failed to create make cache directory '{}': {}", path.display(), err),
+            CacheReadOnly{ path }            => write!(f, "Cannot update make cache '{}' because it is read-only", path.display()),
+            CacheDirReadError{ path, err }   => write!(f, "Failed to read make cache directory '{}': {}", path.display(), err),
+            CacheLockError{ path, err }      => write!(f, "Failed to lock make cache lock file '{}': {}", path.display(), err),
 
             CacheEntryNotAFile{ path }        => write!(f, "Given make cache entry '{}' exists but is not a file", path.display()),
             CacheEntryOpenError{ path, err }  => write!(f, "Failed to open cache entry file '{}': {}", path.display(), err),
             CacheEntryParseError{ path, err } => write!(f, "Failed to read and parse cache entry file '{}' as JSON: {}", path.display(), err),
 
-            CacheEntryCreateError{ path, err } => write!(f, "Failed to create cache entry file '{}': {}", path.display(), err),
-            CacheEntryWriteError{ path, err }  => write!(f, "Failed to write and serialize cache entry file '{}' as JSON: {}", path.display(), err),
+            CacheEntryCreateError{ path, err }     => write!(f, "Failed to create cache entry file '{}': {}", path.display(), err),
+            CacheEntryWriteError{ path, err }      => write!(f, "Failed to write and serialize cache entry file '{}' as JSON: {}", path.display(), err),
+            CacheEntryRenameError{ from, to, err } => write!(f, "Failed to move cache entry file '{}' to '{}': {}", from.display(), to.display(), err),
+            CacheEntryRemoveError{ path, err }     => write!(f, "Failed to remove cache entry file '{}': {}", path.display(), err),
+
+            FileHashError{ path, err } => write!(f, "Failed to read file '{}' to compute its hash: {}", path.display(), err),
+
+            CacheExportError{ path, err }                  => write!(f, "Failed to export make cache '{}' as JSON: {}", path.display(), err),
+            CacheImportParseError{ path, err }             => write!(f, "Failed to read and parse exported cache to import into make cache '{}' as JSON: {}", path.display(), err),
+            CacheImportVersionError{ path, got, expected } => write!(f, "Cannot import exported cache into make cache '{}': unsupported format version {} (expected {})", path.display(), got, expected),
         }
     }
 }
@@ -118,20 +167,90 @@ impl Error for CacheError {}
 
 /// Defines errors that relate to shell interaction.
 #[derive(Debug)]
-pub enum ShellError {
-    
+pub enum ShellCommandError {
+    /// Failed to spawn the command.
+    SpawnError{ exec: String, err: std::io::Error },
+    /// Failed to wait for the command to complete.
+    WaitError{ exec: String, err: std::io::Error },
+    /// Failed to write the input of the command.
+    InputWriteError{ exec: String, err: std::io::Error },
+    /// Failed to read the output of the command.
+    OutputReadError{ exec: String, err: std::io::Error },
+    /// The command completed, but returned a non-zero exit code.
+    NonZeroExit{ exec: String, code: i32 },
+    /// The command did not complete in time and was killed.
+    Timeout{ exec: String },
+    /// A command in a pipeline (other than the last one) failed.
+    PipelineStage{ index: usize, err: Box<Self> },
+    /// A command line to parse did not contain an executable.
+    EmptyCommandLine,
+    /// A command line to parse had a quote that was not closed.
+    UnterminatedQuote{ line: String },
+}
+
+impl Display for ShellCommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use ShellCommandError::*;
+        match self {
+            SpawnError{ exec, err }      => write!(f, "Failed to spawn command '{}': {}", exec, err),
+            WaitError{ exec, err }       => write!(f, "Failed to wait for command '{}' to complete: {}", exec, err),
+            InputWriteError{ exec, err } => write!(f, "Failed to write input of command '{}': {}", exec, err),
+            OutputReadError{ exec, err } => write!(f, "Failed to read output of command '{}': {}", exec, err),
+            NonZeroExit{ exec, code }    => write!(f, "Command '{}' returned non-zero exit code {}", exec, code),
+            Timeout{ exec }              => write!(f, "Command '{}' timed out", exec),
+            PipelineStage{ index, err }  => write!(f, "Command {} in pipeline failed: {}", index, err),
+            EmptyCommandLine             => write!(f, "Command line is empty"),
+            UnterminatedQuote{ line }    => write!(f, "Unterminated quote in command line '{}'", line),
+        }
+    }
+}
+
+impl Error for ShellCommandError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        use ShellCommandError::*;
+        match self {
+            SpawnError{ err, .. }      |
+            WaitError{ err, .. }       |
+            InputWriteError{ err, .. } |
+            OutputReadError{ err, .. } => Some(err),
+            PipelineStage{ err, .. }   => Some(err),
+
+            NonZeroExit{ .. }       |
+            Timeout{ .. }           |
+            EmptyCommandLine        |
+            UnterminatedQuote{ .. } => None,
+        }
+    }
+}
+
+
+
+/// Defines errors that relate to loading an InstallerStyle.
+#[derive(Debug)]
+pub enum StyleError {
+    /// Failed to read the given style file.
+    FileReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to parse the given style file as TOML.
+    TomlParseError{ path: PathBuf, err: toml::de::Error },
+    /// Failed to parse the given style file as JSON.
+    JsonParseError{ path: PathBuf, err: serde_json::Error },
+    /// The given colour name is not known.
+    UnknownColour{ path: PathBuf, field: String, colour: String },
 }
 
-impl Display for ShellError {
+impl Display for StyleError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
-        use ShellError::*;
+        use StyleError::*;
         match self {
-            
+            FileReadError{ path, err }           => write!(f, "Failed to read style file '{}': {}", path.display(), err),
+            TomlParseError{ path, err }          => write!(f, "Failed to parse style file '{}' as TOML: {}", path.display(), err),
+            JsonParseError{ path, err }          => write!(f, "Failed to parse style file '{}' as JSON: {}", path.display(), err),
+            UnknownColour{ path, field, colour } => write!(f, "Unknown colour '{}' for field '{}' in style file '{}' (expected one of black, red, green, yellow, blue, magenta, cyan or white)", colour, field, path.display()),
         }
     }
 }
 
-impl Error for ShellError {}
+impl Error for StyleError {}
 
 
 
@@ -143,6 +262,8 @@ pub enum LastEditedTimeError {
     PathNotFound{ path: PathBuf },
     /// Failed to read the metadata of the given path.
     PathMetadataReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to read the entries of a (nested) directory.
+    DirReadError{ path: PathBuf, err: std::io::Error },
 }
 
 impl Display for LastEditedTimeError {
@@ -151,6 +272,7 @@ impl Display for LastEditedTimeError {
         match self {
             PathNotFound{ path }               => write!(f, "Failed to read metadata of '{}': file not found", path.display()),
             PathMetadataReadError{ path, err } => write!(f, "Failed to read metadata of '{}': {}", path.display(), err),
+            DirReadError{ path, err }          => write!(f, "Failed to read directory '{}': {}", path.display(), err),
         }
     }
 }
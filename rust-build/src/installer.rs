@@ -13,25 +13,236 @@
 //!   individual installer components.
 // 
 
-use std::collections::HashMap;
-use std::rc::Rc;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "rayon")]
+use std::sync::{Condvar, MutexGuard};
+use std::time::Instant;
 
-use crate::spec::Target;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::errors::{BuildError, TargetError};
+use crate::observer::{BuildObserver, NoOpObserver};
+use crate::progress::{ProgressReporter, SilentReporter};
+use crate::report::{BuildReport, ReportRecorder};
+#[cfg(feature = "rayon")]
+use crate::shell::deadline;
+use crate::shell::set_deadline;
+use crate::spec::{Architecture, OperatingSystem, Target};
 use crate::style::InstallerStyle;
 
 
+/***** AUXILLARY *****/
+/// Wraps a BuildObserver to additionally advance a ProgressReporter whenever a target is made for the first time, and to record a report of the build.
+struct ProgressObserver<'a> {
+    /// The observer to forward all events to.
+    observer : &'a dyn BuildObserver,
+    /// The reporter to advance.
+    progress : &'a dyn ProgressReporter,
+    /// The recorder that keeps track of what happens for the build report.
+    recorder : &'a ReportRecorder,
+    /// The names of the targets that have already been made.
+    done     : Mutex<HashSet<String>>,
+}
+
+impl<'a> BuildObserver for ProgressObserver<'a> {
+    fn on_target_start(&self, name: &str) {
+        self.recorder.on_target_start(name);
+        self.observer.on_target_start(name);
+    }
+
+    fn on_target_finish(&self, name: &str, result: &Result<bool, TargetError>) {
+        self.recorder.on_target_finish(name, result);
+        self.observer.on_target_finish(name, result);
+        if result.is_ok() && self.done.lock().unwrap_or_else(|err| err.into_inner()).insert(name.into()) {
+            self.progress.advance(name);
+        }
+    }
+
+    fn on_target_warnings(&self, name: &str, count: usize) {
+        self.recorder.on_target_warnings(name, count);
+        self.observer.on_target_warnings(name, count);
+    }
+
+    fn on_effect_changed(&self, name: &str, effect: &str) {
+        self.recorder.on_effect_changed(name, effect);
+        self.observer.on_effect_changed(name, effect);
+    }
+}
+
+
+
+/// Limits how many targets of the same resource class are made at once (see `Target::resource_class()`).
+#[cfg(feature = "rayon")]
+struct Semaphore {
+    /// The number of permits that are still available.
+    permits   : Mutex<usize>,
+    /// Used to wake up threads waiting for a permit.
+    available : Condvar,
+}
+
+#[cfg(feature = "rayon")]
+impl Semaphore {
+    /// Constructor for the Semaphore.
+    /// 
+    /// # Arguments
+    /// - `permits`: The number of permits that may be held at once.
+    /// 
+    /// # Returns
+    /// A new Semaphore instance.
+    #[inline]
+    fn new(permits: usize) -> Self {
+        Self {
+            permits   : Mutex::new(permits),
+            available : Condvar::new(),
+        }
+    }
+
+    /// Blocks until a permit is available, and then takes it.
+    /// 
+    /// # Returns
+    /// A SemaphoreGuard that returns the permit when dropped.
+    fn acquire(&self) -> SemaphoreGuard<'_> {
+        let mut permits: MutexGuard<usize> = self.permits.lock().unwrap_or_else(|err| err.into_inner());
+        while *permits == 0 {
+            permits = self.available.wait(permits).unwrap_or_else(|err| err.into_inner());
+        }
+        *permits -= 1;
+        SemaphoreGuard { semaphore: self }
+    }
+}
+
+/// Holds a permit of a Semaphore, returning it when dropped.
+#[cfg(feature = "rayon")]
+struct SemaphoreGuard<'a> {
+    /// The Semaphore to return the permit to.
+    semaphore : &'a Semaphore,
+}
+
+#[cfg(feature = "rayon")]
+impl<'a> Drop for SemaphoreGuard<'a> {
+    fn drop(&mut self) {
+        *self.semaphore.permits.lock().unwrap_or_else(|err| err.into_inner()) += 1;
+        self.semaphore.available.notify_one();
+    }
+}
+
+
+
+/// Counts the number of unique targets in the dependency tree of the given target (including itself).
+/// 
+/// # Arguments
+/// - `target`: The Target to count the dependency tree of.
+/// - `seen`: The names of the targets that have already been counted.
+/// 
+/// # Returns
+/// The number of newly counted targets.
+fn count_targets<'a>(target: &'a dyn Target, seen: &mut HashSet<&'a str>) -> usize {
+    if !seen.insert(target.name()) { return 0; }
+    1 + target.deps().iter().map(|v| count_targets(v.target, seen)).sum::<usize>()
+}
+
+/// Assigns the given target and all targets in its dependency tree to the level in which they can be made (see `Installer::build_levels()`).
+/// 
+/// # Arguments
+/// - `target`: The Target to find the level of.
+/// - `levels`: The targets that have already been assigned to a level, by name.
+/// 
+/// # Returns
+/// The level of the given target.
+#[cfg(feature = "rayon")]
+fn target_level<'a>(target: &'a dyn Target, levels: &mut HashMap<&'a str, (usize, &'a dyn Target)>) -> usize {
+    if let Some((level, _)) = levels.get(target.name()) { return *level; }
+    let level: usize = target.deps().iter().map(|v| target_level(v.target, levels) + 1).max().unwrap_or(0);
+    levels.insert(target.name(), (level, target));
+    level
+}
+
+/// Makes the given target and its dependencies level by level, making the targets within a level in parallel.
+/// 
+/// Every target in the dependency tree is made exactly once, with `Target::make_ready_observed()`. Targets whose resource class has a limit (see `Target::resource_class()`) wait until fewer than that many targets of the class are being made.
+/// 
+/// # Arguments
+/// - `target`: The Target to make.
+/// - `os`: The target OS that we intend to build.
+/// - `arch`: The target architecture that we intend to build.
+/// - `force`: If 'true', always builds all targets instead of only when there is no (detected) change.
+/// - `dry_run`: If 'true', prints what would be done instead of actually executing the commands.
+/// - `observer`: The BuildObserver to notify of the events during the build.
+/// - `limits`: The maximum number of targets that may be made at once per resource class. Classes without a limit are not limited.
+/// 
+/// # Errors
+/// This function errors if we failed to make any of the targets. The level in which that happened is still completed, but no further levels are started.
+#[cfg(feature = "rayon")]
+fn make_parallel(target: &dyn Target, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool, observer: &dyn BuildObserver, limits: &HashMap<String, usize>) -> Result<(), TargetError> {
+    // Sort the tree into levels
+    let mut assigned: HashMap<&str, (usize, &dyn Target)> = HashMap::new();
+    let depth: usize = target_level(target, &mut assigned);
+    let mut levels: Vec<Vec<&dyn Target>> = vec![ vec![]; depth + 1 ];
+    for (level, target) in assigned.into_values() {
+        levels[level].push(target);
+    }
+
+    // Prepare a semaphore for every limited resource class
+    let semaphores: HashMap<&str, Semaphore> = limits.iter().map(|(class, limit)| (class.as_str(), Semaphore::new(*limit))).collect();
+
+    // Make them one level at a time, passing the deadline on to the worker threads
+    let deadline: Option<Instant> = deadline();
+    for mut level in levels {
+        level.sort_unstable_by_key(|t| t.name());
+        let results: Vec<Result<(), TargetError>> = level.par_iter().map(|target| {
+            let _permit: Option<SemaphoreGuard> = semaphores.get(target.resource_class()).map(Semaphore::acquire);
+            let old: Option<Instant> = set_deadline(deadline);
+            let res: Result<(), TargetError> = target.make_ready_observed(os, arch, force, dry_run, observer);
+            set_deadline(old);
+            res
+        }).collect();
+        if let Some(err) = results.into_iter().find_map(Result::err) { return Err(err); }
+    }
+    Ok(())
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Defines a builder for the installer.
 pub struct Builder {
     /// The list of targets that we will build the installer with.
-    targets : Vec<Box<dyn Target>>,
+    targets  : Vec<Box<dyn Target>>,
+    /// The observer that is notified of the events during builds.
+    observer : Box<dyn BuildObserver>,
+    /// The reporter that is used to show the progress of builds.
+    progress : Box<dyn ProgressReporter>,
+    /// The style of the installer.
+    style    : InstallerStyle,
+    /// The path to write a JSON report of every build to, if any.
+    report   : Option<PathBuf>,
+    /// Whether to make independent targets in parallel.
+    #[cfg(feature = "rayon")]
+    parallel : bool,
+    /// The maximum number of targets that may be made at once per resource class.
+    #[cfg(feature = "rayon")]
+    limits   : HashMap<String, usize>,
 }
 
 impl Default for Builder {
     #[inline]
     fn default() -> Self {
         Self {
-            targets : vec![],
+            targets  : vec![],
+            observer : Box::new(NoOpObserver),
+            progress : Box::new(SilentReporter),
+            style    : InstallerStyle::default(),
+            report   : None,
+            #[cfg(feature = "rayon")]
+            parallel : false,
+            #[cfg(feature = "rayon")]
+            limits   : HashMap::new(),
         }
     }
 }
@@ -52,6 +263,130 @@ impl Builder {
         self.targets.push(Box::new(target));
         self
     }
+
+    /// Sets the observer that is notified of everything that happens while building targets.
+    /// 
+    /// By default, a `NoOpObserver` is used that ignores all events.
+    /// 
+    /// # Arguments
+    /// - `observer`: The BuildObserver to notify.
+    /// 
+    /// # Returns
+    /// The same `Builder` as self, for chaining purposes.
+    #[inline]
+    pub fn observer(mut self, observer: impl 'static + BuildObserver) -> Self {
+        self.observer = Box::new(observer);
+        self
+    }
+
+    /// Sets the reporter that is used to show the progress of builds.
+    /// 
+    /// By default, a `SilentReporter` is used that shows nothing.
+    /// 
+    /// # Arguments
+    /// - `progress`: The ProgressReporter to drive.
+    /// 
+    /// # Returns
+    /// The same `Builder` as self, for chaining purposes.
+    #[inline]
+    pub fn progress(mut self, progress: impl 'static + ProgressReporter) -> Self {
+        self.progress = Box::new(progress);
+        self
+    }
+
+    /// Sets the style of the installer (e.g., as loaded with `InstallerStyle::from_file()`).
+    /// 
+    /// # Arguments
+    /// - `style`: The InstallerStyle to use.
+    /// 
+    /// # Returns
+    /// The same `Builder` as self, for chaining purposes.
+    #[inline]
+    pub fn style(mut self, style: InstallerStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the path to write a JSON report to at the end of every build (e.g., to store as a CI artifact).
+    /// 
+    /// The report lists, for every target made, whether it was built, up-to-date or failed, how long it took and which effects were found to be changed (see `BuildReport`). It is written even if the build fails.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the file to write the report to. Will be overwritten if it already exists.
+    /// 
+    /// # Returns
+    /// The same `Builder` as self, for chaining purposes.
+    #[inline]
+    pub fn report(mut self, path: impl Into<PathBuf>) -> Self {
+        self.report = Some(path.into());
+        self
+    }
+
+    /// Sets whether to make independent targets in parallel, using rayon's global thread pool.
+    /// 
+    /// If enabled, the dependency tree of the target to build is partitioned into levels (like `Installer::build_levels()`), which are made one after another. All targets within a level are made in parallel, and every target is made only once (see `Target::make_ready_observed()`). By default, targets are made sequentially, in the order in which they are depended upon.
+    /// 
+    /// # Arguments
+    /// - `parallel`: Whether to make independent targets in parallel (true) or not (false).
+    /// 
+    /// # Returns
+    /// The same `Builder` as self, for chaining purposes.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Limits how many targets of the given resource class are made at once when building in parallel (see `Builder::parallel()`).
+    /// 
+    /// Targets are tagged with a resource class through `Target::resource_class()`; targets that are not tagged share `spec::DEFAULT_RESOURCE_CLASS`. Classes without a limit are not limited beyond the size of rayon's thread pool.
+    /// 
+    /// # Arguments
+    /// - `class`: The resource class to limit.
+    /// - `limit`: The maximum number of targets of that class that may be made at once. A limit of 0 is treated as 1.
+    /// 
+    /// # Returns
+    /// The same `Builder` as self, for chaining purposes.
+    #[cfg(feature = "rayon")]
+    #[inline]
+    pub fn resource_limit(mut self, class: impl Into<String>, limit: usize) -> Self {
+        self.limits.insert(class.into(), limit.max(1));
+        self
+    }
+
+
+
+    /// Builds the Installer from the targets added to this builder.
+    /// 
+    /// # Returns
+    /// A new Installer instance with all the targets registered.
+    /// 
+    /// # Panics
+    /// This function panics if two targets have the same name.
+    pub fn build(self) -> Installer {
+        // Register all targets, asserting their names are unique
+        let mut targets: HashMap<String, Arc<dyn Target>> = HashMap::with_capacity(self.targets.len());
+        for target in self.targets {
+            let name: String = target.name().into();
+            if targets.insert(name.clone(), Arc::from(target)).is_some() {
+                panic!("A Target with name '{}' is already registered", name);
+            }
+        }
+
+        // Done
+        Installer {
+            style    : self.style,
+            observer : self.observer,
+            progress : self.progress,
+            report   : self.report,
+            #[cfg(feature = "rayon")]
+            parallel : self.parallel,
+            #[cfg(feature = "rayon")]
+            limits   : self.limits,
+            targets,
+        }
+    }
 }
 
 
@@ -61,8 +396,21 @@ pub struct Installer {
     /// Determines the style of the installer (i.e., the colour scheme and such).
     style : InstallerStyle,
 
+    /// The observer that is notified of the events during builds.
+    observer : Box<dyn BuildObserver>,
+    /// The reporter that is used to show the progress of builds.
+    progress : Box<dyn ProgressReporter>,
+    /// The path to write a JSON report of every build to, if any.
+    report   : Option<PathBuf>,
+    /// Whether to make independent targets in parallel.
+    #[cfg(feature = "rayon")]
+    parallel : bool,
+    /// The maximum number of targets that may be made at once per resource class.
+    #[cfg(feature = "rayon")]
+    limits   : HashMap<String, usize>,
+
     /// Keeps track of all of the targets registered in the Installer.
-    targets : HashMap<String, Rc<dyn Target>>,
+    targets : HashMap<String, Arc<dyn Target>>,
 }
 
 impl Installer {
@@ -77,6 +425,185 @@ impl Installer {
 
 
 
+    /// Builds the given target (and, by extension, its dependencies).
+    /// 
+    /// The Installer's observer (see `Builder::observer()`) is notified of everything that happens during the build, and its progress reporter (see `Builder::progress()`) is advanced once for every target made and given a summary of the build at the end. If a report path is set (see `Builder::report()`), a `BuildReport` is written to it afterwards, regardless of whether the build succeeded.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target to build.
+    /// - `os`: The target OS that we intend to build.
+    /// - `arch`: The target architecture that we intend to build.
+    /// - `force`: If 'true', always builds all targets instead of only when there is no (detected) change.
+    /// - `dry_run`: If 'true', prints what would be done instead of actually executing the commands.
+    /// 
+    /// # Errors
+    /// This function errors if the target is unknown, if we failed to make it or if we failed to write the report.
+    pub fn build(&self, name: impl AsRef<str>, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool) -> Result<(), BuildError> {
+        let name: &str = name.as_ref();
+
+        // Find the target
+        let target: &Arc<dyn Target> = match self.targets.get(name) {
+            Some(target) => target,
+            None         => { return Err(BuildError::UnknownTarget{ name: name.into() }); },
+        };
+
+        // Make it, while reporting progress
+        let start: Instant = Instant::now();
+        let recorder: ReportRecorder = ReportRecorder::new();
+        self.progress.start(count_targets(&**target, &mut HashSet::new()));
+        let observer: ProgressObserver = ProgressObserver {
+            observer : &*self.observer,
+            progress : &*self.progress,
+            recorder : &recorder,
+            done     : Mutex::new(HashSet::new()),
+        };
+        #[cfg(feature = "rayon")]
+        let res: Result<(), TargetError> = if self.parallel { make_parallel(&**target, os, arch, force, dry_run, &observer, &self.limits) } else { target.make_observed(os, arch, force, dry_run, &observer) };
+        #[cfg(not(feature = "rayon"))]
+        let res: Result<(), TargetError> = target.make_observed(os, arch, force, dry_run, &observer);
+        let report: BuildReport = BuildReport {
+            target   : name.into(),
+            success  : res.is_ok(),
+            duration : start.elapsed().as_secs_f64(),
+            targets  : recorder.into_targets(),
+        };
+        self.progress.finish();
+        self.progress.summary(&report);
+
+        // Write the report, if told to do so
+        if let Some(path) = &self.report {
+            let handle: File = match File::create(path) {
+                Ok(handle) => handle,
+                Err(err)   => { return Err(BuildError::ReportCreateError{ path: path.clone(), err }); },
+            };
+            if let Err(err) = serde_json::to_writer_pretty(handle, &report) {
+                // A failed build is the more interesting error to return
+                if let Err(err) = res { return Err(BuildError::MakeError{ name: name.into(), err }); }
+                return Err(BuildError::ReportWriteError{ path: path.clone(), err });
+            }
+        }
+
+        // Done
+        match res {
+            Ok(_)    => Ok(()),
+            Err(err) => Err(BuildError::MakeError{ name: name.into(), err }),
+        }
+    }
+
+
+
+    /// Builds the given target (and, by extension, its dependencies), aborting if it takes too long.
+    /// 
+    /// Once the deadline passes, no new targets are built, and any `ShellCommand`s that are still running are killed. Note that this means that partial outputs of the aborted targets may remain.
+    /// 
+    /// # Arguments
+    /// - `deadline`: The moment by which the build must be done.
+    /// - `name`: The name of the target to build.
+    /// - `os`: The target OS that we intend to build.
+    /// - `arch`: The target architecture that we intend to build.
+    /// - `force`: If 'true', always builds all targets instead of only when there is no (detected) change.
+    /// - `dry_run`: If 'true', prints what would be done instead of actually executing the commands.
+    /// 
+    /// # Errors
+    /// This function errors if the target is unknown, if we failed to make it or if the deadline passed (in which case a `BuildError::Timeout` is returned).
+    pub fn build_with_deadline(&self, deadline: Instant, name: impl AsRef<str>, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool) -> Result<(), BuildError> {
+        let name: &str = name.as_ref();
+
+        // Run the build with the deadline set, restoring any previous one afterwards
+        let old: Option<Instant> = set_deadline(Some(deadline));
+        let res: Result<(), BuildError> = self.build(name, os, arch, force, dry_run);
+        set_deadline(old);
+
+        // Any failure after the deadline is because of it
+        match res {
+            Err(BuildError::MakeError{ .. }) if Instant::now() >= deadline => Err(BuildError::Timeout{ name: name.into() }),
+            res                                                           => res,
+        }
+    }
+
+
+
+    /// Partitions the registered targets into "levels" that can be built one after another.
+    /// 
+    /// The first level contains all targets without (registered) dependencies, the second level all targets that only depend on targets in the first level, and so on. As such, all targets within the same level are independent of each other and may be built concurrently, as long as the previous level has been built completely.
+    /// 
+    /// Note that dependencies on targets that are not registered in the Installer are ignored.
+    /// 
+    /// # Returns
+    /// A list of levels, each of which is a (sorted) list of target names.
+    /// 
+    /// # Panics
+    /// This function panics if the dependencies of the targets form a cycle.
+    pub fn build_levels(&self) -> Vec<Vec<String>> {
+        // Collect the (registered) dependencies per target
+        let mut todo: HashMap<&str, HashSet<&str>> = self.targets.iter().map(|(name, target)| {
+            (name.as_str(), target.deps().iter().map(|v| v.target.name()).filter(|n| self.targets.contains_key(*n)).collect())
+        }).collect();
+
+        // Peel off the targets without remaining dependencies until none are left
+        let mut levels: Vec<Vec<String>> = vec![];
+        while !todo.is_empty() {
+            let mut level: Vec<&str> = todo.iter().filter(|(_, deps)| deps.is_empty()).map(|(name, _)| *name).collect();
+            if level.is_empty() { panic!("Dependency cycle detected between targets {:?}", todo.keys().collect::<Vec<_>>()); }
+            level.sort_unstable();
+
+            // Remove them from the graph
+            for name in &level {
+                todo.remove(name);
+            }
+            for deps in todo.values_mut() {
+                for name in &level {
+                    deps.remove(name);
+                }
+            }
+
+            levels.push(level.into_iter().map(String::from).collect());
+        }
+
+        // Done
+        levels
+    }
+
+
+
+    /// Renders the dependency graph of the registered targets as a [Mermaid](https://mermaid.js.org) flowchart.
+    /// 
+    /// Every target is a node, and every dependency is an edge from the depending target to the target it depends on. Edges are labelled with the names of the effects that are depended upon (if any).
+    /// 
+    /// # Returns
+    /// A `graph TD` block that can be embedded in, e.g., Markdown.
+    pub fn to_mermaid(&self) -> String {
+        /// Converts a target name to something that is a valid Mermaid node ID.
+        fn node_id(name: &str) -> String {
+            name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' }).collect()
+        }
+
+        // Sort the targets to have a deterministic result
+        let mut targets: Vec<(&String, &Arc<dyn Target>)> = self.targets.iter().collect();
+        targets.sort_unstable_by_key(|(name, _)| *name);
+
+        // Write the nodes first, then the edges
+        let mut res: String = String::from("graph TD\n");
+        for (name, _) in &targets {
+            res.push_str(&format!("    {}[\"{}\"]\n", node_id(name), name));
+        }
+        for (name, target) in &targets {
+            for view in target.deps() {
+                let effects: Vec<&str> = view.iter().map(|e| e.name()).collect();
+                if effects.is_empty() {
+                    res.push_str(&format!("    {} --> {}\n", node_id(name), node_id(view.target.name())));
+                } else {
+                    res.push_str(&format!("    {} -->|\"{}\"| {}\n", node_id(name), effects.join(", "), node_id(view.target.name())));
+                }
+            }
+        }
+
+        // Done
+        res
+    }
+
+
+
     // /// Registers a new build target with the installer.
     // /// 
     // /// # Arguments
@@ -90,7 +617,7 @@ impl Installer {
     // #[inline]
     // pub fn register(&mut self, target: impl Target) {
     //     // Sanity check the name's unique
-    //     if let Some(old) = self.targets.insert(target.name().clone(), Rc::new(target)) {
+    //     if let Some(old) = self.targets.insert(target.name().clone(), Arc::new(target)) {
     //         panic!("A Target with name '{}' is already registered", old.name());
     //     }
     // }
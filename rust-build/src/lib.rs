@@ -26,6 +26,9 @@ pub mod spec;
 pub mod view;
 pub mod cache;
 pub mod shell;
+pub mod observer;
+pub mod progress;
+pub mod report;
 pub mod style;
 pub mod installer;
 #[cfg(test)]
@@ -36,6 +39,9 @@ pub mod tests;
 pub use errors::BuildError as Error;
 pub use spec::{Effect, Named, Target, TargetBuilder};
 pub use cache::Cache;
+pub use observer::BuildObserver;
+pub use progress::ProgressReporter;
+pub use report::BuildReport;
 pub use installer::{Builder, Installer};
 
 
@@ -54,3 +60,18 @@ macro_rules! debug {
     };
 }
 pub(crate) use debug;
+
+/// A feature-dependent `warn` macro.
+#[cfg(feature = "log")]
+macro_rules! warning {
+    ($($t:tt)*) => {
+        log::warn!($($t)*)
+    };
+}
+#[cfg(not(feature = "log"))]
+macro_rules! warning {
+    ($($t:tt)*) => {
+        // Do not use them
+    };
+}
+pub(crate) use warning as warn;
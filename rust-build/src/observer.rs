@@ -0,0 +1,62 @@
+//  OBSERVER.rs
+//    by Lut99
+// 
+//  Created:
+//    15 Oct 2026, 10:52:17
+//  Last edited:
+//    15 Oct 2026, 10:52:17
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines the BuildObserver, which can be used to observe what
+//!   happens during a build (e.g., for custom reporting).
+// 
+
+use crate::errors::TargetError;
+
+
+/***** LIBRARY *****/
+/// Defines a BuildObserver, which is notified of the events happening while targets are being made.
+/// 
+/// All methods do nothing by default, so implementations only have to override the events they are interested in. Note that, when building in parallel (see `Builder::parallel()`), events may be received from multiple threads at once.
+pub trait BuildObserver: Send + Sync {
+    /// Called when a target is about to be made (i.e., before its dependencies are).
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    #[inline]
+    fn on_target_start(&self, _name: &str) {}
+
+    /// Called when a target has been made (or failed to).
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `result`: Whether the target had to be rebuild, or the error that occurred while making it.
+    #[inline]
+    fn on_target_finish(&self, _name: &str, _result: &Result<bool, TargetError>) {}
+
+    /// Called when a target has been built and reported how many warnings that emitted (see `Target::warnings()`).
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `count`: The number of warnings.
+    #[inline]
+    fn on_target_warnings(&self, _name: &str, _count: usize) {}
+
+    /// Called when an effect that a target depends on (or one of its inputs) has been found to be changed.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target that depends on the effect.
+    /// - `effect`: The name of the effect that has changed.
+    #[inline]
+    fn on_effect_changed(&self, _name: &str, _effect: &str) {}
+}
+
+
+
+/// Defines a BuildObserver that ignores all events.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoOpObserver;
+
+impl BuildObserver for NoOpObserver {}
@@ -0,0 +1,102 @@
+//  PROGRESS.rs
+//    by Lut99
+// 
+//  Created:
+//    15 Oct 2026, 11:14:03
+//  Last edited:
+//    15 Oct 2026, 11:14:03
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines the ProgressReporter, which is used by the Installer to
+//!   report how far along a build is.
+// 
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use console::style;
+
+use crate::report::BuildReport;
+
+
+/***** LIBRARY *****/
+/// Defines a ProgressReporter, which is driven by the Installer to show the progress of a build.
+/// 
+/// When building in parallel (see `Builder::parallel()`), targets may be advanced from multiple threads at once.
+pub trait ProgressReporter: Send + Sync {
+    /// Called when a build starts.
+    /// 
+    /// # Arguments
+    /// - `total`: The number of (unique) targets that will be made during this build.
+    fn start(&self, total: usize);
+
+    /// Called whenever a target has been made. Called once per target, even if it is depended upon multiple times.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target that has been made.
+    fn advance(&self, name: &str);
+
+    /// Called when a build is done (whether it failed or not).
+    fn finish(&self);
+
+    /// Called after `ProgressReporter::finish()` with a report of what happened to every target (e.g., to show how long they took).
+    /// 
+    /// Does nothing by default.
+    /// 
+    /// # Arguments
+    /// - `report`: The BuildReport of the build that just finished.
+    #[inline]
+    fn summary(&self, _report: &BuildReport) {}
+}
+
+
+
+/// Defines a ProgressReporter that does not report anything.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SilentReporter;
+
+impl ProgressReporter for SilentReporter {
+    #[inline]
+    fn start(&self, _total: usize) {}
+
+    #[inline]
+    fn advance(&self, _name: &str) {}
+
+    #[inline]
+    fn finish(&self) {}
+}
+
+
+
+/// Defines a ProgressReporter that writes a line to stdout for every target made, and a timing summary at the end.
+#[derive(Debug, Default)]
+pub struct ConsoleReporter {
+    /// The number of targets made so far.
+    done  : AtomicUsize,
+    /// The number of targets to make in total.
+    total : AtomicUsize,
+}
+
+impl ProgressReporter for ConsoleReporter {
+    #[inline]
+    fn start(&self, total: usize) {
+        self.done.store(0, Ordering::Relaxed);
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    fn advance(&self, name: &str) {
+        let done: usize = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        println!("{} {}", style(format!("[{}/{}]", done, self.total.load(Ordering::Relaxed))).bold(), name);
+    }
+
+    #[inline]
+    fn finish(&self) {
+        println!("{}", style(format!("Done ({}/{} targets)", self.done.load(Ordering::Relaxed), self.total.load(Ordering::Relaxed))).bold());
+    }
+
+    #[inline]
+    fn summary(&self, report: &BuildReport) {
+        println!("{} {}", style("Timings:").bold(), report.summary());
+    }
+}
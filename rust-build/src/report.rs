@@ -0,0 +1,198 @@
+//  REPORT.rs
+//    by Lut99
+// 
+//  Created:
+//    15 Oct 2026, 14:41:09
+//  Last edited:
+//    15 Oct 2026, 14:41:09
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Defines a structured report of a build run, which may be written to
+//!   disk as JSON (e.g., as a CI artifact).
+// 
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::TargetError;
+use crate::observer::BuildObserver;
+
+
+/***** AUXILLARY *****/
+/// The targets that are currently being made on a single thread, together with when they were started and how much time their dependencies took so far.
+type RunningTargets = Vec<(String, Instant, Duration)>;
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the possible outcomes of making a target.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TargetOutcome {
+    /// The target had to be (and was) rebuild.
+    Built,
+    /// The target was skipped because nothing changed.
+    UpToDate,
+    /// The target (or one of its dependencies) failed to be made.
+    Failed,
+}
+
+
+
+/// Defines the report of a single target in a build.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TargetReport {
+    /// The name of the target.
+    pub name            : String,
+    /// What happened to the target.
+    pub outcome         : TargetOutcome,
+    /// The time spent on the target itself (i.e., excluding the time spent on making its dependencies), in seconds.
+    pub duration        : f64,
+    /// The names of the effects (of dependencies or inputs) that were found to be changed.
+    pub changed_effects : Vec<String>,
+    /// The number of warnings emitted while building the target, if it counts them (see `Target::warnings()`).
+    #[serde(default)]
+    pub warnings        : Option<usize>,
+    /// The error that occurred, if the target failed.
+    pub error           : Option<String>,
+}
+
+
+
+/// Defines the report of a whole build, as produced by `Installer::build()`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct BuildReport {
+    /// The name of the target that was built.
+    pub target   : String,
+    /// Whether the build succeeded.
+    pub success  : bool,
+    /// The total time the build took, in seconds.
+    pub duration : f64,
+    /// The reports of all targets that were made, in the order they were finished (i.e., dependencies first).
+    pub targets  : Vec<TargetReport>,
+}
+
+impl BuildReport {
+    /// Summarizes how long every target took on a single line (e.g., `cargo[app] 12.3s, copy 0.1s, assets up-to-date`).
+    /// 
+    /// Targets that were built (or failed) are sorted from slowest to fastest, followed by the targets that were up-to-date in alphabetical order.
+    /// 
+    /// # Returns
+    /// A String with the summary.
+    pub fn summary(&self) -> String {
+        let mut targets: Vec<&TargetReport> = self.targets.iter().collect();
+        targets.sort_by(|lhs, rhs| {
+            match (lhs.outcome == TargetOutcome::UpToDate, rhs.outcome == TargetOutcome::UpToDate) {
+                (false, false) => rhs.duration.total_cmp(&lhs.duration).then_with(|| lhs.name.cmp(&rhs.name)),
+                (true, true)   => lhs.name.cmp(&rhs.name),
+                (lhs, rhs)     => lhs.cmp(&rhs),
+            }
+        });
+        targets.into_iter().map(|t| match t.outcome {
+            TargetOutcome::Built    => format!("{} {:.1}s", t.name, t.duration),
+            TargetOutcome::UpToDate => format!("{} up-to-date", t.name),
+            TargetOutcome::Failed   => format!("{} {:.1}s (failed)", t.name, t.duration),
+        }).collect::<Vec<String>>().join(", ")
+    }
+}
+
+
+
+/// Defines a BuildObserver that records what happens to every target into `TargetReport`s.
+/// 
+/// Targets that are made more than once (e.g., because multiple targets depend on them) are only reported the first time.
+#[derive(Debug, Default)]
+pub struct ReportRecorder {
+    /// The targets that are currently being made, per thread (see `Builder::parallel()`).
+    running  : Mutex<HashMap<ThreadId, RunningTargets>>,
+    /// The effects that have been found to be changed per target.
+    changed  : Mutex<HashMap<String, Vec<String>>>,
+    /// The number of warnings reported per target.
+    warnings : Mutex<HashMap<String, usize>>,
+    /// The reports of the targets that are done.
+    done     : Mutex<Vec<TargetReport>>,
+}
+
+impl ReportRecorder {
+    /// Constructor for the ReportRecorder.
+    /// 
+    /// # Returns
+    /// A new ReportRecorder that hasn't recorded anything yet.
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+
+
+    /// Returns the reports of the targets recorded so far.
+    /// 
+    /// # Returns
+    /// A list of TargetReports, in the order the targets were finished.
+    #[inline]
+    pub fn targets(&self) -> Vec<TargetReport> { self.done.lock().unwrap_or_else(|err| err.into_inner()).clone() }
+
+    /// Consumes the recorder and returns the reports of the targets recorded.
+    /// 
+    /// # Returns
+    /// A list of TargetReports, in the order the targets were finished.
+    #[inline]
+    pub fn into_targets(self) -> Vec<TargetReport> { self.done.into_inner().unwrap_or_else(|err| err.into_inner()) }
+}
+
+impl BuildObserver for ReportRecorder {
+    #[inline]
+    fn on_target_start(&self, name: &str) {
+        self.running.lock().unwrap_or_else(|err| err.into_inner()).entry(thread::current().id()).or_default().push((name.into(), Instant::now(), Duration::ZERO));
+    }
+
+    fn on_target_finish(&self, name: &str, result: &Result<bool, TargetError>) {
+        // Compute the time spent on this target alone, and attribute the whole to the parent's dependency time (which is made on the same thread)
+        let duration: Duration = {
+            let mut running = self.running.lock().unwrap_or_else(|err| err.into_inner());
+            let running: &mut RunningTargets = running.entry(thread::current().id()).or_default();
+            match running.pop() {
+                Some((_, start, deps)) => {
+                    let elapsed: Duration = start.elapsed();
+                    if let Some((_, _, parent_deps)) = running.last_mut() { *parent_deps += elapsed; }
+                    elapsed.saturating_sub(deps)
+                },
+                None => Duration::ZERO,
+            }
+        };
+
+        // Only report the first time a target is made
+        let changed  : Vec<String>   = self.changed.lock().unwrap_or_else(|err| err.into_inner()).remove(name).unwrap_or_default();
+        let warnings : Option<usize> = self.warnings.lock().unwrap_or_else(|err| err.into_inner()).remove(name);
+        let mut done = self.done.lock().unwrap_or_else(|err| err.into_inner());
+        if done.iter().any(|t| t.name == name) { return; }
+        done.push(TargetReport {
+            name    : name.into(),
+            outcome : match result {
+                Ok(true)  => TargetOutcome::Built,
+                Ok(false) => TargetOutcome::UpToDate,
+                Err(_)    => TargetOutcome::Failed,
+            },
+            duration        : duration.as_secs_f64(),
+            changed_effects : changed,
+            warnings,
+            error           : result.as_ref().err().map(|err| err.to_string()),
+        });
+    }
+
+    #[inline]
+    fn on_target_warnings(&self, name: &str, count: usize) {
+        self.warnings.lock().unwrap_or_else(|err| err.into_inner()).insert(name.into(), count);
+    }
+
+    #[inline]
+    fn on_effect_changed(&self, name: &str, effect: &str) {
+        self.changed.lock().unwrap_or_else(|err| err.into_inner()).entry(name.into()).or_default().push(effect.into());
+    }
+}
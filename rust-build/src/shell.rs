@@ -13,21 +13,352 @@
 //!   interaction easier.
 // 
 
-use std::collections::HashMap;
+use std::cell::Cell;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+use std::process::{Child, ChildStderr, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
+use crate::debug;
 pub use crate::errors::ShellCommandError as Error;
 
 
+/***** CONSTANTS *****/
+/// The interval at which we poll running commands to see if they have completed when they have a deadline.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+thread_local! {
+    /// The deadline that applies to everything built on this thread (see `Installer::build_with_deadline()`).
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// A spawned command together with the readers for its stdout and (if not merged) stderr, as returned by `ShellCommand::spawn_piped()`.
+type PipedChild = (Child, Box<dyn Send + Read>, Option<ChildStderr>);
+
+/// All but the last command of a pipeline once spawned, together with the thread writing to the first command's stdin and the stdin for the last command, as returned by `Pipeline::spawn_stages()`.
+type SpawnedStages = (Vec<Child>, Option<JoinHandle<io::Result<()>>>, Stdio);
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Returns the global deadline that applies to everything built on this thread, if any.
+#[inline]
+pub fn deadline() -> Option<Instant> { DEADLINE.with(|d| d.get()) }
+
+/// Sets the global deadline that applies to everything built on this thread.
+/// 
+/// # Arguments
+/// - `deadline`: The new deadline, or `None` to remove it.
+/// 
+/// # Returns
+/// The previous deadline, such that it may be restored.
+#[inline]
+pub(crate) fn set_deadline(deadline: Option<Instant>) -> Option<Instant> { DEADLINE.with(|d| d.replace(deadline)) }
+
+/// Returns the flag that makes the given shell interpreter run a command string.
+/// 
+/// # Arguments
+/// - `shell`: The path to (or name of) the shell interpreter.
+/// 
+/// # Returns
+/// `/C` for `cmd`, `-Command` for PowerShell and `-c` for anything else (i.e., POSIX-like shells).
+fn shell_flag(shell: &str) -> &'static str {
+    // Split on both separators, since Windows paths may be given on any platform
+    let name: String = shell.rsplit(['/', '\\']).next().unwrap_or(shell).to_lowercase();
+    match name.strip_suffix(".exe").unwrap_or(&name) {
+        "cmd"                 => "/C",
+        "powershell" | "pwsh" => "-Command",
+        _                     => "-c",
+    }
+}
+
+/// Reads everything from the given stream on a separate thread, such that a command cannot block on a full pipe while we wait for it.
+/// 
+/// # Arguments
+/// - `stream`: The stream to read, or `None` to read nothing.
+/// 
+/// # Returns
+/// A handle to the thread, which returns the bytes read.
+fn read_to_end(stream: Option<impl 'static + Send + Read>) -> JoinHandle<io::Result<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut buf: Vec<u8> = vec![];
+        if let Some(mut stream) = stream { stream.read_to_end(&mut buf)?; }
+        Ok(buf)
+    })
+}
+
+/// Reads the given stream line-by-line on a separate thread, sending every line over the given channel.
+/// 
+/// # Arguments
+/// - `stream`: The stream to read, or `None` to read nothing.
+/// - `wrap`: Wraps a line in the StreamLine variant for this stream.
+/// - `sender`: The channel to send the lines (or any error) over.
+/// 
+/// # Returns
+/// A handle to the thread.
+fn read_lines(stream: Option<impl 'static + Send + Read>, wrap: fn(String) -> StreamLine, sender: Sender<io::Result<StreamLine>>) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let stream = match stream {
+            Some(stream) => stream,
+            None         => { return; },
+        };
+        for line in BufReader::new(stream).split(b'\n') {
+            // Decode it leniently, since commands may emit anything
+            let line: io::Result<StreamLine> = line.map(|mut line| {
+                if line.last() == Some(&b'\r') { line.pop(); }
+                wrap(String::from_utf8_lossy(&line).into())
+            });
+            let failed: bool = line.is_err();
+            if sender.send(line).is_err() || failed { return; }
+        }
+    })
+}
+
+/// Writes the given data to the given stream on a separate thread, closing it afterwards.
+/// 
+/// This is done on a separate thread such that a command cannot block on a full output pipe while we are still writing its input. If the stream is closed early (i.e., the command does not read all of its input), this is not considered an error.
+/// 
+/// # Arguments
+/// - `stream`: The stream to write to, or `None` to write nothing.
+/// - `data`: The data to write.
+/// 
+/// # Returns
+/// A handle to the thread.
+fn write_all(stream: Option<impl 'static + Send + Write>, data: Vec<u8>) -> JoinHandle<io::Result<()>> {
+    thread::spawn(move || {
+        if let Some(mut stream) = stream {
+            match stream.write_all(&data) {
+                Ok(_)                                               => {},
+                Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {},
+                Err(err)                                            => { return Err(err); },
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Quotes the given word for a POSIX-like shell, if necessary.
+/// 
+/// # Arguments
+/// - `word`: The word (e.g., an argument) to quote.
+/// 
+/// # Returns
+/// The word as-is if it only contains harmless characters, or else the word wrapped in single quotes.
+fn quote(word: &str) -> Cow<'_, str> {
+    if !word.is_empty() && word.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=,+@%".contains(c)) {
+        Cow::Borrowed(word)
+    } else {
+        Cow::Owned(format!("'{}'", word.replace('\'', "'\\''")))
+    }
+}
+
+/// Splits the given command line into words like a POSIX-like shell would (but without any expansions).
+/// 
+/// Words are separated by whitespace, unless it's quoted or escaped with a backslash. In single quotes, everything is taken literally; in double quotes, only `\"` and `\\` are escapes.
+/// 
+/// # Arguments
+/// - `line`: The command line to split.
+/// 
+/// # Returns
+/// The words in the command line.
+/// 
+/// # Errors
+/// This function errors if a quote is not closed.
+fn split_words(line: &str) -> Result<Vec<String>, Error> {
+    let mut words : Vec<String>     = vec![];
+    let mut word  : Option<String>  = None;
+    let mut chars : Peekable<Chars> = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => { if let Some(word) = word.take() { words.push(word); } },
+            '\'' => {
+                let word: &mut String = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => { break; },
+                        Some(c)    => { word.push(c); },
+                        None       => { return Err(Error::UnterminatedQuote{ line: line.into() }); },
+                    }
+                }
+            },
+            '"' => {
+                let word: &mut String = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"')                                              => { break; },
+                        Some('\\') if matches!(chars.peek(), Some('"' | '\\')) => { word.push(chars.next().unwrap()); },
+                        Some(c)                                                => { word.push(c); },
+                        None                                                   => { return Err(Error::UnterminatedQuote{ line: line.into() }); },
+                    }
+                }
+            },
+            '\\'                   => { word.get_or_insert_with(String::new).push(chars.next().unwrap_or('\\')); },
+            c                      => { word.get_or_insert_with(String::new).push(c); },
+        }
+    }
+    if let Some(word) = word { words.push(word); }
+    Ok(words)
+}
+
+/// Returns the default shell interpreter of the current platform.
+/// 
+/// On Windows, this is `$COMSPEC` if set, or else `cmd`. Elsewhere, it is `$SHELL` if set, or else `/bin/sh`.
+/// 
+/// # Returns
+/// The path to (or name of) the shell interpreter.
+pub fn default_shell() -> String {
+    #[cfg(windows)]
+    let (var, default): (&str, &str) = ("COMSPEC", "cmd");
+    #[cfg(not(windows))]
+    let (var, default): (&str, &str) = ("SHELL", "/bin/sh");
+    match std::env::var(var) {
+        Ok(shell) if !shell.is_empty() => shell,
+        _                              => default.into(),
+    }
+}
+
+
+
+
+
 /***** LIBRARY *****/
+/// Defines why a command stopped running.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ExitReason {
+    /// The command exited by itself with the given return code.
+    Code(i32),
+    /// The command was terminated by the given signal (only on Unix).
+    Signal(i32),
+}
+
+impl ExitReason {
+    /// Returns the return code of the command, following the shell convention of `128 + signal` for commands that were killed by a signal.
+    #[inline]
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::Code(code)     => *code,
+            Self::Signal(signal) => 128 + *signal,
+        }
+    }
+
+    /// Returns the signal that terminated the command, if any.
+    #[inline]
+    pub fn signal(&self) -> Option<i32> {
+        match self {
+            Self::Code(_)        => None,
+            Self::Signal(signal) => Some(*signal),
+        }
+    }
+
+    /// Returns whether the command succeeded (i.e., exited by itself with return code `0`).
+    #[inline]
+    pub fn success(&self) -> bool { *self == Self::Code(0) }
+}
+
+impl From<ExitStatus> for ExitReason {
+    fn from(value: ExitStatus) -> Self {
+        // See if it was killed by a signal first
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(signal) = value.signal() { return Self::Signal(signal); }
+        }
+
+        // Otherwise, there should be a code
+        Self::Code(value.code().unwrap_or(-1))
+    }
+}
+
+
+
+/// Defines the output of a ShellCommand as captured by `ShellCommand::output()`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShellOutput {
+    /// Why the command stopped running (i.e., its return code or the signal that killed it).
+    pub status : ExitReason,
+    /// Everything the command wrote to its stdout (including its stderr if it was merged).
+    pub stdout : Vec<u8>,
+    /// Everything the command wrote to its stderr (empty if it was merged into stdout).
+    pub stderr : Vec<u8>,
+}
+
+impl ShellOutput {
+    /// Returns whether the command succeeded (i.e., returned exit code `0`).
+    #[inline]
+    pub fn success(&self) -> bool { self.status.success() }
+
+    /// Returns the return code of the command, or `128 + signal` if it was killed by a signal.
+    #[inline]
+    pub fn code(&self) -> i32 { self.status.code() }
+
+    /// Returns the captured stdout as a String, replacing any invalid UTF-8.
+    #[inline]
+    pub fn stdout_lossy(&self) -> String { String::from_utf8_lossy(&self.stdout).into() }
+
+    /// Returns the captured stderr as a String, replacing any invalid UTF-8.
+    #[inline]
+    pub fn stderr_lossy(&self) -> String { String::from_utf8_lossy(&self.stderr).into() }
+}
+
+
+
+/// Defines a single line of output of a ShellCommand, as given by `ShellCommand::run_streaming()`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StreamLine {
+    /// The line was written to stdout (or to stderr, if it was merged).
+    Stdout(String),
+    /// The line was written to stderr.
+    Stderr(String),
+}
+
+impl StreamLine {
+    /// Returns the line itself (without newline), regardless of which stream it was written to.
+    #[inline]
+    pub fn line(&self) -> &str {
+        match self {
+            Self::Stdout(line) | Self::Stderr(line) => line,
+        }
+    }
+}
+
+
+
 /// Defines a shell command that can be run when building.
 #[derive(Clone, Debug)]
 pub struct ShellCommand {
     /// The executable to run.
-    exec : String,
+    exec         : String,
     /// The arguments to pass to the executable.
-    args : Vec<String>,
+    args         : Vec<String>,
     /// Additional environment variables to set.
-    envs : HashMap<String, String>,
+    envs         : HashMap<String, String>,
+    /// Environment variables to remove from the inherited environment.
+    removed_envs : HashSet<String>,
+    /// Whether to not inherit any environment variables at all.
+    clear_env    : bool,
+    /// The directory to run the command in, if not the current one.
+    cwd          : Option<PathBuf>,
+    /// The data to write to the command's stdin, if any.
+    stdin        : Option<Vec<u8>>,
+
+    /// Whether to redirect the command's stderr to its stdout (i.e., `2>&1`).
+    merge_stderr : bool,
 }
 
 impl ShellCommand {
@@ -41,9 +372,15 @@ impl ShellCommand {
     #[inline]
     pub fn exec_only(exec: impl Into<String>) -> Self {
         Self {
-            exec : exec.into(),
-            args : vec![],
-            envs : HashMap::new(),
+            exec         : exec.into(),
+            args         : vec![],
+            envs         : HashMap::new(),
+            removed_envs : HashSet::new(),
+            clear_env    : false,
+            cwd          : None,
+            stdin        : None,
+
+            merge_stderr : false,
         }
     }
 
@@ -58,9 +395,15 @@ impl ShellCommand {
     #[inline]
     pub fn with_args(exec: impl Into<String>, args: impl IntoIterator<Item = impl Into<String>, IntoIter = impl Iterator<Item = impl Into<String>>>) -> Self {
         Self {
-            exec : exec.into(),
-            args : args.into_iter().map(|a| a.into()).collect(),
-            envs : HashMap::new(),
+            exec         : exec.into(),
+            args         : args.into_iter().map(|a| a.into()).collect(),
+            envs         : HashMap::new(),
+            removed_envs : HashSet::new(),
+            clear_env    : false,
+            cwd          : None,
+            stdin        : None,
+
+            merge_stderr : false,
         }
     }
 
@@ -75,12 +418,33 @@ impl ShellCommand {
     #[inline]
     pub fn with_envs(exec: impl Into<String>, envs: impl IntoIterator<Item = (impl Into<String>, impl Into<String>), IntoIter = impl Iterator<Item = (impl Into<String>, impl Into<String>)>>) -> Self {
         Self {
-            exec : exec.into(),
-            args : vec![],
-            envs : envs.into_iter().map(|(n, v)| (n.into(), v.into())).collect(),
+            exec         : exec.into(),
+            args         : vec![],
+            envs         : envs.into_iter().map(|(n, v)| (n.into(), v.into())).collect(),
+            removed_envs : HashSet::new(),
+            clear_env    : false,
+            cwd          : None,
+            stdin        : None,
+
+            merge_stderr : false,
         }
     }
 
+    /// Constructor for the ShellCommand that initializes it to run in the given working directory (but without any arguments or environment variables set yet).
+    /// 
+    /// # Arguments
+    /// - `exec`: The executable to run.
+    /// - `cwd`: The directory to run the executable in.
+    /// 
+    /// # Returns
+    /// A new ShellCommand for the executable that runs in the given directory.
+    #[inline]
+    pub fn with_cwd(exec: impl Into<String>, cwd: impl Into<PathBuf>) -> Self {
+        let mut cmd: Self = Self::exec_only(exec);
+        cmd.cwd = Some(cwd.into());
+        cmd
+    }
+
     /// Constructor for the ShellCommand that initializes it with the given arguments and environment variables.
     /// 
     /// # Arguments
@@ -93,14 +457,91 @@ impl ShellCommand {
     #[inline]
     pub fn new(exec: impl Into<String>, args: impl IntoIterator<Item = impl Into<String>, IntoIter = impl Iterator<Item = impl Into<String>>>, envs: impl IntoIterator<Item = (impl Into<String>, impl Into<String>), IntoIter = impl Iterator<Item = (impl Into<String>, impl Into<String>)>>) -> Self {
         Self {
-            exec : exec.into(),
-            args : args.into_iter().map(|a| a.into()).collect(),
-            envs : envs.into_iter().map(|(n, v)| (n.into(), v.into())).collect(),
+            exec         : exec.into(),
+            args         : args.into_iter().map(|a| a.into()).collect(),
+            envs         : envs.into_iter().map(|(n, v)| (n.into(), v.into())).collect(),
+            removed_envs : HashSet::new(),
+            clear_env    : false,
+            cwd          : None,
+            stdin        : None,
+
+            merge_stderr : false,
         }
     }
 
 
 
+    /// Constructor for the ShellCommand that runs the given command line in the platform's default shell (see `default_shell()`).
+    /// 
+    /// For example, this is `/bin/sh -c "<command>"` on Unix and `cmd /C "<command>"` on Windows.
+    /// 
+    /// # Arguments
+    /// - `command`: The command line to run.
+    /// 
+    /// # Returns
+    /// A new ShellCommand that runs the shell interpreter.
+    #[inline]
+    pub fn shell(command: impl Into<String>) -> Self {
+        Self::with_shell(default_shell(), command)
+    }
+
+    /// Constructor for the ShellCommand that runs the given command line in the given shell.
+    /// 
+    /// # Arguments
+    /// - `shell`: The path to (or name of) the shell interpreter to use. Its flag to run a command string is deduced from its name (e.g., `/C` for `cmd` and `-c` for `sh`).
+    /// - `command`: The command line to run.
+    /// 
+    /// # Returns
+    /// A new ShellCommand that runs the shell interpreter.
+    #[inline]
+    pub fn with_shell(shell: impl Into<String>, command: impl Into<String>) -> Self {
+        let shell: String = shell.into();
+        let flag: &str = shell_flag(&shell);
+        Self::with_args(shell, [ flag.to_string(), command.into() ])
+    }
+
+
+
+    /// Returns the executable that this ShellCommand runs.
+    #[inline]
+    pub fn exec(&self) -> &str { &self.exec }
+
+    /// Returns the arguments that this ShellCommand passes to its executable.
+    #[inline]
+    pub fn args(&self) -> &[String] { &self.args }
+
+    /// Returns the environment variables that this ShellCommand sets on top of the ones it inherits.
+    #[inline]
+    pub fn envs(&self) -> &HashMap<String, String> { &self.envs }
+
+    /// Returns the directory that this ShellCommand runs in, if not the current one.
+    #[inline]
+    pub fn cwd(&self) -> Option<&Path> { self.cwd.as_deref() }
+
+    /// Computes a hash of everything that determines what this ShellCommand does (i.e., its executable, arguments, environment, working directory and stdin).
+    /// 
+    /// This can be stored alongside the artifacts the command produces (see `CacheEntry::command_hash`), such that they are rebuilt if the command changes (e.g., because a feature flag was added).
+    /// 
+    /// # Returns
+    /// The hash of this ShellCommand, which is stable between runs of the same build.
+    pub fn command_hash(&self) -> u64 {
+        let mut hasher: DefaultHasher = DefaultHasher::new();
+        self.exec.hash(&mut hasher);
+        self.args.hash(&mut hasher);
+        let mut envs: Vec<(&String, &String)> = self.envs.iter().collect();
+        envs.sort_unstable();
+        envs.hash(&mut hasher);
+        let mut removed_envs: Vec<&String> = self.removed_envs.iter().collect();
+        removed_envs.sort_unstable();
+        removed_envs.hash(&mut hasher);
+        self.clear_env.hash(&mut hasher);
+        self.cwd.hash(&mut hasher);
+        self.stdin.hash(&mut hasher);
+        hasher.finish()
+    }
+
+
+
     /// Adds a new argument to this ShellCommand.
     /// 
     /// # Arguments
@@ -138,19 +579,779 @@ impl ShellCommand {
         self.envs.extend(envs);
     }
 
+    /// Removes an environment variable from the environment that the ShellCommand inherits.
+    /// 
+    /// This also undoes any earlier `ShellCommand::add_env()` for the same variable.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the environment variable to remove.
+    #[inline]
+    pub fn remove_env(&mut self, name: impl Into<String>) {
+        let name: String = name.into();
+        self.envs.remove(&name);
+        self.removed_envs.insert(name);
+    }
+
+    /// Makes the ShellCommand not inherit any environment variables, such that it only gets the ones added explicitly (e.g., for reproducible builds).
+    /// 
+    /// Note that this includes `PATH`, so either pass a full path as the executable or add `PATH` explicitly, or else the executable may not be found.
+    #[inline]
+    pub fn env_clear(&mut self) {
+        self.clear_env = true;
+    }
+
+    /// Sets the executable that this ShellCommand runs, replacing the one it was created with.
+    /// 
+    /// # Arguments
+    /// - `exec`: The path to (or name of) the new executable.
+    #[inline]
+    pub fn set_exec(&mut self, exec: impl Into<String>) {
+        self.exec = exec.into();
+    }
+
+    /// Sets the directory to run the command in.
+    /// 
+    /// By default, it runs in the current directory of the installer.
+    /// 
+    /// # Arguments
+    /// - `path`: The path to the directory to run the command in.
+    #[inline]
+    pub fn set_cwd(&mut self, path: impl Into<PathBuf>) {
+        self.cwd = Some(path.into());
+    }
+
+    /// Sets the data to write to the command's stdin.
+    /// 
+    /// The command's stdin is closed once all data is written. By default, the command inherits the installer's stdin (or gets none at all when using `ShellCommand::output()` or `ShellCommand::run_streaming()`).
+    /// 
+    /// # Arguments
+    /// - `data`: The data to write.
+    #[inline]
+    pub fn set_stdin(&mut self, data: impl Into<Vec<u8>>) {
+        self.stdin = Some(data.into());
+    }
+
+    /// Sets whether to merge the command's stderr into its stdout (like a `2>&1` redirect).
+    /// 
+    /// When enabled, both streams are written to the same stdout in the order in which the command writes them. By default, they are kept separate.
+    /// 
+    /// # Arguments
+    /// - `merge_stderr`: Whether to merge stderr into stdout (true) or not (false).
+    #[inline]
+    pub fn set_merge_stderr(&mut self, merge_stderr: bool) {
+        self.merge_stderr = merge_stderr;
+    }
+
 
 
+    /// Prepares a `std::process::Command` that runs this ShellCommand.
+    /// 
+    /// # Returns
+    /// A new Command with the executable, arguments, environment variables and working directory set.
+    fn command(&self) -> Command {
+        let mut cmd: Command = Command::new(&self.exec);
+        cmd.args(&self.args);
+        if self.clear_env { cmd.env_clear(); }
+        if let Some(cwd) = &self.cwd { cmd.current_dir(cwd); }
+        if self.stdin.is_some() { cmd.stdin(Stdio::piped()); }
+        for name in &self.removed_envs {
+            cmd.env_remove(name);
+        }
+        cmd.envs(&self.envs);
+        cmd
+    }
+
+
+
+    /// Spawns the command with its stdout and stderr piped to us (see `ShellCommand::output()`).
+    /// 
+    /// # Arguments
+    /// - `stdin`: The stdin to give to the command (e.g., the output of a previous command), or `None` to give it the data set with `ShellCommand::set_stdin()` (or nothing if there is none).
+    /// 
+    /// # Returns
+    /// The handle to the running command, a reader for its stdout (which also gets its stderr if it's merged) and a reader for its stderr (if not merged).
+    /// 
+    /// # Errors
+    /// This function may fail if we failed to launch the executable.
+    fn spawn_piped(&self, stdin: Option<Stdio>) -> Result<PipedChild, Error> {
+        // Prepare the command, with a single pipe for both streams if they should be merged
+        let mut cmd: Command = self.command();
+        if let Some(stdin) = stdin { cmd.stdin(stdin); } else if self.stdin.is_none() { cmd.stdin(Stdio::null()); }
+        let merged: Option<io::PipeReader> = if self.merge_stderr {
+            let (reader, writer): (io::PipeReader, io::PipeWriter) = match io::pipe() {
+                Ok(pipe) => pipe,
+                Err(err) => { return Err(Error::SpawnError{ exec: self.exec.clone(), err }); },
+            };
+            let err_writer: io::PipeWriter = match writer.try_clone() {
+                Ok(writer) => writer,
+                Err(err)   => { return Err(Error::SpawnError{ exec: self.exec.clone(), err }); },
+            };
+            cmd.stdout(writer);
+            cmd.stderr(err_writer);
+            Some(reader)
+        } else {
+            cmd.stdout(Stdio::piped());
+            cmd.stderr(Stdio::piped());
+            None
+        };
+
+        // Run it
+        debug!("Running {:?}", cmd);
+        let mut handle: Child = match cmd.spawn() {
+            Ok(handle) => handle,
+            Err(err)   => { return Err(Error::SpawnError{ exec: self.exec.clone(), err }); },
+        };
+        // Drop our ends of the merged pipe, or else reading it never finishes
+        drop(cmd);
+
+        // Return the streams
+        let stdout: Box<dyn Send + Read> = match merged {
+            Some(reader) => Box::new(reader),
+            None         => Box::new(handle.stdout.take().expect("stdout of command is not piped")),
+        };
+        let stderr: Option<ChildStderr> = handle.stderr.take();
+        Ok((handle, stdout, stderr))
+    }
+
+    /// Spawns the command with its stdout and stderr inherited from us (see `ShellCommand::run()`).
+    /// 
+    /// # Arguments
+    /// - `stdin`: The stdin to give to the command (e.g., the output of a previous command), or `None` to give it the data set with `ShellCommand::set_stdin()` (or our stdin if there is none).
+    /// 
+    /// # Returns
+    /// The handle to the running command.
+    /// 
+    /// # Errors
+    /// This function may fail if we failed to launch the executable.
+    fn spawn(&self, stdin: Option<Stdio>) -> Result<Child, Error> {
+        // Prepare the command
+        let mut cmd: Command = self.command();
+        if let Some(stdin) = stdin { cmd.stdin(stdin); }
+        if self.merge_stderr { cmd.stderr(Stdio::from(std::io::stdout())); }
+
+        // Run it
+        debug!("Running {:?}", cmd);
+        match cmd.spawn() {
+            Ok(handle) => Ok(handle),
+            Err(err)   => Err(Error::SpawnError{ exec: self.exec.clone(), err }),
+        }
+    }
+
+    /// Spawns the command with its stdout (and stderr, if merged) written to a new pipe, such that it may be given to another command (see `ShellCommand::pipe()`).
+    /// 
+    /// # Arguments
+    /// - `stdin`: The stdin to give to the command (e.g., the output of a previous command), or `None` to give it the data set with `ShellCommand::set_stdin()` (or our stdin if there is none).
+    /// 
+    /// # Returns
+    /// The handle to the running command and the reading end of the pipe.
+    /// 
+    /// # Errors
+    /// This function may fail if we failed to create the pipe or to launch the executable.
+    fn spawn_into_pipe(&self, stdin: Option<Stdio>) -> Result<(Child, io::PipeReader), Error> {
+        let (reader, writer): (io::PipeReader, io::PipeWriter) = match io::pipe() {
+            Ok(pipe) => pipe,
+            Err(err) => { return Err(Error::SpawnError{ exec: self.exec.clone(), err }); },
+        };
+
+        // Prepare the command
+        let mut cmd: Command = self.command();
+        if let Some(stdin) = stdin { cmd.stdin(stdin); }
+        if self.merge_stderr {
+            match writer.try_clone() {
+                Ok(writer) => { cmd.stderr(writer); },
+                Err(err)   => { return Err(Error::SpawnError{ exec: self.exec.clone(), err }); },
+            }
+        }
+        cmd.stdout(writer);
+
+        // Run it (and drop our ends of the pipe, or else reading it never finishes)
+        debug!("Running {:?}", cmd);
+        match cmd.spawn() {
+            Ok(handle) => Ok((handle, reader)),
+            Err(err)   => Err(Error::SpawnError{ exec: self.exec.clone(), err }),
+        }
+    }
+
+    /// Runs the command, killing it if it is still running by the given deadline.
+    /// 
+    /// # Arguments
+    /// - `deadline`: The moment by which the command must be completed, or `None` to wait indefinitely.
+    /// 
+    /// # Returns
+    /// Why the command stopped running once it completes.
+    /// 
+    /// # Errors
+    /// This function may fail if we failed to launch the executable, or if it did not complete before the deadline.
+    fn run_until(&self, deadline: Option<Instant>) -> Result<ExitReason, Error> {
+        let mut handle: Child = self.spawn(None)?;
+        let stdin: Option<JoinHandle<io::Result<()>>> = self.stdin.clone().map(|data| write_all(handle.stdin.take(), data));
+        let status: ExitStatus = self.wait_until(&mut handle, deadline)?;
+        self.join_stdin(stdin)?;
+
+        // Return why it stopped
+        Ok(status.into())
+    }
+
+    /// Waits for the given (running) command to complete, killing it if it is still running by the given deadline.
+    /// 
+    /// # Arguments
+    /// - `handle`: The handle to the spawned command.
+    /// - `deadline`: The moment by which the command must be completed, or `None` to wait indefinitely.
+    /// 
+    /// # Returns
+    /// The exit status of the command once it completes.
+    /// 
+    /// # Errors
+    /// This function may fail if we failed to wait for the command, or if it did not complete before the deadline.
+    fn wait_until(&self, handle: &mut Child, deadline: Option<Instant>) -> Result<ExitStatus, Error> {
+        match deadline {
+            Some(deadline) => loop {
+                match handle.try_wait() {
+                    Ok(Some(status)) => { break Ok(status); },
+                    Ok(None)         => {},
+                    Err(err)         => { return Err(Error::WaitError{ exec: self.exec.clone(), err }); },
+                }
+
+                // Kill it if we're out of time
+                if Instant::now() >= deadline {
+                    debug!("Killing '{}' (deadline passed)", self.exec);
+                    if let Err(err) = handle.kill() { return Err(Error::WaitError{ exec: self.exec.clone(), err }); }
+                    if let Err(err) = handle.wait() { return Err(Error::WaitError{ exec: self.exec.clone(), err }); }
+                    return Err(Error::Timeout{ exec: self.exec.clone() });
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            },
+            None => match handle.wait() {
+                Ok(status) => Ok(status),
+                Err(err)   => Err(Error::WaitError{ exec: self.exec.clone(), err }),
+            },
+        }
+    }
+
+    /// Waits until all data has been written to the command's stdin (see `write_all()`).
+    /// 
+    /// # Arguments
+    /// - `stdin`: The handle of the thread writing the data, or `None` if there is none.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to write the data.
+    fn join_stdin(&self, stdin: Option<JoinHandle<io::Result<()>>>) -> Result<(), Error> {
+        match stdin.map(|stdin| stdin.join()) {
+            None | Some(Ok(Ok(_))) => Ok(()),
+            Some(Ok(Err(err)))     => Err(Error::InputWriteError{ exec: self.exec.clone(), err }),
+            Some(Err(_))           => Err(Error::InputWriteError{ exec: self.exec.clone(), err: io::Error::other("writer thread panicked") }),
+        }
+    }
+
     /// Runs the command that is build in this ShellCommand.
     /// 
     /// This variation does not return anything from the underlying command - only its return code.
     /// 
+    /// Note that the command is killed if the deadline of the current build passes (see `Installer::build_with_deadline()`).
+    /// 
+    /// # Returns
+    /// The return code of the command once it completes.
+    /// 
+    /// # Errors
+    /// This function may fail if we failed to even launch the executable in the first place, or if the build's deadline passed.
+    #[inline]
+    pub fn run(&self) -> Result<i32, Error> {
+        self.run_until(deadline()).map(|status| status.code())
+    }
+
+    /// Runs the command that is build in this ShellCommand, like `ShellCommand::run()`, but tells apart commands that exited by themselves and commands that were killed by a signal.
+    /// 
+    /// # Returns
+    /// An ExitReason with the return code of the command or the signal that terminated it.
+    /// 
+    /// # Errors
+    /// This function may fail if we failed to even launch the executable in the first place, or if the build's deadline passed.
+    #[inline]
+    pub fn run_status(&self) -> Result<ExitReason, Error> {
+        self.run_until(deadline())
+    }
+
+    /// Pipes the output of this ShellCommand into another one (like `foo | bar`).
+    /// 
+    /// # Arguments
+    /// - `next`: The ShellCommand that gets the stdout (and stderr, if merged) of this one as its stdin.
+    /// 
+    /// # Returns
+    /// A new Pipeline with both commands, which may be run with `Pipeline::run()` or `Pipeline::output()`.
+    #[inline]
+    pub fn pipe(self, next: ShellCommand) -> Pipeline {
+        Pipeline{ stages: vec![ self, next ] }
+    }
+
+
+
+    /// Runs the command that is build in this ShellCommand, unless we're doing a dry run.
+    /// 
+    /// This is a convenience wrapper around `ShellCommand::run()` for use in `Target::build()`, such that every target that shells out behaves the same when doing a dry run.
+    /// 
+    /// # Arguments
+    /// - `dry_run`: If true, does not actually run the command but instead just prints the command line it would run (see the `Display` implementation).
+    /// 
+    /// # Returns
+    /// The return code of the command once it completes, or `0` if `dry_run` is true.
+    /// 
+    /// # Errors
+    /// This function may fail if we failed to even launch the executable in the first place, or if the build's deadline passed.
+    pub fn run_dry(&self, dry_run: bool) -> Result<i32, Error> {
+        if dry_run {
+            println!("[dry_run] Would run {}", self);
+            return Ok(0);
+        }
+        self.run()
+    }
+
+    /// Runs the command that is build in this ShellCommand and fails if it does not succeed (within the given timeout, if any), unless we're doing a dry run.
+    /// 
+    /// This is the checked version of `ShellCommand::run_dry()`, for targets that fail the build if their command fails.
+    /// 
+    /// # Arguments
+    /// - `dry_run`: If true, does not actually run the command but instead just prints the command line it would run (see the `Display` implementation).
+    /// - `timeout`: The maximum time the command may take, if any.
+    /// 
+    /// # Errors
+    /// This function fails if we failed to launch the executable, if it timed out or if it returned a non-zero exit code.
+    pub fn run_dry_timeout_checked(&self, dry_run: bool, timeout: Option<Duration>) -> Result<(), Error> {
+        if dry_run {
+            println!("[dry_run] Would run {}", self);
+            return Ok(());
+        }
+        match timeout {
+            Some(timeout) => self.run_timeout_checked(timeout),
+            None          => self.run_checked(),
+        }
+    }
+
+    /// Runs the command that is build in this ShellCommand and captures what it writes to its stdout and stderr.
+    /// 
+    /// If `ShellCommand::set_merge_stderr()` is set, both streams are captured in `ShellOutput::stdout` in the order in which the command writes them. The command's stdin is closed.
+    /// 
+    /// Like `ShellCommand::run()`, the command is killed if the deadline of the current build passes (see `Installer::build_with_deadline()`).
+    /// 
+    /// # Returns
+    /// A ShellOutput with the return code and the captured output of the command once it completes.
+    /// 
+    /// # Errors
+    /// This function may fail if we failed to launch the executable, failed to read its output or if the build's deadline passed.
+    pub fn output(&self) -> Result<ShellOutput, Error> {
+        let (mut handle, stdout, stderr): PipedChild = self.spawn_piped(None)?;
+
+        // Write and read the streams while we wait for it to complete
+        let stdin: Option<JoinHandle<io::Result<()>>> = self.stdin.clone().map(|data| write_all(handle.stdin.take(), data));
+        let stdout: JoinHandle<io::Result<Vec<u8>>> = read_to_end(Some(stdout));
+        let stderr: JoinHandle<io::Result<Vec<u8>>> = read_to_end(stderr);
+        let status: ExitStatus = self.wait_until(&mut handle, deadline())?;
+        self.join_stdin(stdin)?;
+
+        // Collect the output
+        let mut output: ShellOutput = ShellOutput{ status: status.into(), stdout: vec![], stderr: vec![] };
+        for (stream, buf) in [ (stdout, &mut output.stdout), (stderr, &mut output.stderr) ] {
+            *buf = match stream.join() {
+                Ok(Ok(bytes)) => bytes,
+                Ok(Err(err))  => { return Err(Error::OutputReadError{ exec: self.exec.clone(), err }); },
+                Err(_)        => { return Err(Error::OutputReadError{ exec: self.exec.clone(), err: io::Error::other("reader thread panicked") }); },
+            };
+        }
+        Ok(output)
+    }
+
+    /// Runs the command that is build in this ShellCommand, passing everything it writes to the given callback line-by-line as it happens.
+    /// 
+    /// Lines are decoded leniently (replacing any invalid UTF-8) and given without their newline. If `ShellCommand::set_merge_stderr()` is set, all lines are given as `StreamLine::Stdout` in the order in which the command writes them. The command's stdin is closed.
+    /// 
+    /// Like `ShellCommand::run()`, the command is killed if the deadline of the current build passes (see `Installer::build_with_deadline()`).
+    /// 
+    /// # Arguments
+    /// - `callback`: The closure to call for every line (e.g., to forward it to a log).
+    /// 
     /// # Returns
     /// The return code of the command once it completes.
     /// 
     /// # Errors
-    /// This function may fail if we failed to even launch the executable in the first place.
+    /// This function may fail if we failed to launch the executable, failed to read its output or if the build's deadline passed.
+    #[inline]
+    pub fn run_streaming<F: FnMut(StreamLine)>(&self, callback: F) -> Result<i32, Error> { self.run_streaming_until(deadline(), callback) }
+
+    /// Runs the command that is build in this ShellCommand like `ShellCommand::run_streaming()`, killing it if it takes longer than the given timeout.
+    /// 
+    /// Like `ShellCommand::run()`, the command is also killed if the deadline of the current build passes earlier.
+    /// 
+    /// # Arguments
+    /// - `timeout`: The maximum time the command may take.
+    /// - `callback`: The closure to call for every line (e.g., to forward it to a log).
+    /// 
+    /// # Returns
+    /// The return code of the command once it completes.
+    /// 
+    /// # Errors
+    /// This function may fail if we failed to launch the executable, failed to read its output or if it timed out.
+    pub fn run_streaming_timeout<F: FnMut(StreamLine)>(&self, timeout: Duration, callback: F) -> Result<i32, Error> {
+        let until: Instant = Instant::now() + timeout;
+        self.run_streaming_until(Some(deadline().map(|d| d.min(until)).unwrap_or(until)), callback)
+    }
+
+    /// Runs the command, passing everything it writes to the given callback line-by-line and killing it if it is still running by the given deadline.
+    /// 
+    /// # Arguments
+    /// - `deadline`: The moment by which the command must be completed, or `None` to wait indefinitely.
+    /// - `callback`: The closure to call for every line.
+    /// 
+    /// # Returns
+    /// The return code of the command once it completes.
+    /// 
+    /// # Errors
+    /// This function may fail if we failed to launch the executable, failed to read its output or if it did not complete before the deadline.
+    fn run_streaming_until<F: FnMut(StreamLine)>(&self, deadline: Option<Instant>, mut callback: F) -> Result<i32, Error> {
+        let (mut handle, stdout, stderr): PipedChild = self.spawn_piped(None)?;
+
+        // Read both streams concurrently, but give the lines to the callback on this thread
+        let stdin: Option<JoinHandle<io::Result<()>>> = self.stdin.clone().map(|data| write_all(handle.stdin.take(), data));
+        let (sender, receiver): (Sender<io::Result<StreamLine>>, Receiver<io::Result<StreamLine>>) = mpsc::channel();
+        read_lines(Some(stdout), StreamLine::Stdout, sender.clone());
+        read_lines(stderr, StreamLine::Stderr, sender);
+        loop {
+            // Stop reading once the streams are closed or once we're out of time (in which case `wait_until()` kills the command)
+            let line: io::Result<StreamLine> = match deadline {
+                Some(deadline) => match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(line)                                                        => line,
+                    Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => { break; },
+                },
+                None => match receiver.recv() {
+                    Ok(line) => line,
+                    Err(_)   => { break; },
+                },
+            };
+            match line {
+                Ok(line) => { callback(line); },
+                Err(err) => {
+                    // Don't leave the command running
+                    let _ = handle.kill();
+                    let _ = handle.wait();
+                    return Err(Error::OutputReadError{ exec: self.exec.clone(), err });
+                },
+            }
+        }
+        let status: ExitStatus = self.wait_until(&mut handle, deadline)?;
+        self.join_stdin(stdin)?;
+
+        // Return the code
+        Ok(ExitReason::from(status).code())
+    }
+
+    /// Runs the command that is build in this ShellCommand, killing it if it takes longer than the given timeout.
+    /// 
+    /// Like `ShellCommand::run()`, the command is also killed if the deadline of the current build passes earlier.
+    /// 
+    /// # Arguments
+    /// - `timeout`: The maximum time the command may take.
+    /// 
+    /// # Returns
+    /// The return code of the command once it completes.
+    /// 
+    /// # Errors
+    /// This function may fail if we failed to even launch the executable in the first place, or if it timed out.
+    pub fn run_timeout(&self, timeout: Duration) -> Result<i32, Error> {
+        let until: Instant = Instant::now() + timeout;
+        self.run_until(Some(deadline().map(|d| d.min(until)).unwrap_or(until))).map(|status| status.code())
+    }
+
+    /// Runs the command that is build in this ShellCommand, re-running it if it fails (e.g., for flaky network commands).
+    /// 
+    /// The command is retried if it returns a non-zero exit code or could not be launched, waiting `backoff * attempt` before the next attempt. It is not retried once the deadline of the current build passes.
+    /// 
+    /// # Arguments
+    /// - `attempts`: The maximum number of times to run the command. `1` (or `0`) behaves like `ShellCommand::run()`.
+    /// - `backoff`: The time to wait after the first failed attempt. Every next failure waits that much longer.
+    /// 
+    /// # Returns
+    /// The return code of the first successful attempt, or of the last attempt if none succeeded.
+    /// 
+    /// # Errors
+    /// This function errors if the last attempt failed to launch the executable, or if the build's deadline passed.
+    pub fn run_with_retries(&self, attempts: usize, backoff: Duration) -> Result<i32, Error> {
+        let attempts: usize = attempts.max(1);
+        for attempt in 1..attempts {
+            match self.run() {
+                Ok(0)                           => { return Ok(0); },
+                Err(err @ Error::Timeout{ .. }) => { return Err(err); },
+                Ok(_code)                       => { debug!("'{}' returned exit code {} (attempt {}/{}); retrying", self.exec, _code, attempt, attempts); },
+                Err(_err)                       => { debug!("'{}' failed: {} (attempt {}/{}); retrying", self.exec, _err, attempt, attempts); },
+            }
+            thread::sleep(backoff * attempt as u32);
+        }
+        self.run()
+    }
+
+    /// Runs the command that is build in this ShellCommand, and fails if it does not succeed.
+    /// 
+    /// This is a convenience wrapper around `ShellCommand::run()` for the common case where a non-zero exit code should fail the build.
+    /// 
+    /// # Errors
+    /// This function fails if we failed to launch the executable, or if it returned a non-zero exit code.
+    pub fn run_checked(&self) -> Result<(), Error> {
+        match self.run()? {
+            0    => Ok(()),
+            code => Err(Error::NonZeroExit{ exec: self.exec.clone(), code }),
+        }
+    }
+
+    /// Runs the command that is build in this ShellCommand, and fails if it does not succeed.
+    /// 
+    /// This is a shorthand for `ShellCommand::run_checked()`.
+    /// 
+    /// # Errors
+    /// This function fails if we failed to launch the executable, or if it returned a non-zero exit code.
+    #[inline]
+    pub fn check(&self) -> Result<(), Error> { self.run_checked() }
+
+    /// Runs the command that is build in this ShellCommand, and fails if it does not succeed within the given timeout.
+    /// 
+    /// This is the checked version of `ShellCommand::run_timeout()`.
+    /// 
+    /// # Arguments
+    /// - `timeout`: The maximum time the command may take.
+    /// 
+    /// # Errors
+    /// This function fails if we failed to launch the executable, if it timed out or if it returned a non-zero exit code.
+    pub fn run_timeout_checked(&self, timeout: Duration) -> Result<(), Error> {
+        match self.run_timeout(timeout)? {
+            0    => Ok(()),
+            code => Err(Error::NonZeroExit{ exec: self.exec.clone(), code }),
+        }
+    }
+}
+
+impl Display for ShellCommand {
+    /// Renders the command line that this ShellCommand runs, as it would be written in a POSIX-like shell.
+    /// 
+    /// This includes the environment variables that are set (in alphabetical order), but not the ones that are removed. If the environment is cleared, this is rendered as a call to `env -i`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        if self.clear_env { write!(f, "env -i ")?; }
+        let mut envs: Vec<(&String, &String)> = self.envs.iter().collect();
+        envs.sort();
+        for (name, value) in envs {
+            write!(f, "{}={} ", name, quote(value))?;
+        }
+        write!(f, "{}", quote(&self.exec))?;
+        for arg in &self.args {
+            write!(f, " {}", quote(arg))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ShellCommand {
+    type Err = Error;
+
+    /// Parses a command line into a ShellCommand, where the first word is the executable and the rest its arguments.
+    /// 
+    /// Words are split like a POSIX-like shell would (e.g., `sh -c "echo hi"` has the arguments `-c` and `echo hi`), but nothing is expanded. Use `ShellCommand::shell()` instead to have the command line interpreted by an actual shell.
+    /// 
+    /// # Errors
+    /// This function errors if the command line is empty or if a quote is not closed.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words: std::vec::IntoIter<String> = split_words(s)?.into_iter();
+        match words.next() {
+            Some(exec) => Ok(Self::with_args(exec, words)),
+            None       => Err(Error::EmptyCommandLine),
+        }
+    }
+}
+
+
+
+/// Defines a series of ShellCommands where the output of every command is given as input to the next one (see `ShellCommand::pipe()`).
+/// 
+/// Only the first command gets the data set with `ShellCommand::set_stdin()` (if any). Whether to merge stderr is respected per command.
+#[derive(Clone, Debug)]
+pub struct Pipeline {
+    /// The commands in the pipeline, in order.
+    stages : Vec<ShellCommand>,
+}
+
+impl Pipeline {
+    /// Adds another command to the end of the pipeline.
+    /// 
+    /// # Arguments
+    /// - `next`: The ShellCommand that gets the stdout of the current last command as its stdin.
+    /// 
+    /// # Returns
+    /// The same `self` as given for chaining purposes.
     #[inline]
+    pub fn pipe(mut self, next: ShellCommand) -> Self {
+        self.stages.push(next);
+        self
+    }
+
+
+
+    /// Returns the commands in this pipeline, in order.
+    #[inline]
+    pub fn stages(&self) -> &[ShellCommand] { &self.stages }
+
+
+
+    /// Spawns all but the last command in the pipeline.
+    /// 
+    /// # Arguments
+    /// - `null_stdin`: Whether to give the first command no stdin if there is no data to give it (true) or to give it ours (false).
+    /// 
+    /// # Returns
+    /// The handles to the running commands, the thread writing the data to the first one (if any) and the stdin for the last command.
+    /// 
+    /// # Errors
+    /// This function errors (with `ShellCommandError::PipelineStage`) if we failed to launch any of the commands. Those that were already launched are killed.
+    fn spawn_stages(&self, null_stdin: bool) -> Result<SpawnedStages, Error> {
+        let mut children : Vec<Child>                         = Vec::with_capacity(self.stages.len());
+        let mut stdin    : Option<JoinHandle<io::Result<()>>> = None;
+        let mut next     : Option<io::PipeReader>             = None;
+        for (i, stage) in self.stages[..self.stages.len() - 1].iter().enumerate() {
+            let input: Option<Stdio> = match next.take() {
+                Some(reader)                                => Some(reader.into()),
+                None if null_stdin && stage.stdin.is_none() => Some(Stdio::null()),
+                None                                        => None,
+            };
+            let (mut handle, reader): (Child, io::PipeReader) = match stage.spawn_into_pipe(input) {
+                Ok(res)  => res,
+                Err(err) => {
+                    Self::kill(children);
+                    return Err(Error::PipelineStage{ index: i, err: Box::new(err) });
+                },
+            };
+            if i == 0 { stdin = stage.stdin.clone().map(|data| write_all(handle.stdin.take(), data)); }
+            children.push(handle);
+            next = Some(reader);
+        }
+        Ok((children, stdin, next.expect("Pipeline has less than two stages").into()))
+    }
+
+    /// Waits for all commands in the pipeline to complete.
+    /// 
+    /// # Arguments
+    /// - `children`: The handles to all but the last command.
+    /// - `last`: The handle to the last command.
+    /// - `stdin`: The thread writing the data to the first command, if any.
+    /// - `deadline`: The moment by which the commands must be completed, or `None` to wait indefinitely.
+    /// 
+    /// # Returns
+    /// The exit status of the last command.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to wait for any of the commands or if they did not complete before the deadline. Errors of any but the last command, including non-zero exit codes, are wrapped in a `ShellCommandError::PipelineStage`. Commands killed by a signal (e.g., because a later command stopped reading their output) are not considered failed.
+    fn wait(&self, children: Vec<Child>, mut last: Child, mut stdin: Option<JoinHandle<io::Result<()>>>, deadline: Option<Instant>) -> Result<ExitStatus, Error> {
+        let last_stage: &ShellCommand = &self.stages[self.stages.len() - 1];
+        let status: ExitStatus = match last_stage.wait_until(&mut last, deadline) {
+            Ok(status) => status,
+            Err(err)   => {
+                Self::kill(children);
+                return Err(err);
+            },
+        };
+
+        // Check the other stages, too
+        let mut children: std::vec::IntoIter<Child> = children.into_iter();
+        for (i, stage) in self.stages.iter().enumerate().take(self.stages.len() - 1) {
+            let mut handle: Child = children.next().expect("Fewer children than stages");
+            let res: Result<(), Error> = match stage.wait_until(&mut handle, deadline) {
+                Ok(status) => match ExitReason::from(status) {
+                    ExitReason::Code(0) | ExitReason::Signal(_) => Ok(()),
+                    ExitReason::Code(code)                      => Err(Error::NonZeroExit{ exec: stage.exec.clone(), code }),
+                },
+                Err(err) => Err(err),
+            };
+            let res: Result<(), Error> = res.and_then(|_| if i == 0 { stage.join_stdin(stdin.take()) } else { Ok(()) });
+            if let Err(err) = res {
+                Self::kill(children.collect());
+                return Err(Error::PipelineStage{ index: i, err: Box::new(err) });
+            }
+        }
+        Ok(status)
+    }
+
+    /// Kills the given commands, ignoring any errors (since we're already failing).
+    /// 
+    /// # Arguments
+    /// - `children`: The handles to the commands to kill.
+    fn kill(children: Vec<Child>) {
+        for mut handle in children {
+            let _ = handle.kill();
+            let _ = handle.wait();
+        }
+    }
+
+
+
+    /// Runs all commands in the pipeline.
+    /// 
+    /// The last command writes to our stdout and stderr like `ShellCommand::run()`. All commands are killed if the deadline of the current build passes (see `Installer::build_with_deadline()`).
+    /// 
+    /// # Returns
+    /// The return code of the last command once all commands complete.
+    /// 
+    /// # Errors
+    /// This function may fail if we failed to launch any of the commands, if any of them but the last returned a non-zero exit code or if the build's deadline passed.
     pub fn run(&self) -> Result<i32, Error> {
-        
+        let (children, stdin, input): SpawnedStages = self.spawn_stages(false)?;
+        let last: Child = match self.stages[self.stages.len() - 1].spawn(Some(input)) {
+            Ok(last) => last,
+            Err(err) => {
+                Self::kill(children);
+                return Err(err);
+            },
+        };
+        let status: ExitStatus = self.wait(children, last, stdin, deadline())?;
+        Ok(ExitReason::from(status).code())
+    }
+
+    /// Runs all commands in the pipeline and captures what the last one writes to its stdout and stderr.
+    /// 
+    /// Apart from that, this behaves like `Pipeline::run()`. If the first command gets no data to write to its stdin, its stdin is closed.
+    /// 
+    /// # Returns
+    /// A ShellOutput with the return code and the captured output of the last command once all commands complete.
+    /// 
+    /// # Errors
+    /// This function may fail if we failed to launch any of the commands, failed to read the output, if any of them but the last returned a non-zero exit code or if the build's deadline passed.
+    pub fn output(&self) -> Result<ShellOutput, Error> {
+        let last_stage: &ShellCommand = &self.stages[self.stages.len() - 1];
+        let (children, stdin, input): SpawnedStages = self.spawn_stages(true)?;
+        let (last, stdout, stderr): PipedChild = match last_stage.spawn_piped(Some(input)) {
+            Ok(res)  => res,
+            Err(err) => {
+                Self::kill(children);
+                return Err(err);
+            },
+        };
+
+        // Read the output while we wait
+        let stdout: JoinHandle<io::Result<Vec<u8>>> = read_to_end(Some(stdout));
+        let stderr: JoinHandle<io::Result<Vec<u8>>> = read_to_end(stderr);
+        let status: ExitStatus = self.wait(children, last, stdin, deadline())?;
+
+        // Collect the output
+        let mut output: ShellOutput = ShellOutput{ status: status.into(), stdout: vec![], stderr: vec![] };
+        for (stream, buf) in [ (stdout, &mut output.stdout), (stderr, &mut output.stderr) ] {
+            *buf = match stream.join() {
+                Ok(Ok(bytes)) => bytes,
+                Ok(Err(err))  => { return Err(Error::OutputReadError{ exec: last_stage.exec.clone(), err }); },
+                Err(_)        => { return Err(Error::OutputReadError{ exec: last_stage.exec.clone(), err: io::Error::other("reader thread panicked") }); },
+            };
+        }
+        Ok(output)
+    }
+}
+
+impl Display for Pipeline {
+    /// Renders the pipeline as it would be written in a POSIX-like shell (e.g., `echo hello | tr a-z A-Z`).
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        for (i, stage) in self.stages.iter().enumerate() {
+            if i > 0 { write!(f, " | ")?; }
+            write!(f, "{}", stage)?;
+        }
+        Ok(())
     }
 }
@@ -15,12 +15,96 @@
 //!   specification.
 // 
 
+use std::any::Any;
 use std::error::Error;
-use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Instant;
 
 use crate::errors::TargetError;
+use crate::observer::{BuildObserver, NoOpObserver};
+use crate::shell::deadline;
 use crate::view::{EffectView, ViewFilter};
-use crate::cache::Cache;
+use crate::cache::{Cache, LastEditedTime};
+
+
+/***** CONSTANTS *****/
+/// The resource class of targets that are not tagged with one (see `Target::resource_class()`).
+pub const DEFAULT_RESOURCE_CLASS: &str = "default";
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Checks whether the given effect has changed on behalf of the given target, notifying the observer if it did.
+/// 
+/// # Arguments
+/// - `name`: The name of the target that depends on the effect.
+/// - `effect`: The Effect to check.
+/// - `observer`: The BuildObserver to notify if the effect has changed.
+/// 
+/// # Returns
+/// Whether the effect has changed.
+/// 
+/// # Errors
+/// This function errors if we failed to check the effect.
+fn effect_changed(name: &str, effect: &dyn Effect, observer: &dyn BuildObserver) -> Result<bool, TargetError> {
+    let changed: bool = match effect.has_changed() {
+        Ok(changed) => changed,
+        Err(err)    => { return Err(TargetError::HasChangedError{ effect_name: effect.name().into(), err }); }
+    };
+    if changed { observer.on_effect_changed(name, effect.name()); }
+    Ok(changed)
+}
+
+/// Checks whether any of the effects that the given target depends on (or any of its inputs) have changed, assuming that its dependencies have already been made.
+/// 
+/// # Arguments
+/// - `target`: The Target to check the dependencies of.
+/// - `force`: If 'true', the target is considered outdated regardless.
+/// - `observer`: The BuildObserver to notify of changed effects.
+/// 
+/// # Returns
+/// Whether the target is outdated (see `Target::build_deps()`).
+/// 
+/// # Errors
+/// This function errors if we failed to check any of the effects.
+fn deps_changed<T: ?Sized + Target>(target: &T, force: bool, observer: &dyn BuildObserver) -> Result<bool, TargetError> {
+    let mut outdated: bool = force;
+    for view in target.deps() {
+        for effect in view { outdated |= effect_changed(target.name(), &**effect, observer)?; }
+    }
+    for effect in target.inputs() { outdated |= effect_changed(target.name(), &**effect, observer)?; }
+    Ok(outdated)
+}
+
+/// Builds and commits the given target if it is outdated, unless the deadline of the build has passed.
+/// 
+/// # Arguments
+/// - `target`: The Target to build.
+/// - `outdated`: Whether the target is outdated (see `Target::build_deps()`).
+/// - `os`: The target OS that we intend to build.
+/// - `arch`: The target architecture that we intend to build.
+/// - `dry_run`: If 'true', prints what would be done instead of actually executing the commands.
+/// - `observer`: The BuildObserver to notify of the warnings of the target, if it counts them.
+/// 
+/// # Returns
+/// Whether the target was built.
+/// 
+/// # Errors
+/// This function errors if we failed to build or commit the target, or if the deadline passed.
+fn build_outdated<T: ?Sized + Target>(target: &T, outdated: bool, os: OperatingSystem, arch: Architecture, dry_run: bool, observer: &dyn BuildObserver) -> Result<bool, TargetError> {
+    if !outdated { return Ok(false); }
+
+    // Do not start anything new if we're out of time
+    if deadline().map(|d| Instant::now() >= d).unwrap_or(false) { return Err(TargetError::Timeout{ name: target.name().into() }); }
+    let res: Result<bool, TargetError> = target.build(os, arch, dry_run).and_then(|_| target.commit(dry_run)).map(|_| true);
+    if let Some(count) = target.warnings() { observer.on_target_warnings(target.name(), count); }
+    res
+}
+
+
+
 
 
 /***** LIBRARY *****/
@@ -111,7 +195,7 @@ impl Architecture {
 
 
 
-/// Defines a named Dependency, Effect or Target.
+/// Defines a named Effect or Target.
 pub trait Named {
     // Child-provided
     /// Returns the identifier of this Effect.
@@ -120,8 +204,10 @@ pub trait Named {
 
 
 
-/// Defines an Effect, which is something that a Target produces. Typically (though not always), an Effect is also a Dependency such that future target may use it themselves.
-pub trait Effect: Named {
+/// Defines an Effect, which is something that a Target produces. Typically (though not always), an Effect is also a dependency (through an `EffectView`) such that future targets may use it themselves.
+/// 
+/// Effects must be `Send + Sync`, such that targets may be made from multiple threads at once (see `Builder::parallel()`).
+pub trait Effect: Named + Send + Sync {
     // Child-provided
     /// Determines if the depedency has been updated since the last time.
     /// 
@@ -132,7 +218,7 @@ pub trait Effect: Named {
     /// 
     /// # Errors
     /// This function may error for its own reasons.
-    fn has_changed(&self) -> Result<bool, Box<dyn Error>>;
+    fn has_changed(&self) -> Result<bool, Box<dyn Error + Send + Sync>>;
 
     /// Updates the underlying mechanisms to "commit" the current state of the dependency as the 'last' state.
     /// 
@@ -145,13 +231,41 @@ pub trait Effect: Named {
     /// 
     /// # Errors
     /// If we failed  to update the underlying mechanisms, this function may throw an error. Note, however, that the change must also be uncommitted if this function errors.
-    fn commit_change(&self, dry_run: bool) -> Result<(), Box<dyn Error>>;
+    fn commit_change(&self, dry_run: bool) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    /// Returns the time the Effect was last changed, if it has such a notion.
+    /// 
+    /// This can be used to, e.g., report when something was built, or to find the newest of a set of effects.
+    /// 
+    /// By default, this returns `None`.
+    /// 
+    /// # Returns
+    /// The LastEditedTime of the Effect, or `None` if it does not have one (or it does not exist (yet)).
+    /// 
+    /// # Errors
+    /// This function may error for its own reasons.
+    #[inline]
+    fn last_changed(&self) -> Result<Option<LastEditedTime>, Box<dyn Error + Send + Sync>> { Ok(None) }
+
+
+
+    /// Returns this Effect as an Any, such that it may be downcasted to its concrete type.
+    /// 
+    /// This is useful when you obtained an effect through an EffectView (e.g., a `&Box<dyn Effect>`) and want to access type-specific information (e.g., a `File`'s path).
+    /// 
+    /// Typically, implementations will simply return `self`.
+    /// 
+    /// # Returns
+    /// A reference to self as an `Any`.
+    fn as_any(&self) -> &dyn Any;
 }
 
 
 
 /// Defines a Target, which is something that compiles, installs or runs something else.
-pub trait Target: Named {
+/// 
+/// Targets must be `Send + Sync`, such that independent targets may be made from multiple threads at once (see `Builder::parallel()`, which requires the `rayon` feature).
+pub trait Target: Named + Send + Sync {
     // Globally available
     /// Builds the target's dependencies, itself and then commits the results to cache.
     /// 
@@ -166,23 +280,60 @@ pub trait Target: Named {
     /// 
     /// # Errors
     /// This function errors if any of the three other functions would error.
+    #[inline]
     fn make(&self, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool) -> Result<(), TargetError> {
-        // Call the dependencies first, to find out if anything has to happen.
-        let outdated: bool = self.build_deps(os, arch, force, dry_run)?;
+        self.make_observed(os, arch, force, dry_run, &NoOpObserver)
+    }
 
-        // Next, if it does, run the build & commit
-        if outdated {
-            self.build(os, arch, dry_run)?;
-            self.commit(dry_run)?;
-        }
+    /// Builds the target's dependencies, itself and then commits the results to cache, while notifying the given observer of what happens.
+    /// 
+    /// # Arguments
+    /// - `os`: The target OS that we intend to build.
+    /// - `arch`: The target architecture that we intend to build.
+    /// - `force`: If 'true', always builds all targets instead of only when there is no (detected) change.
+    /// - `dry_run`: If 'true', prints what would be done instead of actually executing the commands. Note that this is an imperfect simulation, since effect changes cannot be accurately detected without actually changing them.
+    /// - `observer`: The BuildObserver to notify of the events during the build (including those of the dependencies).
+    /// 
+    /// # Errors
+    /// This function errors if any of the three other functions would error.
+    fn make_observed(&self, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool, observer: &dyn BuildObserver) -> Result<(), TargetError> {
+        observer.on_target_start(self.name());
+
+        // Call the dependencies first, to find out if anything has to happen. If it does, run the build & commit
+        let res: Result<bool, TargetError> = self.build_deps_observed(os, arch, force, dry_run, observer).and_then(|outdated| build_outdated(self, outdated, os, arch, dry_run, observer));
+        observer.on_target_finish(self.name(), &res);
 
         // Done
-        Ok(())
+        res.map(|_| ())
+    }
+
+    /// Makes only this target, assuming that its dependencies have already been made, while notifying the given observer of what happens.
+    /// 
+    /// This is like `Target::make_observed()`, except that the dependencies are only checked for changes instead of made. It is used by executors that make the dependencies themselves (e.g., the parallel one; see `Builder::parallel()`).
+    /// 
+    /// # Arguments
+    /// - `os`: The target OS that we intend to build.
+    /// - `arch`: The target architecture that we intend to build.
+    /// - `force`: If 'true', always builds the target instead of only when there is no (detected) change.
+    /// - `dry_run`: If 'true', prints what would be done instead of actually executing the commands. Note that this is an imperfect simulation, since effect changes cannot be accurately detected without actually changing them.
+    /// - `observer`: The BuildObserver to notify of the events while making this target.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to check the dependencies for changes, or to build or commit this target.
+    fn make_ready_observed(&self, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool, observer: &dyn BuildObserver) -> Result<(), TargetError> {
+        observer.on_target_start(self.name());
+
+        // Only check the dependencies, then run the build & commit if anything changed
+        let res: Result<bool, TargetError> = deps_changed(self, force, observer).and_then(|outdated| build_outdated(self, outdated, os, arch, dry_run, observer));
+        observer.on_target_finish(self.name(), &res);
+
+        // Done
+        res.map(|_| ())
     }
 
     /// Builds any dependencies that this Target has defined. After this operation, it will be safe to call `Target::build()`.
     /// 
-    /// Uses the `Target::deps()` function to determine those.
+    /// Uses the `Target::deps()` function to determine those. Additionally, the Target's own `Target::inputs()` are checked for changes.
     /// 
     /// # Arguments
     /// - `os`: The target OS that we intend to build.
@@ -195,27 +346,44 @@ pub trait Target: Named {
     /// 
     /// # Errors
     /// This function errors if we failed to build any of the targets this target depends on.
+    #[inline]
     fn build_deps(&self, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool) -> Result<bool, TargetError> {
+        self.build_deps_observed(os, arch, force, dry_run, &NoOpObserver)
+    }
+
+    /// Builds any dependencies that this Target has defined, while notifying the given observer of what happens.
+    /// 
+    /// # Arguments
+    /// - `os`: The target OS that we intend to build.
+    /// - `arch`: The target architecture that we intend to build.
+    /// - `force`: If 'true', always builds all dependencies instead of only when there is no (detected) change to their dependencies.
+    /// - `dry_run`: If 'true', prints what would be done instead of actually executing the commands. Note that this is an imperfect simulation, since effect changes cannot be accurately detected without actually changing them.
+    /// - `observer`: The BuildObserver to notify of the events during the build (including those of the dependencies).
+    /// 
+    /// # Returns
+    /// Whether this Target should be rebuild or not (see `Target::build_deps()`).
+    /// 
+    /// # Errors
+    /// This function errors if we failed to build any of the targets this target depends on.
+    fn build_deps_observed(&self, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool, observer: &dyn BuildObserver) -> Result<bool, TargetError> {
         // Iterate over all of the views
         let mut outdated: bool = force;
         for view in self.deps() {
             // Build the target behind this view first.
-            view.target.make(os, arch, force, dry_run)?;
+            view.target.make_observed(os, arch, force, dry_run, observer)?;
 
             // Analyse if any of the dependent dependencies have changed.
-            for effect in view {
-                outdated |= match effect.has_changed() {
-                    Ok(outdated) => outdated,
-                    Err(err)     => { return Err(TargetError::HasChangedError{ effect_name: effect.name().into(), err }); }
-                };
-            }
+            for effect in view { outdated |= effect_changed(self.name(), &**effect, observer)?; }
         }
 
+        // Analyse if any of our own inputs have changed.
+        for effect in self.inputs() { outdated |= effect_changed(self.name(), &**effect, observer)?; }
+
         // Done, everything is built, but we only return if outdated if any effect has been changed or if we `--force`ed.
         Ok(outdated)
     }
 
-    /// Commits any changes to our own effects to the cache (or whatever we use to keep track of changes).
+    /// Commits any changes to our own effects (and inputs) to the cache (or whatever we use to keep track of changes).
     /// 
     /// # Arguments
     /// - `dry_run`: If 'true', prints what would be done instead of actually executing the commands. Note that this is an imperfect simulation, since effect changes cannot be accurately detected without actually changing them.
@@ -224,7 +392,7 @@ pub trait Target: Named {
     /// This function errors if we failed to commit any of our own effects.
     fn commit(&self, dry_run: bool) -> Result<(), TargetError> {
         // Go through our own effects and update 'em
-        for effect in self.effects() {
+        for effect in self.effects().iter().chain(self.inputs()) {
             if let Err(err) = effect.commit_change(dry_run) { return Err(TargetError::CommitError{ effect_name: effect.name().into(), err }); }
         }
 
@@ -250,6 +418,24 @@ pub trait Target: Named {
     /// This function errors if we failed to build this target.
     fn build(&self, os: OperatingSystem, arch: Architecture, dry_run: bool) -> Result<(), TargetError>;
 
+    /// Returns a short identifier of the kind of this Target (e.g., `"cargo"`), which is used when reporting about it.
+    /// 
+    /// By default, this returns `"custom"`.
+    #[inline]
+    fn kind(&self) -> &str { "custom" }
+
+    /// Returns the number of warnings emitted by the last `Target::build()` of this Target, if it counts them.
+    /// 
+    /// By default, this returns `None`.
+    #[inline]
+    fn warnings(&self) -> Option<usize> { None }
+
+    /// Returns the resource class of this Target, which limits how many targets of the same class are made at once when building in parallel (see `Builder::resource_limit()`).
+    /// 
+    /// By default, this returns `DEFAULT_RESOURCE_CLASS`, which is shared by all targets that are not tagged otherwise.
+    #[inline]
+    fn resource_class(&self) -> &str { DEFAULT_RESOURCE_CLASS }
+
 
 
     /// Returns a TargetView on this Target's effects.
@@ -266,6 +452,7 @@ pub trait Target: Named {
         EffectView{
             target  : self,
             filters : vec![ ViewFilter::All ],
+            unique  : false,
         }
     }
     /// Returns a TargetView on this Target's effects.
@@ -284,7 +471,8 @@ pub trait Target: Named {
     {
         EffectView{
             target  : self,
-            filters : vec![ ViewFilter::Allow{ names: names.into() } ],
+            filters : vec![ ViewFilter::Allow{ names: names.into(), case_insensitive: false } ],
+            unique  : false,
         }
     }
 
@@ -294,9 +482,55 @@ pub trait Target: Named {
     /// Returns a list of dependencies of this Target. The ordering of them is irrelevant.
     /// 
     /// Note that they are as EffectViews instead of simple Dependencies to allow the target to only depend on a subset of a dependency.
-    fn deps(&self) -> &[EffectView];
+    fn deps(&self) -> &[EffectView<'_>];
     /// Returns a list of effects that this Target produces. The ordering of them is irrelevant.
     fn effects(&self) -> &[Box<dyn Effect>];
+
+    /// Returns a list of inputs of this Target that are not produced by any other Target (e.g., a hash of its own configuration). The ordering of them is irrelevant.
+    /// 
+    /// If any of them has changed, the Target is rebuild. They are committed together with the Target's effects.
+    /// 
+    /// By default, a Target has no such inputs.
+    #[inline]
+    fn inputs(&self) -> &[Box<dyn Effect>] { &[] }
+}
+
+
+
+// Allow references to targets to be used as targets themselves, such that targets depended upon by others can still be registered in an Installer.
+impl<T: ?Sized + Named> Named for &T {
+    #[inline]
+    fn name(&self) -> &str { (**self).name() }
+}
+impl<T: ?Sized + Target> Target for &T {
+    #[inline]
+    fn make(&self, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool) -> Result<(), TargetError> { (**self).make(os, arch, force, dry_run) }
+    #[inline]
+    fn make_observed(&self, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool, observer: &dyn BuildObserver) -> Result<(), TargetError> { (**self).make_observed(os, arch, force, dry_run, observer) }
+    #[inline]
+    fn make_ready_observed(&self, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool, observer: &dyn BuildObserver) -> Result<(), TargetError> { (**self).make_ready_observed(os, arch, force, dry_run, observer) }
+    #[inline]
+    fn build_deps(&self, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool) -> Result<bool, TargetError> { (**self).build_deps(os, arch, force, dry_run) }
+    #[inline]
+    fn build_deps_observed(&self, os: OperatingSystem, arch: Architecture, force: bool, dry_run: bool, observer: &dyn BuildObserver) -> Result<bool, TargetError> { (**self).build_deps_observed(os, arch, force, dry_run, observer) }
+    #[inline]
+    fn commit(&self, dry_run: bool) -> Result<(), TargetError> { (**self).commit(dry_run) }
+
+    #[inline]
+    fn build(&self, os: OperatingSystem, arch: Architecture, dry_run: bool) -> Result<(), TargetError> { (**self).build(os, arch, dry_run) }
+    #[inline]
+    fn kind(&self) -> &str { (**self).kind() }
+    #[inline]
+    fn warnings(&self) -> Option<usize> { (**self).warnings() }
+    #[inline]
+    fn resource_class(&self) -> &str { (**self).resource_class() }
+
+    #[inline]
+    fn deps(&self) -> &[EffectView<'_>] { (**self).deps() }
+    #[inline]
+    fn effects(&self) -> &[Box<dyn Effect>] { (**self).effects() }
+    #[inline]
+    fn inputs(&self) -> &[Box<dyn Effect>] { (**self).inputs() }
 }
 
 
@@ -376,5 +610,5 @@ pub trait TargetBuilder<'a> {
     /// 
     /// # Panics
     /// Note that this function may panic due to any of the other factory methods producing invalid targets.
-    fn build(self, cache: Rc<Cache>) -> Result<Self::Target, Box<dyn Error>>;
+    fn build(self, cache: Arc<Cache>) -> Result<Self::Target, Box<dyn Error>>;
 }
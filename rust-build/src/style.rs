@@ -4,7 +4,7 @@
 //  Created:
 //    20 Sep 2022, 22:12:01
 //  Last edited:
-//    20 Sep 2022, 22:23:58
+//    15 Oct 2026, 11:32:40
 //  Auto updated?
 //    Yes
 // 
@@ -13,18 +13,140 @@
 //!   installer's style.
 // 
 
+use std::path::Path;
+
+use console::Color;
+use serde::Deserialize;
+
+pub use crate::errors::StyleError as Error;
+
+
+/***** HELPER FUNCTIONS *****/
+/// Parses the given colour name to a Color.
+/// 
+/// # Arguments
+/// - `path`: The path of the file we're parsing (used for debugging).
+/// - `field`: The name of the field we're parsing (used for debugging).
+/// - `colour`: The name of the colour to parse.
+/// 
+/// # Returns
+/// The parsed Color.
+/// 
+/// # Errors
+/// This function errors if the colour name is unknown.
+fn parse_colour(path: &Path, field: &str, colour: &str) -> Result<Color, Error> {
+    match colour.to_lowercase().as_str() {
+        "black"   => Ok(Color::Black),
+        "red"     => Ok(Color::Red),
+        "green"   => Ok(Color::Green),
+        "yellow"  => Ok(Color::Yellow),
+        "blue"    => Ok(Color::Blue),
+        "magenta" => Ok(Color::Magenta),
+        "cyan"    => Ok(Color::Cyan),
+        "white"   => Ok(Color::White),
+        _         => Err(Error::UnknownColour{ path: path.into(), field: field.into(), colour: colour.into() }),
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines the contents of a style file. Every field is optional, and falls back to its default if omitted.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StyleFile {
+    /// The colour used for successes.
+    success_colour   : Option<String>,
+    /// The colour used for errors.
+    error_colour     : Option<String>,
+    /// The colour used to highlight things (e.g., target names).
+    highlight_colour : Option<String>,
+
+    /// The symbol printed in front of successes.
+    success_symbol : Option<String>,
+    /// The symbol printed in front of errors.
+    error_symbol   : Option<String>,
+}
+
+
+
+
 
 /***** LIBRARY *****/
 /// Contains information about how the Installer should look like.
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct InstallerStyle {
-    
+    /// The colour used for successes.
+    pub success_colour   : Color,
+    /// The colour used for errors.
+    pub error_colour     : Color,
+    /// The colour used to highlight things (e.g., target names).
+    pub highlight_colour : Color,
+
+    /// The symbol printed in front of successes.
+    pub success_symbol : String,
+    /// The symbol printed in front of errors.
+    pub error_symbol   : String,
 }
 
 impl Default for InstallerStyle {
     #[inline]
     fn default() -> Self {
         Self {
-            
+            success_colour   : Color::Green,
+            error_colour     : Color::Red,
+            highlight_colour : Color::Cyan,
+
+            success_symbol : "✓".into(),
+            error_symbol   : "✗".into(),
         }
     }
 }
+
+impl InstallerStyle {
+    /// Constructor for the InstallerStyle that reads it from the given file.
+    /// 
+    /// The file is parsed as JSON if it has the `.json` extension, or as TOML otherwise. It may define any of the fields `success_colour`, `error_colour`, `highlight_colour`, `success_symbol` and `error_symbol`; any fields that are omitted keep their default value. Colours are given by name (e.g., `"red"`).
+    /// 
+    /// # Arguments
+    /// - `path`: The path to the style file to read (e.g., `.installer-style.toml`).
+    /// 
+    /// # Returns
+    /// A new InstallerStyle instance.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to read or parse the file, or if it contains an unknown colour name.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path: &Path = path.as_ref();
+
+        // Read & parse the file
+        let raw: String = match std::fs::read_to_string(path) {
+            Ok(raw)  => raw,
+            Err(err) => { return Err(Error::FileReadError{ path: path.into(), err }); },
+        };
+        let file: StyleFile = if path.extension().map(|e| e == "json").unwrap_or(false) {
+            match serde_json::from_str(&raw) {
+                Ok(file) => file,
+                Err(err) => { return Err(Error::JsonParseError{ path: path.into(), err }); },
+            }
+        } else {
+            match toml::from_str(&raw) {
+                Ok(file) => file,
+                Err(err) => { return Err(Error::TomlParseError{ path: path.into(), err }); },
+            }
+        };
+
+        // Overwrite the defaults with whatever is given
+        let mut res: Self = Self::default();
+        if let Some(colour) = file.success_colour   { res.success_colour   = parse_colour(path, "success_colour", &colour)?; }
+        if let Some(colour) = file.error_colour     { res.error_colour     = parse_colour(path, "error_colour", &colour)?; }
+        if let Some(colour) = file.highlight_colour { res.highlight_colour = parse_colour(path, "highlight_colour", &colour)?; }
+        if let Some(symbol) = file.success_symbol { res.success_symbol = symbol; }
+        if let Some(symbol) = file.error_symbol   { res.error_symbol   = symbol; }
+
+        // Done
+        Ok(res)
+    }
+}
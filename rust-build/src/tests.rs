@@ -13,10 +13,31 @@
 //!   determine what we want to do.
 // 
 
+use std::collections::HashSet;
+use std::error::Error;
 use std::path::PathBuf;
 use std::process::{Child, Command, ExitStatus};
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "rayon")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
-use console::style;
+use console::{style, Color};
+
+use crate::cache::{Cache, CacheBackend, CacheEntry, CacheGuard, CacheStats, FsBackend, InMemoryBackend, LastEditedTime, FORMAT_VERSION, LOCK_FILE};
+use crate::errors::{BuildError, CacheError, TargetError};
+#[cfg(feature = "rayon")]
+use crate::installer::Builder;
+use crate::installer::Installer;
+use crate::observer::BuildObserver;
+use crate::progress::ProgressReporter;
+use crate::report::{BuildReport, TargetOutcome};
+use crate::shell::{Error as ShellCommandError, ExitReason, Pipeline, ShellCommand, ShellOutput, StreamLine};
+use crate::style::{Error as StyleError, InstallerStyle};
+use crate::spec::{Architecture, Effect, Named, OperatingSystem, Target};
+#[cfg(feature = "rayon")]
+use crate::spec::DEFAULT_RESOURCE_CLASS;
+use crate::view::{EffectView, ViewFilter};
 
 
 /***** HELPER FUNCTIONS *****/
@@ -84,6 +105,247 @@ fn test_project<S: AsRef<str>>(dir: impl AsRef<str>, package_name: impl AsRef<st
 
 
 
+/***** HELPER TARGETS *****/
+/// Defines an Effect that is always changed.
+struct ChangedEffect {
+    /// The name of the effect.
+    name : String,
+}
+
+impl Named for ChangedEffect {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+impl Effect for ChangedEffect {
+    #[inline]
+    fn has_changed(&self) -> Result<bool, Box<dyn Error + Send + Sync>> { Ok(true) }
+    #[inline]
+    fn commit_change(&self, _dry_run: bool) -> Result<(), Box<dyn Error + Send + Sync>> { Ok(()) }
+
+    #[inline]
+    fn as_any(&self) -> &dyn std::any::Any { self }
+}
+
+
+
+/// Defines a Target that does nothing, but that can have dependencies.
+struct DummyTarget {
+    /// The name of the target.
+    name    : String,
+    /// The dependencies of the target.
+    deps    : Vec<EffectView<'static>>,
+    /// The effects of the target.
+    effects : Vec<Box<dyn Effect>>,
+}
+
+impl DummyTarget {
+    /// Constructor for the DummyTarget that leaks it, such that it can be depended upon by other targets and registered in an Installer.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `deps`: The targets that this target depends on.
+    /// 
+    /// # Returns
+    /// A static reference to the new DummyTarget.
+    fn leaked(name: impl Into<String>, deps: &[&'static DummyTarget]) -> &'static Self {
+        Box::leak(Box::new(Self {
+            name    : name.into(),
+            deps    : deps.iter().copied().map(|d| d.view()).collect(),
+            effects : vec![],
+        }))
+    }
+
+    /// Constructor for the DummyTarget that leaks it, and that has a single effect which is always changed.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target. The effect will be called `<name>_effect`.
+    /// - `deps`: The targets that this target depends on.
+    /// 
+    /// # Returns
+    /// A static reference to the new DummyTarget.
+    fn leaked_changed(name: impl Into<String>, deps: &[&'static DummyTarget]) -> &'static Self {
+        let name: String = name.into();
+        Box::leak(Box::new(Self {
+            effects : vec![ Box::new(ChangedEffect{ name: format!("{}_effect", name) }) ],
+            name,
+            deps    : deps.iter().copied().map(|d| d.view()).collect(),
+        }))
+    }
+}
+
+impl Named for DummyTarget {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+impl Target for DummyTarget {
+    #[inline]
+    fn build(&self, _os: OperatingSystem, _arch: Architecture, _dry_run: bool) -> Result<(), TargetError> { Ok(()) }
+
+    #[inline]
+    fn deps(&self) -> &[EffectView<'_>] { &self.deps }
+    #[inline]
+    fn effects(&self) -> &[Box<dyn Effect>] { &self.effects }
+}
+
+
+
+/// Defines a Target that runs a ShellCommand when it is built.
+struct CommandTarget {
+    /// The name of the target.
+    name    : String,
+    /// The dependencies of the target.
+    deps    : Vec<EffectView<'static>>,
+    /// The effects of the target (always changed, such that dependencies are always built).
+    effects : Vec<Box<dyn Effect>>,
+    /// The command to run.
+    cmd     : ShellCommand,
+}
+
+impl CommandTarget {
+    /// Constructor for the CommandTarget that leaks it, such that it can be depended upon by other targets and registered in an Installer.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `deps`: The targets that this target depends on.
+    /// - `cmd`: The ShellCommand to run when built.
+    /// 
+    /// # Returns
+    /// A static reference to the new CommandTarget.
+    fn leaked(name: impl Into<String>, deps: &[&'static CommandTarget], cmd: ShellCommand) -> &'static Self {
+        let name: String = name.into();
+        Box::leak(Box::new(Self {
+            effects : vec![ Box::new(ChangedEffect{ name: format!("{}_effect", name) }) ],
+            name,
+            deps    : deps.iter().copied().map(|d| d.view()).collect(),
+            cmd,
+        }))
+    }
+}
+
+impl Named for CommandTarget {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+impl Target for CommandTarget {
+    fn build(&self, _os: OperatingSystem, _arch: Architecture, _dry_run: bool) -> Result<(), TargetError> {
+        match self.cmd.run_checked() {
+            Ok(_)    => Ok(()),
+            Err(err) => Err(TargetError::BuildError{ name: self.name.clone(), err: Box::new(err) }),
+        }
+    }
+
+    #[inline]
+    fn deps(&self) -> &[EffectView<'_>] { &self.deps }
+    #[inline]
+    fn effects(&self) -> &[Box<dyn Effect>] { &self.effects }
+}
+
+
+
+/// Defines a Target that sleeps for a while when it is built, while counting how many targets sharing its counters are being built at once.
+#[cfg(feature = "rayon")]
+struct SleepyTarget {
+    /// The name of the target.
+    name    : String,
+    /// The resource class of the target.
+    class   : String,
+    /// The dependencies of the target.
+    deps    : Vec<EffectView<'static>>,
+    /// The effects of the target (always changed, such that dependencies are always built).
+    effects : Vec<Box<dyn Effect>>,
+    /// The number of targets that are currently being built.
+    running : Arc<AtomicUsize>,
+    /// The highest number of targets that were ever being built at once.
+    max     : Arc<AtomicUsize>,
+}
+
+#[cfg(feature = "rayon")]
+impl SleepyTarget {
+    /// Constructor for the SleepyTarget that leaks it, such that it can be depended upon by other targets and registered in an Installer.
+    /// 
+    /// # Arguments
+    /// - `name`: The name of the target.
+    /// - `class`: The resource class of the target.
+    /// - `deps`: The targets that this target depends on.
+    /// - `running`: The counter of targets that are currently being built.
+    /// - `max`: The highest number of targets that were ever being built at once.
+    /// 
+    /// # Returns
+    /// A static reference to the new SleepyTarget.
+    fn leaked(name: impl Into<String>, class: impl Into<String>, deps: &[&'static SleepyTarget], running: &Arc<AtomicUsize>, max: &Arc<AtomicUsize>) -> &'static Self {
+        let name: String = name.into();
+        Box::leak(Box::new(Self {
+            effects : vec![ Box::new(ChangedEffect{ name: format!("{}_effect", name) }) ],
+            name,
+            class   : class.into(),
+            deps    : deps.iter().copied().map(|d| d.view()).collect(),
+            running : running.clone(),
+            max     : max.clone(),
+        }))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl Named for SleepyTarget {
+    #[inline]
+    fn name(&self) -> &str { &self.name }
+}
+#[cfg(feature = "rayon")]
+impl Target for SleepyTarget {
+    fn build(&self, _os: OperatingSystem, _arch: Architecture, _dry_run: bool) -> Result<(), TargetError> {
+        let running: usize = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max.fetch_max(running, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(50));
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    #[inline]
+    fn deps(&self) -> &[EffectView<'_>] { &self.deps }
+    #[inline]
+    fn effects(&self) -> &[Box<dyn Effect>] { &self.effects }
+    #[inline]
+    fn resource_class(&self) -> &str { &self.class }
+}
+
+
+
+/// Defines a BuildObserver that records all events it receives.
+struct RecordingObserver {
+    /// The events received so far.
+    events : Arc<Mutex<Vec<String>>>,
+}
+
+impl BuildObserver for RecordingObserver {
+    #[inline]
+    fn on_target_start(&self, name: &str) { self.events.lock().unwrap().push(format!("start {}", name)); }
+    #[inline]
+    fn on_target_finish(&self, name: &str, result: &Result<bool, TargetError>) { self.events.lock().unwrap().push(format!("finish {} {:?}", name, result.as_ref().ok())); }
+    #[inline]
+    fn on_effect_changed(&self, name: &str, effect: &str) { self.events.lock().unwrap().push(format!("changed {} {}", name, effect)); }
+}
+
+/// Defines a ProgressReporter that records all calls it receives.
+struct RecordingReporter {
+    /// The calls received so far.
+    events : Arc<Mutex<Vec<String>>>,
+}
+
+impl ProgressReporter for RecordingReporter {
+    #[inline]
+    fn start(&self, total: usize) { self.events.lock().unwrap().push(format!("start {}", total)); }
+    #[inline]
+    fn advance(&self, name: &str) { self.events.lock().unwrap().push(format!("advance {}", name)); }
+    #[inline]
+    fn finish(&self) { self.events.lock().unwrap().push("finish".into()); }
+    #[inline]
+    fn summary(&self, report: &BuildReport) { self.events.lock().unwrap().push(format!("summary {}", report.summary())); }
+}
+
+
+
+
+
 /***** TESTS *****/
 #[test]
 fn test_cargo() {
@@ -92,3 +354,1106 @@ fn test_cargo() {
         vec![ "build", "hello-world" ],
     ]);
 }
+
+#[test]
+fn test_build_levels() {
+    // Define a diamond with a loose target next to it
+    let a: &'static DummyTarget = DummyTarget::leaked("a", &[]);
+    let b: &'static DummyTarget = DummyTarget::leaked("b", &[ a ]);
+    let c: &'static DummyTarget = DummyTarget::leaked("c", &[ a ]);
+    let d: &'static DummyTarget = DummyTarget::leaked("d", &[ b, c ]);
+    let e: &'static DummyTarget = DummyTarget::leaked("e", &[]);
+    let installer: Installer = Installer::builder().add_target(d).add_target(c).add_target(b).add_target(a).add_target(e).build();
+
+    // Check the levels
+    assert_eq!(installer.build_levels(), vec![
+        vec![ "a".to_string(), "e".to_string() ],
+        vec![ "b".to_string(), "c".to_string() ],
+        vec![ "d".to_string() ],
+    ]);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_build_parallel() {
+    // Define a diamond, such that `a` is depended upon twice
+    let a: &'static DummyTarget = DummyTarget::leaked("a", &[]);
+    let b: &'static DummyTarget = DummyTarget::leaked("b", &[ a ]);
+    let c: &'static DummyTarget = DummyTarget::leaked("c", &[ a ]);
+    let d: &'static DummyTarget = DummyTarget::leaked("d", &[ b, c ]);
+    let events: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+    let installer: Installer = Installer::builder().add_target(a).add_target(b).add_target(c).add_target(d).observer(RecordingObserver{ events: events.clone() }).parallel(true).build();
+
+    // Every target should be built exactly once, and only after its dependencies have finished
+    installer.build("d", OperatingSystem::host(), Architecture::host(), true, false).unwrap();
+    let events: Vec<String> = events.lock().unwrap().clone();
+    let index = |event: &str| -> usize {
+        assert_eq!(events.iter().filter(|e| *e == event).count(), 1, "Expected '{}' exactly once in {:?}", event, events);
+        events.iter().position(|e| e == event).unwrap()
+    };
+    for name in [ "a", "b", "c", "d" ] { index(&format!("finish {} Some(true)", name)); }
+    assert_eq!(events.len(), 8);
+    assert!(index("finish a Some(true)") < index("start b"));
+    assert!(index("finish a Some(true)") < index("start c"));
+    assert!(index("finish b Some(true)") < index("start d"));
+    assert!(index("finish c Some(true)") < index("start d"));
+
+    // Failures should stop the build before the next level
+    let e: &'static CommandTarget = CommandTarget::leaked("e", &[], ShellCommand::shell("exit 1"));
+    let f: &'static CommandTarget = CommandTarget::leaked("f", &[ e ], ShellCommand::shell("exit 0"));
+    let installer: Installer = Installer::builder().add_target(e).add_target(f).parallel(true).build();
+    assert!(matches!(installer.build("f", OperatingSystem::host(), Architecture::host(), true, false), Err(BuildError::MakeError{ err: TargetError::BuildError{ .. }, .. })));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_build_resource_limit() {
+    // Define a level of heavy and light targets under a common root
+    let (heavy_running, heavy_max): (Arc<AtomicUsize>, Arc<AtomicUsize>) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let (light_running, light_max): (Arc<AtomicUsize>, Arc<AtomicUsize>) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let mut deps: Vec<&'static SleepyTarget> = vec![];
+    for i in 0..6 { deps.push(SleepyTarget::leaked(format!("heavy{}", i), "heavy", &[], &heavy_running, &heavy_max)); }
+    for i in 0..4 { deps.push(SleepyTarget::leaked(format!("light{}", i), DEFAULT_RESOURCE_CLASS, &[], &light_running, &light_max)); }
+    let root: &'static SleepyTarget = SleepyTarget::leaked("root", DEFAULT_RESOURCE_CLASS, &deps, &light_running, &light_max);
+    let mut builder: Builder = Installer::builder().add_target(root).parallel(true).resource_limit("heavy", 2);
+    for dep in deps { builder = builder.add_target(dep); }
+    let installer: Installer = builder.build();
+
+    // At most two heavy targets may have been built at once, while all of them have been built
+    installer.build("root", OperatingSystem::host(), Architecture::host(), true, false).unwrap();
+    assert_eq!(heavy_running.load(Ordering::SeqCst), 0);
+    assert!((1..=2).contains(&heavy_max.load(Ordering::SeqCst)), "Expected at most 2 heavy targets at once, got {}", heavy_max.load(Ordering::SeqCst));
+
+    // A limit of 0 is treated as 1
+    heavy_max.store(0, Ordering::SeqCst);
+    let heavy: Vec<&'static SleepyTarget> = (0..3).map(|i| SleepyTarget::leaked(format!("heavy{}", i), "heavy", &[], &heavy_running, &heavy_max)).collect();
+    let root: &'static SleepyTarget = SleepyTarget::leaked("root", DEFAULT_RESOURCE_CLASS, &heavy, &light_running, &light_max);
+    let installer: Installer = Installer::builder().add_target(root).add_target(heavy[0]).add_target(heavy[1]).add_target(heavy[2]).parallel(true).resource_limit("heavy", 0).build();
+    installer.build("root", OperatingSystem::host(), Architecture::host(), true, false).unwrap();
+    assert_eq!(heavy_max.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_to_mermaid() {
+    let a: &'static DummyTarget = DummyTarget::leaked("a", &[]);
+    let b: &'static DummyTarget = DummyTarget::leaked("b", &[ a ]);
+    let c: &'static DummyTarget = DummyTarget::leaked("c-1", &[ a, b ]);
+    let installer: Installer = Installer::builder().add_target(a).add_target(b).add_target(c).build();
+
+    // Check the graph contains the expected lines
+    let graph: String = installer.to_mermaid();
+    let lines: Vec<&str> = graph.lines().map(|l| l.trim()).collect();
+    assert_eq!(lines[0], "graph TD");
+    assert!(lines.contains(&"c_1[\"c-1\"]"));
+    assert!(lines.contains(&"b --> a"));
+    assert!(lines.contains(&"c_1 --> a"));
+    assert!(lines.contains(&"c_1 --> b"));
+    assert_eq!(lines.len(), 1 + 3 + 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_run() {
+    // The exit code should be returned as-is
+    assert_eq!(ShellCommand::with_args("sh", [ "-c", "exit 3" ]).run().unwrap(), 3);
+
+    // Writing to both streams should work when they are merged
+    let mut cmd: ShellCommand = ShellCommand::with_args("sh", [ "-c", "echo out; echo err >&2" ]);
+    cmd.set_merge_stderr(true);
+    assert_eq!(cmd.run().unwrap(), 0);
+
+    // Non-existing commands fail to spawn
+    assert!(ShellCommand::exec_only("/this/command/does/not/exist").run().is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_output() {
+    // Both streams should be captured separately, together with the code
+    let mut cmd: ShellCommand = ShellCommand::with_args("sh", [ "-c", "echo \"out $FOO\"; echo err >&2; exit 2" ]);
+    cmd.add_env("FOO", "bar");
+    let output: ShellOutput = cmd.output().unwrap();
+    assert_eq!(output.code(), 2);
+    assert!(!output.success());
+    assert_eq!(output.stdout_lossy(), "out bar\n");
+    assert_eq!(output.stderr_lossy(), "err\n");
+
+    // Or together, if they are merged
+    cmd.set_merge_stderr(true);
+    let output: ShellOutput = cmd.output().unwrap();
+    assert_eq!(output.stdout_lossy(), "out bar\nerr\n");
+    assert!(output.stderr.is_empty());
+
+    // Large outputs should not block the command
+    let output: ShellOutput = ShellCommand::with_args("sh", [ "-c", "head -c 1000000 /dev/zero" ]).output().unwrap();
+    assert!(output.success());
+    assert_eq!(output.stdout.len(), 1000000);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_exit_signal() {
+    // Normal exits should report their code
+    let status: ExitReason = ShellCommand::with_args("sh", [ "-c", "exit 3" ]).run_status().unwrap();
+    assert_eq!((status, status.signal(), status.code()), (ExitReason::Code(3), None, 3));
+
+    // Killed commands should report the signal, and map it to a code like the shell does
+    let cmd: ShellCommand = ShellCommand::with_args("sh", [ "-c", "kill -9 $$" ]);
+    let status: ExitReason = cmd.run_status().unwrap();
+    assert_eq!((status, status.signal(), status.code()), (ExitReason::Signal(9), Some(9), 137));
+    assert!(!status.success());
+    assert_eq!(cmd.run().unwrap(), 137);
+    let output: ShellOutput = cmd.output().unwrap();
+    assert_eq!((output.status, output.code()), (ExitReason::Signal(9), 137));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_cwd() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-shell-cwd");
+    std::fs::create_dir_all(&dir).unwrap();
+    let dir: PathBuf = dir.canonicalize().unwrap();
+
+    // Commands run in the current directory by default...
+    let output: ShellOutput = ShellCommand::exec_only("pwd").output().unwrap();
+    assert_eq!(PathBuf::from(output.stdout_lossy().trim_end()), std::env::current_dir().unwrap().canonicalize().unwrap());
+
+    // ...but in the given one if set
+    let cmd: ShellCommand = ShellCommand::with_cwd("pwd", &dir);
+    assert_eq!(cmd.cwd(), Some(dir.as_path()));
+    assert_eq!(PathBuf::from(cmd.output().unwrap().stdout_lossy().trim_end()), dir);
+    let mut cmd: ShellCommand = ShellCommand::with_args("sh", [ "-c", "test -d ../rust-build-test-shell-cwd" ]);
+    cmd.set_cwd(&dir);
+    assert_eq!(cmd.run().unwrap(), 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_stdin() {
+    // The data should be written to the command, after which its stdin is closed
+    let mut cmd: ShellCommand = ShellCommand::exec_only("cat");
+    cmd.set_stdin("Hello there!");
+    assert_eq!(cmd.output().unwrap().stdout_lossy(), "Hello there!");
+    assert_eq!(cmd.run().unwrap(), 0);
+
+    // Large inputs should not block the command, even if it also writes a lot
+    let mut cmd: ShellCommand = ShellCommand::exec_only("cat");
+    cmd.set_stdin(vec![ b'a'; 1000000 ]);
+    assert_eq!(cmd.output().unwrap().stdout.len(), 1000000);
+
+    // Commands that don't read their input shouldn't fail
+    let mut cmd: ShellCommand = ShellCommand::with_args("sh", [ "-c", "exit 0" ]);
+    cmd.set_stdin(vec![ b'a'; 1000000 ]);
+    assert_eq!(cmd.run().unwrap(), 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_run_streaming() {
+    // Every line should be given separately, marked with its stream
+    let cmd: ShellCommand = ShellCommand::with_args("sh", [ "-c", "echo one; echo two >&2; printf 'three\\r\\nfour'; exit 3" ]);
+    let mut lines: Vec<StreamLine> = vec![];
+    assert_eq!(cmd.run_streaming(|line| lines.push(line)).unwrap(), 3);
+    let stdout: Vec<&str> = lines.iter().filter_map(|l| if let StreamLine::Stdout(l) = l { Some(l.as_str()) } else { None }).collect();
+    assert_eq!(stdout, vec![ "one", "three", "four" ]);
+    assert_eq!(lines.iter().filter(|l| matches!(l, StreamLine::Stderr(_))).map(|l| l.line()).collect::<Vec<&str>>(), vec![ "two" ]);
+
+    // Lines should arrive while the command is still running
+    let cmd: ShellCommand = ShellCommand::with_args("sh", [ "-c", "echo first; sleep 0.5; echo second" ]);
+    let start: Instant = Instant::now();
+    let mut times: Vec<Duration> = vec![];
+    assert_eq!(cmd.run_streaming(|_| times.push(start.elapsed())).unwrap(), 0);
+    assert_eq!(times.len(), 2);
+    assert!(times[0] < Duration::from_millis(400));
+
+    // Merged streams are all stdout
+    let mut cmd: ShellCommand = ShellCommand::with_args("sh", [ "-c", "echo out; echo err >&2" ]);
+    cmd.set_merge_stderr(true);
+    let mut lines: Vec<StreamLine> = vec![];
+    cmd.run_streaming(|line| lines.push(line)).unwrap();
+    assert_eq!(lines, vec![ StreamLine::Stdout("out".into()), StreamLine::Stdout("err".into()) ]);
+}
+
+#[test]
+fn test_shell_display() {
+    // Tokens with spaces or special characters should be quoted, including the executable and the values of environment variables
+    let mut cmd: ShellCommand = ShellCommand::with_args("/opt/my tools/build", [ "--out", "my dir/app", "$HOME", "a\tb" ]);
+    cmd.add_env("EMPTY", "");
+    cmd.add_env("RUSTFLAGS", "-C target-cpu=native");
+    assert_eq!(cmd.to_string(), "EMPTY='' RUSTFLAGS='-C target-cpu=native' '/opt/my tools/build' --out 'my dir/app' '$HOME' 'a\tb'");
+
+    // Plain tokens are left as-is
+    assert_eq!(ShellCommand::with_args("cargo", [ "build", "--features=a,b", "--target-dir", "./target" ]).to_string(), "cargo build --features=a,b --target-dir ./target");
+}
+
+#[test]
+fn test_shell_run_dry() {
+    // The command line should be rendered with environment variables and quotes where necessary
+    let mut cmd: ShellCommand = ShellCommand::with_args("/this/command/does/not/exist", [ "--release", "hello world", "it's", "" ]);
+    cmd.add_envs([ ("B", "2"), ("A", "1 2") ]);
+    assert_eq!(cmd.to_string(), "A='1 2' B=2 /this/command/does/not/exist --release 'hello world' 'it'\\''s' ''");
+
+    // It should not be run when doing a dry run
+    assert_eq!(cmd.run_dry(true).unwrap(), 0);
+    assert!(matches!(cmd.run_dry(false), Err(ShellCommandError::SpawnError{ .. })));
+    cmd.run_dry_timeout_checked(true, Some(Duration::ZERO)).unwrap();
+    assert!(matches!(cmd.run_dry_timeout_checked(false, None), Err(ShellCommandError::SpawnError{ .. })));
+
+    // Otherwise, it should be checked and killed once it times out
+    #[cfg(unix)]
+    {
+        assert!(matches!(ShellCommand::with_args("sh", [ "-c", "exit 3" ]).run_dry_timeout_checked(false, None), Err(ShellCommandError::NonZeroExit{ code: 3, .. })));
+        assert!(matches!(ShellCommand::with_args("sleep", [ "5" ]).run_dry_timeout_checked(false, Some(Duration::from_millis(100))), Err(ShellCommandError::Timeout{ .. })));
+    }
+}
+
+#[test]
+fn test_shell_from_str() {
+    // Words should be split on whitespace, respecting quotes
+    let cmd: ShellCommand = "sh  -c \"echo hi\"".parse().unwrap();
+    assert_eq!(cmd.exec(), "sh");
+    assert_eq!(cmd.args(), [ "-c".to_string(), "echo hi".to_string() ]);
+    let cmd: ShellCommand = "echo 'a \"b\"' \"c \\\"d\\\"\" e\\ f g'h'\"\" ''".parse().unwrap();
+    assert_eq!(cmd.args(), [ "a \"b\"", "c \"d\"", "e f", "gh", "" ]);
+
+    // Rendered commands should parse back to themselves
+    let cmd: ShellCommand = ShellCommand::with_args("cargo", [ "build", "it's a test", "" ]);
+    let parsed: ShellCommand = cmd.to_string().parse().unwrap();
+    assert_eq!((parsed.exec(), parsed.args()), (cmd.exec(), cmd.args()));
+
+    // Empty lines and unterminated quotes are errors
+    assert!(matches!("   ".parse::<ShellCommand>(), Err(ShellCommandError::EmptyCommandLine)));
+    assert!(matches!("echo 'hi".parse::<ShellCommand>(), Err(ShellCommandError::UnterminatedQuote{ .. })));
+    assert!(matches!("echo \"hi\\\"".parse::<ShellCommand>(), Err(ShellCommandError::UnterminatedQuote{ .. })));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_pipe() {
+    // The output of every stage should be given to the next
+    let pipeline: Pipeline = ShellCommand::with_args("echo", [ "hello" ]).pipe(ShellCommand::with_args("tr", [ "a-z", "A-Z" ]));
+    assert_eq!(pipeline.to_string(), "echo hello | tr a-z A-Z");
+    assert_eq!(pipeline.output().unwrap().stdout_lossy(), "HELLO\n");
+    assert_eq!(pipeline.run().unwrap(), 0);
+
+    // Including stdin data and merged stderr, for any number of stages
+    let mut first: ShellCommand = ShellCommand::with_args("sh", [ "-c", "cat; echo err >&2" ]);
+    first.set_stdin("in\n");
+    first.set_merge_stderr(true);
+    let pipeline: Pipeline = first.pipe(ShellCommand::with_args("sort", [ "-r" ])).pipe(ShellCommand::with_args("sh", [ "-c", "cat; exit 4" ]));
+    let output: ShellOutput = pipeline.output().unwrap();
+    assert_eq!((output.code(), output.stdout_lossy()), (4, "in\nerr\n".into()));
+
+    // Failures of earlier stages are reported as such
+    let pipeline: Pipeline = ShellCommand::with_args("sh", [ "-c", "exit 3" ]).pipe(ShellCommand::exec_only("cat"));
+    assert!(matches!(pipeline.run(), Err(ShellCommandError::PipelineStage{ index: 0, err }) if matches!(*err, ShellCommandError::NonZeroExit{ code: 3, .. })));
+    let pipeline: Pipeline = ShellCommand::exec_only("/this/command/does/not/exist").pipe(ShellCommand::exec_only("cat"));
+    assert!(matches!(pipeline.output(), Err(ShellCommandError::PipelineStage{ index: 0, .. })));
+
+    // But earlier stages that are cut off are not
+    let pipeline: Pipeline = ShellCommand::exec_only("yes").pipe(ShellCommand::with_args("head", [ "-n", "1" ]));
+    assert_eq!(pipeline.output().unwrap().stdout_lossy(), "y\n");
+}
+
+#[test]
+fn test_shell_error_source() {
+    // Errors caused by IO errors should chain to them
+    let err: ShellCommandError = ShellCommand::exec_only("/this/command/does/not/exist").run().unwrap_err();
+    assert!(err.to_string().contains("/this/command/does/not/exist"));
+    let source: &std::io::Error = err.source().and_then(|err| err.downcast_ref()).expect("Error has no IO error as source");
+    assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+
+    // Others are the root cause themselves
+    assert!(ShellCommandError::NonZeroExit{ exec: "cargo".into(), code: 1 }.source().is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_remove_env() {
+    std::env::set_var("RUST_BUILD_TEST_REMOVE_ENV", "1");
+
+    // The variable should be inherited by default
+    let check: &str = "test -n \"${RUST_BUILD_TEST_REMOVE_ENV+x}\"";
+    assert_eq!(ShellCommand::with_args("sh", [ "-c", check ]).run().unwrap(), 0);
+
+    // But not when removed, even if it was explicitly added before
+    let mut cmd: ShellCommand = ShellCommand::with_args("sh", [ "-c", check ]);
+    cmd.add_env("RUST_BUILD_TEST_REMOVE_ENV", "2");
+    cmd.remove_env("RUST_BUILD_TEST_REMOVE_ENV");
+    assert_eq!(cmd.run().unwrap(), 1);
+
+    // Adding it again afterwards overrides the removal
+    cmd.add_env("RUST_BUILD_TEST_REMOVE_ENV", "3");
+    assert_eq!(cmd.run().unwrap(), 0);
+
+    // Nothing is inherited at all when the whole environment is cleared
+    let mut cmd: ShellCommand = ShellCommand::exec_only("/usr/bin/env");
+    cmd.env_clear();
+    cmd.add_env("FOO", "bar");
+    assert_eq!(cmd.to_string(), "env -i FOO=bar /usr/bin/env");
+    assert_eq!(cmd.output().unwrap().stdout_lossy(), "FOO=bar\n");
+}
+
+#[test]
+fn test_build_observer() {
+    let a: &'static DummyTarget = DummyTarget::leaked_changed("a", &[]);
+    let b: &'static DummyTarget = DummyTarget::leaked("b", &[ a ]);
+    let events: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+    let installer: Installer = Installer::builder().add_target(a).add_target(b).observer(RecordingObserver{ events: events.clone() }).build();
+
+    // Building the root should report the dependency first, then the changed effect and then itself
+    installer.build("b", OperatingSystem::host(), Architecture::host(), false, false).unwrap();
+    assert_eq!(*events.lock().unwrap(), vec![
+        "start b".to_string(),
+        "start a".to_string(),
+        "finish a Some(false)".to_string(),
+        "changed b a_effect".to_string(),
+        "finish b Some(true)".to_string(),
+    ]);
+}
+
+#[test]
+fn test_progress_reporter() {
+    // Define a diamond, such that `a` is made twice
+    let a: &'static DummyTarget = DummyTarget::leaked("a", &[]);
+    let b: &'static DummyTarget = DummyTarget::leaked("b", &[ a ]);
+    let c: &'static DummyTarget = DummyTarget::leaked("c", &[ a ]);
+    let d: &'static DummyTarget = DummyTarget::leaked("d", &[ b, c ]);
+    let events: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+    let installer: Installer = Installer::builder().add_target(a).add_target(b).add_target(c).add_target(d).progress(RecordingReporter{ events: events.clone() }).build();
+
+    // Every target should be advanced exactly once
+    installer.build("d", OperatingSystem::host(), Architecture::host(), false, false).unwrap();
+    assert_eq!(*events.lock().unwrap(), vec![
+        "start 4".to_string(),
+        "advance a".to_string(),
+        "advance b".to_string(),
+        "advance c".to_string(),
+        "advance d".to_string(),
+        "finish".to_string(),
+        "summary a up-to-date, b up-to-date, c up-to-date, d up-to-date".to_string(),
+    ]);
+}
+
+#[test]
+fn test_style_from_file() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-style");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // Parse a TOML file that overrides only some of the fields
+    std::fs::write(dir.join("style.toml"), "success_colour = \"blue\"\nerror_symbol = \"!\"\n").unwrap();
+    let style: InstallerStyle = InstallerStyle::from_file(dir.join("style.toml")).unwrap();
+    assert_eq!(style, InstallerStyle{ success_colour: Color::Blue, error_symbol: "!".into(), ..Default::default() });
+
+    // The same should be possible in JSON
+    std::fs::write(dir.join("style.json"), "{ \"success_colour\": \"Blue\", \"error_symbol\": \"!\" }").unwrap();
+    assert_eq!(InstallerStyle::from_file(dir.join("style.json")).unwrap(), style);
+
+    // Unknown colours should be rejected
+    std::fs::write(dir.join("invalid.toml"), "error_colour = \"purple-ish\"\n").unwrap();
+    assert!(matches!(InstallerStyle::from_file(dir.join("invalid.toml")), Err(StyleError::UnknownColour{ .. })));
+}
+
+#[test]
+fn test_view_filters() {
+    let a: &'static DummyTarget = DummyTarget::leaked_changed("a", &[]);
+
+    // An empty list of filters lets everything pass
+    let view: EffectView = a.view().clear_filters();
+    assert_eq!(view.iter().map(|e| e.name()).collect::<Vec<&str>>(), vec![ "a_effect" ]);
+    assert_eq!(view.into_iter().count(), 1);
+
+    // A `None` blocks everything, regardless of what comes after it
+    let view: EffectView = a.view().clear_filters().add_filter(ViewFilter::None).add_filter(ViewFilter::All);
+    assert_eq!(view.iter().count(), 0);
+    assert_eq!(view.into_iter().count(), 0);
+}
+
+#[test]
+fn test_view_unique() {
+    let dup: &'static DummyTarget = Box::leak(Box::new(DummyTarget {
+        name    : "dup".into(),
+        deps    : vec![],
+        effects : vec![ Box::new(ChangedEffect{ name: "a".into() }), Box::new(ChangedEffect{ name: "b".into() }), Box::new(ChangedEffect{ name: "a".into() }) ],
+    }));
+
+    // By default, duplicates are yielded as-is
+    assert_eq!(dup.view().iter().map(|e| e.name()).collect::<Vec<&str>>(), vec![ "a", "b", "a" ]);
+
+    // But a unique view yields every name once, keeping the first
+    let view: EffectView = dup.view().unique();
+    assert_eq!(view.iter().map(|e| e.name()).collect::<Vec<&str>>(), vec![ "a", "b" ]);
+    assert_eq!(view.into_iter().map(|e| e.name()).collect::<Vec<&str>>(), vec![ "a", "b" ]);
+}
+
+#[test]
+fn test_view_case_insensitive() {
+    let app: &'static DummyTarget = Box::leak(Box::new(DummyTarget {
+        name    : "app".into(),
+        deps    : vec![],
+        effects : vec![ Box::new(ChangedEffect{ name: "App".into() }) ],
+    }));
+
+    // By default, names are matched exactly
+    assert_eq!(app.view().add_filter(ViewFilter::Allow{ names: vec![ "app".into() ], case_insensitive: false }).iter().count(), 0);
+    assert_eq!(app.view().add_filter(ViewFilter::Deny{ names: vec![ "app".into() ], case_insensitive: false }).iter().count(), 1);
+
+    // But they may ignore case
+    assert_eq!(app.view().add_filter(ViewFilter::Allow{ names: vec![ "app".into() ], case_insensitive: true }).iter().count(), 1);
+    assert_eq!(app.view().add_filter(ViewFilter::Deny{ names: vec![ "app".into() ], case_insensitive: true }).iter().count(), 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_run_checked() {
+    // Successful commands pass, failing commands report their code
+    ShellCommand::with_args("sh", [ "-c", "exit 0" ]).run_checked().unwrap();
+    assert!(matches!(ShellCommand::with_args("sh", [ "-c", "exit 1" ]).run_checked(), Err(ShellCommandError::NonZeroExit{ code: 1, .. })));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_check() {
+    ShellCommand::exec_only("true").check().unwrap();
+    assert!(matches!(ShellCommand::exec_only("false").check(), Err(ShellCommandError::NonZeroExit{ ref exec, code: 1 }) if exec == "false"));
+}
+
+#[test]
+fn test_shell_command_hash() {
+    let mut cmd: ShellCommand = ShellCommand::with_args("cargo", [ "build", "--release" ]);
+    cmd.add_envs([ ("A", "1"), ("B", "2") ]);
+    let mut same: ShellCommand = ShellCommand::with_args("cargo", [ "build", "--release" ]);
+    same.add_envs([ ("B", "2"), ("A", "1") ]);
+    assert_eq!(cmd.command_hash(), same.command_hash());
+
+    // Any change to what the command does should change its hash
+    let mut other: ShellCommand = cmd.clone();
+    other.add_args([ "--features", "full" ]);
+    assert_ne!(cmd.command_hash(), other.command_hash());
+    let mut other: ShellCommand = cmd.clone();
+    other.add_env("A", "2");
+    assert_ne!(cmd.command_hash(), other.command_hash());
+    let mut other: ShellCommand = cmd.clone();
+    other.set_cwd("/tmp");
+    assert_ne!(cmd.command_hash(), other.command_hash());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_run_with_retries() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-shell-retries");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // Fails twice (appending to the file every time), then succeeds
+    let mut cmd: ShellCommand = ShellCommand::with_args("sh", [ "-c", "echo x >> attempts; [ $(wc -l < attempts) -ge 3 ]" ]);
+    cmd.set_cwd(&dir);
+    assert_eq!(cmd.run_with_retries(5, Duration::from_millis(10)).unwrap(), 0);
+    assert_eq!(std::fs::read_to_string(dir.join("attempts")).unwrap().lines().count(), 3);
+
+    // Running out of attempts returns the last code
+    std::fs::remove_file(dir.join("attempts")).unwrap();
+    assert_eq!(cmd.run_with_retries(2, Duration::from_millis(10)).unwrap(), 1);
+    assert_eq!(std::fs::read_to_string(dir.join("attempts")).unwrap().lines().count(), 2);
+
+    // A single attempt is just a run
+    assert_eq!(ShellCommand::with_args("sh", [ "-c", "exit 4" ]).run_with_retries(1, Duration::from_secs(10)).unwrap(), 4);
+    assert!(matches!(ShellCommand::exec_only("rust-build-does-not-exist").run_with_retries(2, Duration::ZERO), Err(ShellCommandError::SpawnError{ .. })));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_build_with_deadline() {
+    let slow  : &'static CommandTarget = CommandTarget::leaked("slow", &[], ShellCommand::with_args("sleep", [ "5" ]));
+    let after : &'static CommandTarget = CommandTarget::leaked("after", &[ slow ], ShellCommand::with_args("true", [] as [&str; 0]));
+    let installer: Installer = Installer::builder().add_target(slow).add_target(after).build();
+
+    // The slow target should be killed once the deadline passes
+    let start: Instant = Instant::now();
+    let res: Result<(), BuildError> = installer.build_with_deadline(start + Duration::from_millis(200), "after", OperatingSystem::host(), Architecture::host(), true, false);
+    assert!(matches!(res, Err(BuildError::Timeout{ .. })));
+    assert!(start.elapsed() < Duration::from_secs(4));
+
+    // Pending targets are not started at all once the deadline passed
+    let res: Result<(), BuildError> = installer.build_with_deadline(Instant::now(), "after", OperatingSystem::host(), Architecture::host(), true, false);
+    assert!(matches!(res, Err(BuildError::Timeout{ .. })));
+
+    // Without a deadline, commands are no longer affected
+    assert_eq!(ShellCommand::with_args("sh", [ "-c", "sleep 0.3" ]).run().unwrap(), 0);
+}
+
+#[test]
+fn test_shell_interpreter() {
+    // The flag should follow the shell
+    assert_eq!(ShellCommand::with_shell("/bin/bash", "echo hi").args(), [ "-c".to_string(), "echo hi".to_string() ]);
+    assert_eq!(ShellCommand::with_shell("C:\\Windows\\System32\\cmd.exe", "echo hi").args(), [ "/C".to_string(), "echo hi".to_string() ]);
+    assert_eq!(ShellCommand::with_shell("pwsh", "echo hi").args(), [ "-Command".to_string(), "echo hi".to_string() ]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_shell_default_unix() {
+    // The variable is honoured, with `/bin/sh` as a fallback
+    let old: Option<std::ffi::OsString> = std::env::var_os("SHELL");
+    std::env::set_var("SHELL", "/bin/sh");
+    assert_eq!(ShellCommand::shell("exit 3").exec(), "/bin/sh");
+    assert_eq!(ShellCommand::shell("exit 3").run().unwrap(), 3);
+    std::env::remove_var("SHELL");
+    assert_eq!(ShellCommand::shell("exit 3").exec(), "/bin/sh");
+    if let Some(old) = old { std::env::set_var("SHELL", old); }
+}
+
+#[cfg(windows)]
+#[test]
+fn test_shell_default_windows() {
+    // The variable is honoured, with `cmd` as a fallback
+    let old: Option<std::ffi::OsString> = std::env::var_os("COMSPEC");
+    std::env::remove_var("COMSPEC");
+    assert_eq!(ShellCommand::shell("exit 3").exec(), "cmd");
+    assert_eq!(ShellCommand::shell("exit 3").run().unwrap(), 3);
+    if let Some(old) = old { std::env::set_var("COMSPEC", old); }
+}
+
+#[test]
+fn test_cache_read_only() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-read-only");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // Opening a cache read-only should not write anything to it, not even the lock file
+    let cache: Cache = Cache::read_only(&dir).unwrap();
+    assert!(cache.is_read_only());
+    assert!(!dir.join(LOCK_FILE).exists());
+    drop(cache.lock().unwrap());
+
+    // If there is a lock file, it should still be usable
+    Cache::new(&dir, false).unwrap();
+    assert!(dir.join(LOCK_FILE).is_file());
+    drop(Cache::read_only(&dir).unwrap().lock().unwrap());
+}
+
+#[test]
+fn test_cache_new_clean() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-clean");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+
+    // Populate a cache
+    let cache: Cache = Cache::new(&dir, true).unwrap();
+    cache.update_fingerprint("a", 42, false).unwrap();
+    cache.update_fingerprint("b", 42, false).unwrap();
+    assert_eq!(cache.get_fingerprint("a").unwrap(), Some(42));
+
+    // Opening it normally keeps the entries, but opening it clean does not
+    assert_eq!(Cache::new(&dir, false).unwrap().get_fingerprint("a").unwrap(), Some(42));
+    let cache: Cache = Cache::new_clean(&dir, false).unwrap();
+    assert_eq!(cache.get_fingerprint("a").unwrap(), None);
+    assert_eq!(cache.get_fingerprint("b").unwrap(), None);
+    assert!(dir.is_dir());
+    assert_eq!(std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().file_name()).collect::<Vec<_>>(), vec![ LOCK_FILE ]);
+
+    // Clearing reports how many entries it removed
+    cache.update_fingerprint("a", 42, false).unwrap();
+    cache.update_fingerprint("b", 42, false).unwrap();
+    assert_eq!(cache.clear().unwrap(), 2);
+    assert_eq!(cache.clear().unwrap(), 0);
+}
+
+#[test]
+fn test_build_report() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-report");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path: PathBuf = dir.join("report.json");
+
+    // A successful build should report the up-to-date dependency and the built root
+    let a: &'static DummyTarget = DummyTarget::leaked_changed("a", &[]);
+    let b: &'static DummyTarget = DummyTarget::leaked("b", &[ a ]);
+    let installer: Installer = Installer::builder().add_target(a).add_target(b).report(&path).build();
+    installer.build("b", OperatingSystem::host(), Architecture::host(), false, false).unwrap();
+    let report: BuildReport = serde_json::from_reader(std::fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!(report.target, "b");
+    assert!(report.success);
+    assert_eq!(report.targets.iter().map(|t| (t.name.as_str(), t.outcome)).collect::<Vec<_>>(), vec![ ("a", TargetOutcome::UpToDate), ("b", TargetOutcome::Built) ]);
+    assert_eq!(report.targets[1].changed_effects, vec![ "a_effect".to_string() ]);
+
+    // A failed build should still be reported
+    let fail: &'static CommandTarget = CommandTarget::leaked("fail", &[], ShellCommand::shell("exit 1"));
+    let installer: Installer = Installer::builder().add_target(fail).report(&path).build();
+    assert!(installer.build("fail", OperatingSystem::host(), Architecture::host(), true, false).is_err());
+    let report: BuildReport = serde_json::from_reader(std::fs::File::open(&path).unwrap()).unwrap();
+    assert!(!report.success);
+    assert_eq!(report.targets.len(), 1);
+    assert_eq!(report.targets[0].outcome, TargetOutcome::Failed);
+    assert!(report.targets[0].error.is_some());
+}
+
+#[test]
+fn test_build_summary() {
+    let a: &'static CommandTarget = CommandTarget::leaked("a", &[], ShellCommand::shell("sleep 0.2"));
+    let b: &'static CommandTarget = CommandTarget::leaked("b", &[ a ], ShellCommand::shell("exit 0"));
+    let c: &'static DummyTarget = DummyTarget::leaked("c", &[]);
+    let events: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(vec![]));
+    let installer: Installer = Installer::builder().add_target(a).add_target(b).add_target(c).progress(RecordingReporter{ events: events.clone() }).build();
+
+    // The slow target comes first, and the time spent on it should not be attributed to its dependent
+    installer.build("b", OperatingSystem::host(), Architecture::host(), true, false).unwrap();
+    let summary: String = events.lock().unwrap().last().unwrap().clone();
+    assert!(summary.starts_with("summary a 0.") && summary.ends_with("s, b 0.0s"), "Unexpected summary '{}'", summary);
+
+    // Skipped targets are shown as up-to-date
+    installer.build("c", OperatingSystem::host(), Architecture::host(), false, false).unwrap();
+    assert_eq!(events.lock().unwrap().last().unwrap(), "summary c up-to-date");
+}
+
+#[test]
+fn test_cache_gc() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-gc");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    let cache: Cache = Cache::new(&dir, true).unwrap();
+
+    // Track two files and a fingerprint
+    let entry: CacheEntry = CacheEntry{ last_edited: LastEditedTime::from_path(&dir).unwrap(), hash: None, command_hash: None };
+    cache.update_file("live.txt", &entry, false).unwrap();
+    cache.update_file("stale.txt", &entry, false).unwrap();
+    cache.update_fingerprint("a", 42, false).unwrap();
+
+    // Only the stale file entry should be removed
+    let live: HashSet<PathBuf> = HashSet::from([ PathBuf::from("live.txt") ]);
+    assert_eq!(cache.gc(&live).unwrap(), 1);
+    assert!(cache.get_file("live.txt").unwrap().is_some());
+    assert!(cache.get_file("stale.txt").unwrap().is_none());
+    assert_eq!(cache.get_fingerprint("a").unwrap(), Some(42));
+
+    // Running it again removes nothing
+    assert_eq!(cache.gc(&live).unwrap(), 0);
+}
+
+#[test]
+fn test_cache_gc_missing() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-gc-missing");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    let cache: Arc<Cache> = Arc::new(Cache::new(dir.join("cache"), true).unwrap().with_root(&dir));
+
+    // Track two files (one of which is gone), a directory and a fingerprint
+    let entry: CacheEntry = CacheEntry{ last_edited: LastEditedTime::from_path(&dir).unwrap(), hash: None, command_hash: None };
+    std::fs::write(dir.join("present.txt"), "Hello there!").unwrap();
+    std::fs::write(dir.join("removed.txt"), "General Kenobi!").unwrap();
+    cache.update_file(dir.join("present.txt"), &entry, false).unwrap();
+    cache.update_file(dir.join("removed.txt"), &entry, false).unwrap();
+    cache.update_file(dir.join("cache"), &entry, false).unwrap();
+    cache.update_fingerprint("a", 42, false).unwrap();
+    std::fs::remove_file(dir.join("removed.txt")).unwrap();
+
+    // Only the entry of the removed file should be collected, also while others are committing
+    let handle: std::thread::JoinHandle<()> = {
+        let cache: Arc<Cache> = cache.clone();
+        let entry: CacheEntry = entry.clone();
+        let path: PathBuf = dir.join("present.txt");
+        std::thread::spawn(move || for _ in 0..10 {
+            let _lock: CacheGuard = cache.lock().unwrap();
+            cache.update_file(&path, &entry, false).unwrap();
+        })
+    };
+    assert_eq!(cache.gc_missing().unwrap(), 1);
+    handle.join().unwrap();
+    assert!(cache.get_file(dir.join("present.txt")).unwrap().is_some());
+    assert!(cache.get_file(dir.join("removed.txt")).unwrap().is_none());
+    assert!(cache.get_file(dir.join("cache")).unwrap().is_some());
+    assert_eq!(cache.get_fingerprint("a").unwrap(), Some(42));
+
+    // Running it again removes nothing, and read-only caches cannot be collected
+    assert_eq!(cache.gc_missing().unwrap(), 0);
+    assert!(matches!(Cache::read_only(dir.join("cache")).unwrap().gc_missing(), Err(CacheError::CacheReadOnly{ .. })));
+}
+
+#[test]
+fn test_cache_list() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-list");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    let cache: Cache = Cache::new(&dir, true).unwrap();
+    assert!(cache.list().unwrap().is_empty());
+
+    // Populate it with files, a fingerprint and an entry without a path
+    let last_edited: LastEditedTime = LastEditedTime::from_path(&dir).unwrap();
+    cache.update_file("b.txt", CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+    cache.update_file("a.txt", CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+    cache.update_fingerprint("a", 42, false).unwrap();
+    std::fs::write(dir.join(Cache::hash(std::path::Path::new("old.txt")).to_string()), serde_json::to_string(&CacheEntry{ last_edited, hash: None, command_hash: None }).unwrap()).unwrap();
+    std::fs::write(dir.join("garbage"), "Hello there!").unwrap();
+
+    // Only the files with paths should be listed
+    let list: Vec<(PathBuf, CacheEntry)> = cache.list().unwrap();
+    assert_eq!(list.iter().map(|(path, _)| path.clone()).collect::<Vec<PathBuf>>(), vec![ PathBuf::from("a.txt"), PathBuf::from("b.txt") ]);
+    assert_eq!(list[0].1.last_edited, last_edited);
+}
+
+#[test]
+fn test_cache_export_import() {
+    let src: PathBuf = std::env::temp_dir().join("rust-build-test-cache-export");
+    let dst: PathBuf = std::env::temp_dir().join("rust-build-test-cache-import");
+    for dir in [ &src, &dst ] { if dir.exists() { std::fs::remove_dir_all(dir).unwrap(); } }
+
+    // Populate a cache and export it
+    let last_edited: LastEditedTime = LastEditedTime::from_path(src.parent().unwrap()).unwrap();
+    let cache: Cache = Cache::new(&src, true).unwrap();
+    cache.update_file("a.txt", CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+    cache.update_fingerprint("a", 42, false).unwrap();
+    let mut blob: Vec<u8> = vec![];
+    assert_eq!(cache.export(&mut blob).unwrap(), 2);
+
+    // Importing it into a new cache should restore all entries
+    let cache: Cache = Cache::new(&dst, true).unwrap();
+    assert_eq!(cache.import(blob.as_slice()).unwrap(), 2);
+    assert_eq!(cache.get_file("a.txt").unwrap().unwrap().last_edited, last_edited);
+    assert_eq!(cache.get_fingerprint("a").unwrap(), Some(42));
+    assert_eq!(cache.list().unwrap().len(), 1);
+
+    // Blobs of other versions are refused
+    let blob: String = String::from_utf8(blob).unwrap().replacen("\"version\":1", "\"version\":999", 1);
+    assert!(matches!(cache.import(blob.as_bytes()), Err(CacheError::CacheImportVersionError{ got: 999, .. })));
+}
+
+#[test]
+fn test_cache_clear() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-clear");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    let cache: Cache = Cache::new(&dir, true).unwrap();
+    let last_edited: LastEditedTime = LastEditedTime::from_path(&dir).unwrap();
+    cache.update_file("a.txt", CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+    cache.update_file("b.txt", CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+    cache.update_fingerprint("target", 42, false).unwrap();
+    std::fs::write(dir.join("README"), "Not an entry").unwrap();
+
+    // Clearing a single entry should only remove that one (and be fine if it's already gone)
+    cache.clear_entry("./a.txt").unwrap();
+    cache.clear_entry("a.txt").unwrap();
+    assert!(cache.get_file("a.txt").unwrap().is_none());
+    assert!(cache.get_file("b.txt").unwrap().is_some());
+
+    // Clearing everything should remove all entries, but leave the directory and other files
+    cache.clear().unwrap();
+    assert!(cache.get_file("b.txt").unwrap().is_none());
+    assert!(cache.get_fingerprint("target").unwrap().is_none());
+    assert!(dir.join("README").exists());
+
+    // Read-only caches cannot be cleared
+    assert!(matches!(Cache::read_only(&dir).unwrap().clear(), Err(CacheError::CacheReadOnly{ .. })));
+}
+
+#[test]
+fn test_cache_version() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-version");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    let cache: Cache = Cache::new(&dir, true).unwrap();
+    assert_eq!(cache.version(), FORMAT_VERSION);
+    let last_edited: LastEditedTime = LastEditedTime::from_path(&dir).unwrap();
+    cache.update_file("a.txt", CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+    cache.update_fingerprint("a", 42, false).unwrap();
+    assert!(cache.get_file("a.txt").unwrap().is_some());
+
+    // A cache with another version should not see the entries
+    let other: Cache = Cache::new(&dir, false).unwrap().with_version(FORMAT_VERSION + 1);
+    assert!(other.get_file("a.txt").unwrap().is_none());
+    assert!(other.get_fingerprint("a").unwrap().is_none());
+    assert!(other.list().unwrap().is_empty());
+
+    // Until it writes them itself, which hides them from the first cache in turn
+    other.update_file("a.txt", CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+    assert!(other.get_file("a.txt").unwrap().is_some());
+    assert!(cache.get_file("a.txt").unwrap().is_none());
+
+    // Entries without any version (i.e., from older versions) are a miss instead of an error
+    std::fs::write(dir.join(Cache::hash(std::path::Path::new("b.txt")).to_string()), serde_json::to_string(&CacheEntry{ last_edited, hash: None, command_hash: None }).unwrap()).unwrap();
+    assert!(cache.get_file("b.txt").unwrap().is_none());
+}
+
+#[test]
+fn test_cache_threads() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Cache>();
+
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-threads");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    let cache: Arc<Cache> = Arc::new(Cache::new(&dir, true).unwrap());
+    let last_edited: LastEditedTime = LastEditedTime::from_path(&dir).unwrap();
+
+    // Let several threads update both their own and a shared entry, while reading the shared one
+    let handles: Vec<std::thread::JoinHandle<()>> = (0..8).map(|i| {
+        let cache: Arc<Cache> = cache.clone();
+        std::thread::spawn(move || {
+            for _ in 0..50 {
+                cache.update_file(format!("{}.txt", i), CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+                cache.update_file("shared.txt", CacheEntry{ last_edited, hash: Some(format!("{:064}", i)), command_hash: None }, false).unwrap();
+                assert!(cache.get_file("shared.txt").unwrap().is_some());
+            }
+        })
+    }).collect();
+    for handle in handles { handle.join().unwrap(); }
+
+    // All entries should be intact
+    for i in 0..8 { assert_eq!(cache.get_file(format!("{}.txt", i)).unwrap().unwrap().last_edited, last_edited); }
+    assert_eq!(cache.get_file_hash("shared.txt").unwrap().unwrap().len(), 64);
+    assert_eq!(cache.list().unwrap().len(), 9);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_last_edited_recursive() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-last-edited-recursive");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    std::fs::create_dir_all(dir.join("nested")).unwrap();
+    std::fs::write(dir.join("nested").join("file.txt"), "Hello there!").unwrap();
+    std::os::unix::fs::symlink(&dir, dir.join("nested").join("loop")).unwrap();
+
+    // The most recent time in the tree should be found, without getting stuck in the loop
+    let later: std::time::SystemTime = std::time::SystemTime::now() + Duration::from_secs(10);
+    std::fs::File::options().write(true).open(dir.join("nested").join("file.txt")).unwrap().set_modified(later).unwrap();
+    let newest: LastEditedTime = LastEditedTime::from_path_recursive(&dir).unwrap();
+    assert_eq!(newest, LastEditedTime::from_path(dir.join("nested").join("file.txt")).unwrap());
+    assert!(newest > LastEditedTime::from_path(&dir).unwrap());
+
+    // Files are simply their own time
+    assert_eq!(LastEditedTime::from_path_recursive(dir.join("nested").join("file.txt")).unwrap(), newest);
+    assert!(LastEditedTime::from_path_recursive(dir.join("missing")).is_err());
+}
+
+#[test]
+fn test_cache_atomic_write() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-atomic-write");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    let cache: Cache = Cache::new(&dir, true).unwrap();
+    let last_edited: LastEditedTime = LastEditedTime::from_path(&dir).unwrap();
+    let temp_files = || std::fs::read_dir(&dir).unwrap().filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().ends_with(".tmp")).count();
+
+    // A successful write should not leave anything behind
+    cache.update_file("a.txt", CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+    assert!(cache.get_file("a.txt").unwrap().is_some());
+    assert_eq!(temp_files(), 0);
+
+    // If the entry cannot be replaced, the error should say so and the temporary file should be cleaned up
+    let entry: PathBuf = dir.join(Cache::hash(std::path::Path::new("b.txt")).to_string());
+    std::fs::create_dir_all(entry.join("blocker")).unwrap();
+    assert!(matches!(cache.update_file("b.txt", CacheEntry{ last_edited, hash: None, command_hash: None }, false), Err(CacheError::CacheEntryRenameError{ .. })));
+    assert_eq!(temp_files(), 0);
+}
+
+#[test]
+fn test_cache_lock() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-lock");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    let cache: Cache = Cache::new(&dir, true).unwrap();
+    assert!(dir.join(LOCK_FILE).is_file());
+
+    // While locked, others (i.e., another handle to the lock file as another process would have) cannot lock it
+    let guard: CacheGuard = cache.lock().unwrap();
+    let other: std::fs::File = std::fs::File::open(dir.join(LOCK_FILE)).unwrap();
+    assert!(matches!(other.try_lock(), Err(std::fs::TryLockError::WouldBlock)));
+
+    // Another cache on the same directory should block until it is released
+    let start: Instant = Instant::now();
+    let waiter: std::thread::JoinHandle<Duration> = {
+        let dir: PathBuf = dir.clone();
+        std::thread::spawn(move || {
+            let cache: Cache = Cache::new(&dir, false).unwrap();
+            let _guard: CacheGuard = cache.lock().unwrap();
+            start.elapsed()
+        })
+    };
+    std::thread::sleep(Duration::from_millis(200));
+    drop(guard);
+    assert!(waiter.join().unwrap() >= Duration::from_millis(200));
+
+    // The same should go for other processes (if we have a tool to try that with)
+    if Command::new("flock").arg("--version").output().is_ok() {
+        let guard: CacheGuard = cache.lock().unwrap();
+        let mut waiter: Child = Command::new("flock").arg(dir.join(LOCK_FILE)).arg("true").spawn().unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(waiter.try_wait().unwrap().is_none());
+        drop(guard);
+        assert!(waiter.wait().unwrap().success());
+    }
+
+    // Once released, it can be locked again; and starting clean should not remove the lock file
+    other.try_lock().unwrap();
+    other.unlock().unwrap();
+    Cache::new_clean(&dir, false).unwrap();
+    assert!(dir.join(LOCK_FILE).is_file());
+}
+
+#[test]
+fn test_cache_normalize() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-normalize");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    let cache: Cache = Cache::new(&dir, true).unwrap();
+
+    // Different spellings of the same path should normalize to the same one, even if they don't exist
+    assert_eq!(Cache::normalize("./target/debug/app"), PathBuf::from("target/debug/app"));
+    assert_eq!(Cache::normalize("target/release/../debug/./app"), PathBuf::from("target/debug/app"));
+    assert_eq!(Cache::normalize("../a/../../b"), PathBuf::from("../../b"));
+    assert_eq!(Cache::normalize("/../a"), PathBuf::from("/a"));
+    assert_eq!(Cache::normalize("./a/.."), PathBuf::from("."));
+
+    // As such, they should hit the same entry
+    let last_edited: LastEditedTime = LastEditedTime::from_path(&dir).unwrap();
+    cache.update_file("./target/debug/app", CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+    assert_eq!(cache.get_file("target/debug/app").unwrap().unwrap().last_edited, last_edited);
+    assert_eq!(cache.list().unwrap().iter().map(|(path, _)| path.clone()).collect::<Vec<PathBuf>>(), vec![ PathBuf::from("target/debug/app") ]);
+}
+
+#[test]
+fn test_cache_key() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-key");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    std::fs::create_dir_all(dir.join("project").join("src")).unwrap();
+    std::fs::write(dir.join("project").join("src").join("main.rs"), "fn main() {}").unwrap();
+    let cache: Cache = Cache::new(dir.join("cache"), true).unwrap();
+    let last_edited: LastEditedTime = LastEditedTime::from_path(&dir).unwrap();
+
+    // Relative paths (to the current directory) and absolute paths should hit the same entry
+    let cwd: PathBuf = std::env::current_dir().unwrap();
+    assert_eq!(cache.key("./Cargo.toml"), PathBuf::from("Cargo.toml"));
+    assert_eq!(cache.key(cwd.join("src").join("..").join("Cargo.toml")), PathBuf::from("Cargo.toml"));
+    cache.update_file(cwd.join("Cargo.toml"), CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+    assert!(cache.get_file("Cargo.toml").unwrap().is_some());
+
+    // Also for files that don't exist yet
+    cache.update_file("./target/rust-build-test-cache-key/app", CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+    assert!(cache.get_file(cwd.join("target").join("rust-build-test-cache-key").join("app")).unwrap().is_some());
+
+    // Files outside of the root are stored absolutely, unless we pick another root
+    let main: PathBuf = dir.join("project").join("src").join("main.rs");
+    assert_eq!(cache.key(&main), main.canonicalize().unwrap());
+    let cache: Cache = cache.with_root(dir.join("project"));
+    assert_eq!(cache.key(&main), PathBuf::from("src/main.rs"));
+    assert_eq!(cache.key(dir.join("project").join("target").join("app")), PathBuf::from("target/app"));
+
+    // Symlinks should be resolved too
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(dir.join("project"), dir.join("link")).unwrap();
+        cache.update_file(dir.join("link").join("src").join("main.rs"), CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+        assert!(cache.get_file(&main).unwrap().is_some());
+    }
+}
+
+#[test]
+fn test_cache_stats() {
+    let last_edited: LastEditedTime = LastEditedTime::from_path(std::env::temp_dir()).unwrap();
+    let entry: CacheEntry = CacheEntry{ last_edited, hash: None, command_hash: None };
+    let cache: Cache = Cache::with_backend(InMemoryBackend::new());
+    assert_eq!(cache.stats(), CacheStats::default());
+
+    // Lookups and (actual) writes should be counted, also by clones
+    assert!(cache.get_file("a.txt").unwrap().is_none());
+    cache.update_file("a.txt", &entry, true).unwrap();
+    cache.update_file("a.txt", &entry, false).unwrap();
+    assert!(cache.get_file_lenient("a.txt").unwrap().is_some());
+    cache.clone().update_files(&[ (PathBuf::from("b.txt"), entry.clone()), (PathBuf::from("c.txt"), entry) ], false).unwrap();
+    assert_eq!(cache.get_files(&[ PathBuf::from("b.txt"), PathBuf::from("d.txt") ]).unwrap().len(), 2);
+    assert_eq!(cache.stats(), CacheStats{ hits: 2, misses: 2, writes: 3 });
+    assert_eq!(cache.stats().to_string(), "2 hits, 2 misses, 3 writes");
+    assert_eq!(CacheStats{ hits: 1, misses: 1, writes: 0 }.to_string(), "1 hit, 1 miss, 0 writes");
+
+    // Other kinds of entries are not
+    cache.update_fingerprint("target", 42, false).unwrap();
+    assert_eq!(cache.get_fingerprint("target").unwrap(), Some(42));
+    assert_eq!(cache.stats(), CacheStats{ hits: 2, misses: 2, writes: 3 });
+}
+
+#[test]
+fn test_cache_batch() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-batch");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    let last_edited: LastEditedTime = LastEditedTime::from_path(std::env::temp_dir()).unwrap();
+    let entry = |i: usize| CacheEntry{ last_edited, hash: Some(format!("{:064}", i)), command_hash: None };
+
+    // Enough files to be read and written in parallel, in both kinds of backends
+    let files: Vec<PathBuf> = (0..100).map(|i| PathBuf::from(format!("file{}.txt", i))).collect();
+    for cache in [ Cache::new(&dir, true).unwrap(), Cache::with_backend(InMemoryBackend::new()) ] {
+        assert!(cache.get_files(&files).unwrap().iter().all(Option::is_none));
+
+        // Only write the even ones, one of them twice
+        let mut updates: Vec<(PathBuf, CacheEntry)> = files.iter().enumerate().filter(|(i, _)| i % 2 == 0).map(|(i, file)| (file.clone(), entry(i))).collect();
+        updates.push((files[0].clone(), entry(1000)));
+        cache.update_files(&updates, true).unwrap();
+        assert!(cache.get_files(&files).unwrap().iter().all(Option::is_none));
+        cache.update_files(&updates, false).unwrap();
+
+        // The results should be in-order, and agree with the one-by-one operations
+        let entries: Vec<Option<CacheEntry>> = cache.get_files(&files).unwrap();
+        assert_eq!(entries.len(), files.len());
+        for ((i, file), got) in files.iter().enumerate().zip(entries) {
+            let expected: Option<String> = if i == 0 { Some(format!("{:064}", 1000)) } else if i % 2 == 0 { Some(format!("{:064}", i)) } else { None };
+            assert_eq!(got.and_then(|entry| entry.hash), expected);
+            assert_eq!(cache.get_file(file).unwrap().and_then(|entry| entry.hash), expected);
+        }
+        assert_eq!(cache.list().unwrap().len(), 50);
+    }
+
+    // Read-only caches cannot be updated
+    assert!(matches!(Cache::read_only(&dir).unwrap().update_files(&[], false), Err(CacheError::CacheReadOnly{ .. })));
+}
+
+#[test]
+fn test_cache_backend() {
+    // A cache can live entirely in memory
+    let last_edited: LastEditedTime = LastEditedTime::from_path(std::env::temp_dir()).unwrap();
+    let cache: Cache = Cache::with_backend(InMemoryBackend::new());
+    assert!(cache.get_file("a.txt").unwrap().is_none());
+    cache.update_file("a.txt", CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+    cache.update_file("b.txt", CacheEntry{ last_edited, hash: None, command_hash: None }, true).unwrap();
+    cache.update_fingerprint("target", 42, false).unwrap();
+    assert_eq!(cache.get_file("a.txt").unwrap().map(|entry| entry.last_edited), Some(last_edited));
+    assert!(cache.get_file("b.txt").unwrap().is_none());
+    assert_eq!(cache.get_fingerprint("target").unwrap(), Some(42));
+    assert_eq!(cache.list().unwrap().len(), 1);
+    { let _lock: CacheGuard = cache.lock().unwrap(); }
+    cache.clear_entry("a.txt").unwrap();
+    assert!(cache.get_file("a.txt").unwrap().is_none());
+    cache.clear().unwrap();
+    assert!(cache.get_fingerprint("target").unwrap().is_none());
+
+    // The filesystem backend only considers files named like entries
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-backend");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("README"), "Not an entry").unwrap();
+    let backend: FsBackend = FsBackend::new(&dir);
+    assert!(backend.get(1).unwrap().is_none());
+    backend.put(1, &serde_json::json!({ "fingerprint": 42 })).unwrap();
+    assert_eq!(backend.get(1).unwrap(), Some(serde_json::json!({ "fingerprint": 42 })));
+    assert_eq!(backend.location(1), dir.join("1"));
+    assert_eq!(backend.ids().unwrap(), vec![ 1 ]);
+    assert!(backend.remove(1).unwrap());
+    assert!(!backend.remove(1).unwrap());
+    assert!(dir.join("README").exists());
+}
+
+#[test]
+fn test_cache_file_hash() {
+    let dir: PathBuf = std::env::temp_dir().join("rust-build-test-cache-file-hash");
+    if dir.exists() { std::fs::remove_dir_all(&dir).unwrap(); }
+    let cache: Cache = Cache::new(dir.join("cache"), true).unwrap();
+
+    // The hashes should match the known SHA-256 test vectors, also for inputs spanning multiple blocks or reads
+    for (contents, hash) in [
+        ("".to_string(), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+        ("abc".to_string(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+        ("abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq".to_string(), "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"),
+        ("a".repeat(1000000), "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"),
+    ] {
+        std::fs::write(dir.join("file.txt"), contents).unwrap();
+        assert_eq!(Cache::hash_file(dir.join("file.txt")).unwrap(), hash);
+    }
+    assert!(Cache::hash_file(dir.join("missing.txt")).is_err());
+
+    // Updating the hash should store it next to the last edited time
+    let last_edited: LastEditedTime = LastEditedTime::from_path(dir.join("file.txt")).unwrap();
+    assert_eq!(cache.get_file_hash(dir.join("file.txt")).unwrap(), None);
+    let hash: String = cache.update_file_hash(dir.join("file.txt"), CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+    assert_eq!(cache.get_file_hash(dir.join("file.txt")).unwrap(), Some(hash));
+    assert_eq!(cache.get_file(dir.join("file.txt")).unwrap().unwrap().last_edited, last_edited);
+
+    // Entries without a hash should still be readable
+    cache.update_file(dir.join("file.txt"), CacheEntry{ last_edited, hash: None, command_hash: None }, false).unwrap();
+    assert_eq!(cache.get_file_hash(dir.join("file.txt")).unwrap(), None);
+}
@@ -15,6 +15,8 @@
 //!   subset of effects produced by a target.
 // 
 
+use std::collections::HashSet;
+
 use crate::spec::{Effect, Target};
 
 
@@ -27,10 +29,10 @@ pub enum ViewFilter {
     /// Lets all effects pass (filters none of them out).
     All,
 
-    /// Applies a whitelist of names for effects to pass.
-    Allow{ names: Vec<String> },
-    /// Applies a blacklist of names for effects to block.
-    Deny{ names: Vec<String> },
+    /// Applies a whitelist of names for effects to pass. If `case_insensitive` is true, names are compared ignoring (ASCII) case.
+    Allow{ names: Vec<String>, case_insensitive: bool },
+    /// Applies a blacklist of names for effects to block. If `case_insensitive` is true, names are compared ignoring (ASCII) case.
+    Deny{ names: Vec<String>, case_insensitive: bool },
 }
 
 impl ViewFilter {
@@ -47,15 +49,15 @@ impl ViewFilter {
             None => false,
             All  => true,
 
-            Allow{ names } => {
+            Allow{ names, case_insensitive } => {
                 for n in names {
-                    if n == effect.name() { return true; }
+                    if names_match(n, effect.name(), *case_insensitive) { return true; }
                 }
                 false
             },
-            Deny{ names } => {
+            Deny{ names, case_insensitive } => {
                 for n in names {
-                    if n == effect.name() { return false; }
+                    if names_match(n, effect.name(), *case_insensitive) { return false; }
                 }
                 true
             }
@@ -65,12 +67,42 @@ impl ViewFilter {
 
 
 
+/// Returns whether the given names are the same.
+/// 
+/// # Arguments
+/// - `lhs`: The first name to compare.
+/// - `rhs`: The second name to compare.
+/// - `case_insensitive`: Whether to ignore (ASCII) case when comparing.
+/// 
+/// # Returns
+/// true if the names match, or false otherwise.
+#[inline]
+fn names_match(lhs: &str, rhs: &str, case_insensitive: bool) -> bool {
+    if case_insensitive { lhs.eq_ignore_ascii_case(rhs) } else { lhs == rhs }
+}
+
+/// Returns whether the given pipeline of filters will block all effects, i.e., whether it contains a `ViewFilter::None`.
+/// 
+/// # Arguments
+/// - `filters`: The list of filters to check.
+/// 
+/// # Returns
+/// true if no effect will make it through the filters, or false if some might.
+#[inline]
+fn blocks_all(filters: &[ViewFilter]) -> bool {
+    filters.iter().any(|f| matches!(f, ViewFilter::None))
+}
+
+
+
 /// Defines a consuming iterator over an EffectView.
 pub struct EffectViewIntoIter<'a> {
     /// The parent iterator of effects to iterator over.
     iter    : std::slice::Iter<'a, Box<dyn Effect>>,
     /// The list of filters to apply.
     filters : Vec<ViewFilter>,
+    /// The names of the effects we've already yielded, if we yield every name only once.
+    seen    : Option<HashSet<&'a str>>,
 }
 impl<'a> Iterator for EffectViewIntoIter<'a> {
     type Item = &'a Box<dyn Effect>;
@@ -92,6 +124,10 @@ impl<'a> Iterator for EffectViewIntoIter<'a> {
                 }
             }
             if !allowed { continue; }
+            // Skip duplicates if told to do so
+            if let Some(seen) = &mut self.seen {
+                if !seen.insert(next.name()) { continue; }
+            }
 
             // Return it if we made it through
             return Some(next);
@@ -105,6 +141,8 @@ pub struct EffectViewIter<'a, 'b> {
     iter    : std::slice::Iter<'a, Box<dyn Effect>>,
     /// The list of filters to apply.
     filters : &'b [ViewFilter],
+    /// The names of the effects we've already yielded, if we yield every name only once.
+    seen    : Option<HashSet<&'a str>>,
 }
 impl<'a, 'b> Iterator for EffectViewIter<'a, 'b> {
     type Item = &'a Box<dyn Effect>;
@@ -126,6 +164,10 @@ impl<'a, 'b> Iterator for EffectViewIter<'a, 'b> {
                 }
             }
             if !allowed { continue; }
+            // Skip duplicates if told to do so
+            if let Some(seen) = &mut self.seen {
+                if !seen.insert(next.name()) { continue; }
+            }
 
             // Return it if we made it through
             return Some(next);
@@ -173,15 +215,39 @@ impl<'a, 'b> Iterator for EffectViewIter<'a, 'b> {
 
 /***** LIBRARY *****/
 /// Defines an EffectView, which is a specific view on a Target's effects that another dependency has (so it doesn't have to dependent on all of its files).
+/// 
+/// The view's filters are applied as a pipeline, where an effect is only seen if it passes all of them. As such, a view with an empty list of filters sees all effects, while a view with a `ViewFilter::None` anywhere in its list sees none of them.
 #[derive(Clone)]
 pub struct EffectView<'a> {
     /// The parent target that we view.
     pub(crate) target  : &'a dyn Target,
     /// The list of filters to apply.
     pub(crate) filters : Vec<ViewFilter>,
+    /// Whether to yield effects with the same name only once.
+    pub(crate) unique  : bool,
 }
 
 impl<'a> EffectView<'a> {
+    /// Constructor for the EffectView that views all effects of the given target.
+    /// 
+    /// This is equivalent to `Target::view()`, except that it also works for Targets that are not `Sized` (e.g., `&dyn Target`).
+    /// 
+    /// # Arguments
+    /// - `target`: The Target to view the effects of.
+    /// 
+    /// # Returns
+    /// A new EffectView instance that lets all of the target's effects pass.
+    #[inline]
+    pub fn new(target: &'a dyn Target) -> Self {
+        Self {
+            target,
+            filters : vec![ ViewFilter::All ],
+            unique  : false,
+        }
+    }
+
+
+
     /// Adds a new filter to the view that can be used to restrict which effects we see.
     /// 
     /// When thinking about filters, think about a stream of effects. Every filter is then some operation to filter out some effects and keep others. Thus, the order of filters matter (since they are applied as a pipeline).
@@ -200,6 +266,34 @@ impl<'a> EffectView<'a> {
 
 
 
+    /// Removes all filters from the view, such that it sees all effects of its target again.
+    /// 
+    /// # Returns
+    /// The same TargetView as went in for chaining purposes.
+    #[inline]
+    pub fn clear_filters(self) -> Self {
+        let mut this = self;
+        this.filters.clear();
+        this
+    }
+
+
+
+    /// Makes the view yield every effect only once, identified by its name, even if its target (accidentally) lists it multiple times.
+    /// 
+    /// The first occurrence of an effect is kept. This is a safety net to prevent, e.g., committing the same effect twice.
+    /// 
+    /// # Returns
+    /// The same TargetView as went in for chaining purposes.
+    #[inline]
+    pub fn unique(self) -> Self {
+        let mut this = self;
+        this.unique = true;
+        this
+    }
+
+
+
     /// Returns an iterator over the surviving effects after all filters have been applied.
     #[inline]
     pub fn iter<'b>(&'b self) -> EffectViewIter<'a, 'b> { self.into_iter() }
@@ -211,9 +305,12 @@ impl<'a> IntoIterator for EffectView<'a> {
 
     #[inline]
     fn into_iter(self) -> Self::IntoIter {
+        // Don't bother iterating if nothing will pass anyway
+        let effects: &'a [Box<dyn Effect>] = if blocks_all(&self.filters) { &[] } else { self.target.effects() };
         EffectViewIntoIter {
-            iter    : self.target.effects().iter(),
+            iter    : effects.iter(),
             filters : self.filters,
+            seen    : if self.unique { Some(HashSet::new()) } else { None },
         }
     }
 }
@@ -223,9 +320,12 @@ impl<'a, 'b> IntoIterator for &'b EffectView<'a> {
 
     #[inline]
     fn into_iter(self) -> Self::IntoIter {
+        // Don't bother iterating if nothing will pass anyway
+        let effects: &'a [Box<dyn Effect>] = if blocks_all(&self.filters) { &[] } else { self.target.effects() };
         EffectViewIter {
-            iter    : self.target.effects().iter(),
+            iter    : effects.iter(),
             filters : &self.filters,
+            seen    : if self.unique { Some(HashSet::new()) } else { None },
         }
     }
 }